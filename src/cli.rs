@@ -1,16 +1,45 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "authkit")]
 #[command(author, version, about = "AuthKit database schema management CLI")]
 #[command(propagate_version = true)]
+#[command(arg_required_else_help = true)]
 pub struct Cli {
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
+
+    /// Describe every feature: tables and columns it creates, version, dependencies and config key
+    #[arg(long)]
+    pub help_features: bool,
 
     /// Enable verbose output
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Print only each command's result line, errors and warnings (no headers, progress or hints)
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Disable colored output (also honored via the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Error output format (json prints {"error": {"kind", "message", "version", "statement_index"}} to stderr)
+    #[arg(long, value_enum, global = true, default_value = "human")]
+    pub error_format: MessageFormat,
+
+    /// Load environment variables from this file instead of ./.env
+    #[arg(long, value_name = "PATH", global = true)]
+    pub env_file: Option<String>,
+
+    /// Apply the [env.<NAME>] profile of authkit.toml over its base settings
+    #[arg(long, value_name = "NAME", global = true)]
+    pub env: Option<String>,
+
+    /// Read by each command's connection options; clap passes values given here down to them
+    #[command(flatten)]
+    pub pool: PoolArgs,
 }
 
 #[derive(Subcommand)]
@@ -21,44 +50,180 @@ pub enum Commands {
     /// Generate migration SQL files based on enabled features
     Generate(GenerateArgs),
 
+    /// Export the enabled features as migrations for another migration tool
+    Export(ExportArgs),
+
     /// Apply pending migrations to the database
     Migrate(MigrateArgs),
 
     /// Show migration status
     Status(StatusArgs),
 
+    /// List applied migrations in the order they were applied
+    History(HistoryArgs),
+
+    /// Verify applied migrations against the current feature SQL
+    Verify(VerifyArgs),
+
+    /// Mark migrations up to a version as applied without running them
+    Baseline(BaselineArgs),
+
+    /// Roll back the most recently applied migration and apply it again
+    Redo(RedoArgs),
+
+    /// Reconcile the database with the config: apply enabled features, roll back disabled ones
+    Sync(SyncArgs),
+
+    /// Roll back applied migrations, newest first, down to a target version
+    #[command(visible_alias = "down")]
+    Rollback(RollbackArgs),
+
+    /// Add or remove individual tracking rows without running any SQL
+    Repair(RepairArgs),
+
+    /// Print checksums of the built-in feature SQL or compare them to migration files
+    DumpChecksums(DumpChecksumsArgs),
+
+    /// Print a hash of the enabled features' migrations, for comparing services
+    FeatureSetHash(FeatureSetHashArgs),
+
+    /// List every available feature with its version and dependencies
+    ListFeatures(ListFeaturesArgs),
+
+    /// Export the migration tracking table and detected AuthKit tables as JSON
+    Dump(DumpArgs),
+
+    /// Delete expired sessions, verification tokens and rate limit counters
+    CleanExpired(CleanExpiredArgs),
+
+    /// Check the database connection and report the server version and migration state
+    Doctor(DoctorArgs),
+
     /// Drop all AuthKit tables (destructive)
     Destroy(DestroyArgs),
 
     /// Display current schema
     Schema(SchemaArgs),
+
+    /// Compare the schema the enabled features expect against the database
+    Diff(DiffArgs),
+
+    /// Maintain the migration tracking table
+    Tracking(TrackingArgs),
 }
 
 #[derive(Parser)]
 pub struct InitArgs {
-    /// Target database type
-    #[arg(long, value_enum, default_value = "postgres")]
-    pub db: DatabaseType,
+    /// Target database type [default: postgres; with --merge, the config's type is kept]
+    #[arg(long, value_enum)]
+    pub db: Option<DatabaseType>,
 
     /// Output path for config file
-    #[arg(long, default_value = "./authkit.toml")]
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
     pub output: String,
 
     /// Overwrite existing config file
     #[arg(long)]
     pub force: bool,
+
+    /// Update an existing config in place: set --db and enable --features, keeping every other setting and comment
+    #[arg(long, conflicts_with = "force")]
+    pub merge: bool,
+
+    /// Enable these features in the new config, with their dependencies (repeatable or comma-separated)
+    #[arg(long, value_name = "FEATURE", value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// Print a commented example config documenting every feature flag to stdout instead of writing a file
+    #[arg(long, conflicts_with_all = ["output", "force", "merge", "features"])]
+    pub print_example: bool,
 }
 
 #[derive(Parser)]
 pub struct GenerateArgs {
     /// Path to authkit.toml config file
-    #[arg(long, default_value = "./authkit.toml")]
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Output directory for migration files
+    #[arg(long, default_value = "./migrations")]
+    pub output: String,
+
+    /// Overwrite existing files
+    #[arg(long)]
+    pub force: bool,
+
+    /// Remove AuthKit migration files in the output directory that this run doesn't produce
+    #[arg(long)]
+    pub clean: bool,
+
+    /// Print every file to stdout behind a `-- file: <name>` line instead of writing files
+    #[arg(long, conflicts_with_all = ["force", "clean", "skip_unchanged", "output_format"])]
+    pub stdout: bool,
+
+    /// Leave files that already have the expected content alone instead of failing
+    #[arg(long)]
+    pub skip_unchanged: bool,
+
+    /// Report format (json reports created, skipped and conflicting files)
+    #[arg(long, value_enum, default_value = "human")]
+    pub output_format: MessageFormat,
+
+    /// Filename prefix: zero-padded feature version, or a UTC timestamp (sqlx-cli style)
+    #[arg(long, value_enum, default_value = "version")]
+    pub naming: NamingScheme,
+
+    /// Write a single schema.up.sql/schema.down.sql instead of one pair per feature
+    #[arg(long)]
+    pub combined: bool,
+
+    /// Write every feature as one 001_authkit migration with a single tracking entry (fresh databases only)
+    #[arg(long, conflicts_with_all = ["combined", "feature", "naming"])]
+    pub squash: bool,
+
+    /// In the combined schema, place each table's indexes right after its CREATE TABLE
+    #[arg(long, requires = "combined")]
+    pub group_by_table: bool,
+
+    /// Fail if any index name is defined by more than one migration
+    #[arg(long)]
+    pub strict_sql: bool,
+
+    /// Line endings of the written .sql files
+    #[arg(long, value_enum, default_value = "lf")]
+    pub line_ending: LineEnding,
+
+    /// Start the written .sql files with a UTF-8 byte order mark
+    #[arg(long)]
+    pub bom: bool,
+
+    /// Only write this feature's up/down files; it must be enabled in the config
+    #[arg(long, value_name = "FEATURE", conflicts_with_all = ["combined", "clean"])]
+    pub feature: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct ExportArgs {
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
     pub config: String,
 
+    /// Migration tool whose file conventions to follow
+    #[arg(long, value_enum, default_value = "sqlx")]
+    pub format: ExportFormat,
+
     /// Output directory for migration files
     #[arg(long, default_value = "./migrations")]
     pub output: String,
 
+    /// Write reversible migrations (an .up.sql/.down.sql pair per feature) instead of up-only .sql files
+    #[arg(long)]
+    pub reversible: bool,
+
+    /// Filename prefix: zero-padded feature version, or a UTC timestamp
+    #[arg(long, value_enum, default_value = "version")]
+    pub naming: NamingScheme,
+
     /// Overwrite existing files
     #[arg(long)]
     pub force: bool,
@@ -66,28 +231,412 @@ pub struct GenerateArgs {
 
 #[derive(Parser)]
 pub struct MigrateArgs {
+    /// Database connection URL (repeat to migrate several databases)
+    #[arg(
+        long,
+        env = "AUTHKIT_DATABASE_URL",
+        required_unless_present = "db_url_file"
+    )]
+    pub db_url: Vec<String>,
+
+    /// File with one database URL per line (blank lines and # comments are ignored)
+    #[arg(long, value_name = "PATH")]
+    pub db_url_file: Option<String>,
+
+    /// Keep going after a failed migration or database, and report every failure at the end
+    #[arg(long)]
+    pub continue_on_error: bool,
+
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Show what would be executed without applying
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Abort before applying if any migration is missing or has a checksum mismatch
+    #[arg(long)]
+    pub require_clean: bool,
+
+    /// Fail with the first checksum mismatch of an applied migration before running anything
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Record pending migrations whose tables and columns already exist instead of running them
+    #[arg(long)]
+    pub baseline_existing: bool,
+
+    /// Only warn when the database URL's type differs from the config's [database] type
+    #[arg(long)]
+    pub allow_type_mismatch: bool,
+
+    /// Record pending migrations as applied without running their SQL (for schema changes made out-of-band)
+    #[arg(long, conflicts_with_all = ["dry_run", "baseline_existing", "print_plan"])]
+    pub fake: bool,
+
+    /// Re-run the up SQL of applied migrations whose tables or columns were dropped, keeping their tracking rows
+    #[arg(long, conflicts_with_all = ["print_plan", "plan_file"])]
+    pub repair: bool,
+
+    /// Abort if the database contains any tables that don't belong to AuthKit
+    #[arg(long, overrides_with = "allow_dirty")]
+    pub require_empty: bool,
+
+    /// Allow migrating a database with unrelated tables, or with base tables but no migration records (overrides --require-empty)
+    #[arg(long, overrides_with = "require_empty")]
+    pub allow_dirty: bool,
+
+    /// Transaction scope: one per migration, or one around all pending migrations
+    #[arg(long, value_enum, default_value = "per-migration")]
+    pub transaction_mode: TransactionMode,
+
+    /// PostgreSQL: build indexes with CREATE INDEX CONCURRENTLY, after each migration's other statements commit
+    #[arg(long)]
+    pub concurrent_indexes: bool,
+
+    /// Only report users whose emails differ by case or whitespace, without migrating
+    #[arg(long)]
+    pub report_duplicates: bool,
+
+    /// Seconds to wait for a concurrent migrate run to finish (0 fails immediately)
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    pub lock_timeout: u64,
+
+    /// Write a JSON report of applied, failed and unattempted migrations to this path
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<String>,
+
+    /// Write a Prometheus textfile with schema metrics to this path after migrating
+    #[arg(long, value_name = "PATH")]
+    pub emit_metrics: Option<String>,
+
+    /// Retry this many times when SQLite reports the database as locked
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub retry_on_lock: u32,
+
+    /// Instead of the progress bar, write one JSON event per migration start and finish to stderr
+    #[arg(long)]
+    pub progress_json: bool,
+
+    /// Print the pending migrations, their checksums and the statements they execute without applying them
+    #[arg(long, conflicts_with = "plan_file")]
+    pub print_plan: bool,
+
+    /// With --print-plan, also write the plan as JSON to this file for review
+    #[arg(long, value_name = "PATH", requires = "print_plan")]
+    pub plan_out: Option<String>,
+
+    /// Apply a reviewed plan, refusing if the pending migrations changed since it was made
+    #[arg(long, value_name = "PATH")]
+    pub plan_file: Option<String>,
+
+    /// Leave this feature's migration pending for this run (repeatable); the config is unchanged
+    #[arg(long, value_name = "FEATURE")]
+    pub skip_feature: Vec<String>,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+/// Pool options, accepted before or after the subcommand name
+#[derive(Args, Clone)]
+pub struct PoolArgs {
+    /// Maximum number of pooled connections to the database
+    #[arg(long, value_name = "N", global = true, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_connections: u32,
+
+    /// Abort any statement running longer than this many milliseconds (PostgreSQL only)
+    #[arg(long, value_name = "MS", global = true)]
+    pub statement_timeout: Option<u64>,
+}
+
+/// Connection retry options shared by commands that open a database
+#[derive(Args, Clone)]
+pub struct ConnectArgs {
+    /// Give up connecting after this many seconds
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub connect_timeout: u64,
+
+    /// Retry connection-level failures this many times, with exponential backoff
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub connect_retries: u32,
+
+    /// PostgreSQL schema holding the AuthKit tables (overrides [database] schema; default: public)
+    #[arg(long, value_name = "NAME")]
+    pub schema: Option<String>,
+
+    #[command(flatten)]
+    pub pool: PoolArgs,
+
+    /// TLS mode for PostgreSQL, overriding `sslmode` in the URL (default: prefer)
+    #[arg(long, value_enum)]
+    pub ssl_mode: Option<SslMode>,
+
+    /// CA certificate used to verify the PostgreSQL server, overriding `sslrootcert` in the URL
+    #[arg(long, value_name = "PATH")]
+    pub ssl_root_cert: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct StatusArgs {
     /// Database connection URL
     #[arg(long, env = "AUTHKIT_DATABASE_URL")]
     pub db_url: String,
 
     /// Path to authkit.toml config file
-    #[arg(long, default_value = "./authkit.toml")]
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
     pub config: String,
 
-    /// Show what would be executed without applying
+    /// Retry this many times when SQLite reports the database as locked
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub retry_on_lock: u32,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "human")]
+    pub format: StatusFormat,
+
+    /// How to show the Applied At column
+    #[arg(long, value_enum, default_value = "utc")]
+    pub timezone: TimeFormat,
+
+    /// Exit non-zero when any migration is pending or missing
+    #[arg(long)]
+    pub check: bool,
+
+    /// Always exit 0 whatever the migration state, even with --check (advisory pipelines)
+    #[arg(long, visible_alias = "exit-zero-on-pending")]
+    pub exit_zero: bool,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct HistoryArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "table")]
+    pub format: HistoryFormat,
+
+    /// Only show the N most recently applied migrations
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct VerifyArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "human")]
+    pub format: ReportFormat,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct RedoArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Redo the base migration even if the users table has rows
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+#[command(disable_version_flag = true)]
+pub struct BaselineArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Record every enabled feature migration up to and including this version
+    #[arg(long)]
+    pub version: u32,
+
+    /// Baseline even if the tracking table already has rows
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct SyncArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Skip confirmation prompt
+    #[arg(long)]
+    pub force: bool,
+
+    /// Show the migrations that would be rolled back and applied without changing anything
     #[arg(long)]
     pub dry_run: bool,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
 }
 
 #[derive(Parser)]
-pub struct StatusArgs {
+pub struct RollbackArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Keep migrations up to this version; 0 rolls back everything including the base migration
+    /// (default: only the most recent migration)
+    #[arg(long, value_name = "VERSION")]
+    pub to: Option<u32>,
+
+    /// Skip confirmation prompt
+    #[arg(long)]
+    pub force: bool,
+
+    /// Show the migrations that would be rolled back without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print the down SQL statements each migration would run, without running them
+    #[arg(long)]
+    pub print_plan: bool,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct RepairArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Record this enabled migration version as applied (repeatable)
+    #[arg(
+        long,
+        value_name = "VERSION",
+        required_unless_present = "mark_unapplied"
+    )]
+    pub mark_applied: Vec<u32>,
+
+    /// Delete the tracking row for this version (repeatable)
+    #[arg(long, value_name = "VERSION")]
+    pub mark_unapplied: Vec<u32>,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct DoctorArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct CleanExpiredArgs {
     /// Database connection URL
     #[arg(long, env = "AUTHKIT_DATABASE_URL")]
     pub db_url: String,
 
+    /// Only count the expired rows
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Prefix of the AuthKit table names, as set by [database] table_prefix
+    #[arg(long, visible_alias = "prefix", value_name = "PREFIX")]
+    pub table_prefix: Option<String>,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct DumpArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Prefix of the AuthKit table names, as set by [database] table_prefix
+    #[arg(long, visible_alias = "prefix", value_name = "PREFIX")]
+    pub table_prefix: Option<String>,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct FeatureSetHashArgs {
     /// Path to authkit.toml config file
-    #[arg(long, default_value = "./authkit.toml")]
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
     pub config: String,
+
+    /// Database type to hash (overrides config)
+    #[arg(long, value_enum)]
+    pub db: Option<DatabaseType>,
+}
+
+#[derive(Parser)]
+pub struct ListFeaturesArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value = "table")]
+    pub format: ListFormat,
+}
+
+#[derive(Parser)]
+pub struct DumpChecksumsArgs {
+    /// Database type whose SQL is checksummed
+    #[arg(long, value_enum)]
+    pub db: DatabaseType,
+
+    /// Compare the .up.sql files in this directory against the built-in SQL
+    #[arg(long, value_name = "DIR")]
+    pub compare_dir: Option<String>,
 }
 
 #[derive(Parser)]
@@ -99,12 +648,65 @@ pub struct DestroyArgs {
     /// Skip confirmation prompt
     #[arg(long)]
     pub force: bool,
+
+    /// Proceed without prompting only if this matches the database name in the URL
+    #[arg(long, value_name = "NAME", conflicts_with = "force")]
+    pub confirm_db: Option<String>,
+
+    /// Show which tables would be dropped without dropping them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print the DROP TABLE statements in the order they would run, without running them
+    #[arg(long)]
+    pub print_plan: bool,
+
+    /// Keep the tables of this feature (and the features it depends on), dropping the rest
+    #[arg(long, visible_alias = "keep", value_name = "FEATURE")]
+    pub keep_feature: Option<String>,
+
+    /// Prefix of the AuthKit table names, as set by [database] table_prefix
+    #[arg(long, visible_alias = "prefix", value_name = "PREFIX")]
+    pub table_prefix: Option<String>,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct TrackingArgs {
+    #[command(subcommand)]
+    pub command: TrackingCommand,
+}
+
+#[derive(Subcommand)]
+pub enum TrackingCommand {
+    /// Remove tracking rows for versions that no built-in feature uses
+    Prune(TrackingPruneArgs),
+}
+
+#[derive(Parser)]
+pub struct TrackingPruneArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Skip confirmation prompt
+    #[arg(long)]
+    pub force: bool,
+
+    /// Show which rows would be removed without removing them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
 }
 
 #[derive(Parser)]
 pub struct SchemaArgs {
     /// Path to authkit.toml config file
-    #[arg(long, default_value = "./authkit.toml")]
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
     pub config: Option<String>,
 
     /// Target database type (overrides config)
@@ -115,9 +717,59 @@ pub struct SchemaArgs {
     #[arg(long, value_enum, default_value = "sql")]
     pub format: OutputFormat,
 
+    /// In SQL output, place each table's indexes right after its CREATE TABLE
+    #[arg(long)]
+    pub group_by_table: bool,
+
+    /// Only show this feature's migration, whether or not the config enables it
+    #[arg(long, value_name = "FEATURE", conflicts_with_all = ["db_url", "against"])]
+    pub feature: Option<String>,
+
+    /// In SQL and table output, print each migration's down SQL after its up SQL
+    #[arg(long, conflicts_with_all = ["db_url", "against"])]
+    pub include_down: bool,
+
     /// Database URL (to show actual schema)
     #[arg(long, env = "AUTHKIT_DATABASE_URL")]
     pub db_url: Option<String>,
+
+    /// With --db-url, only show this table
+    #[arg(long, value_name = "NAME", requires = "db_url")]
+    pub table: Option<String>,
+
+    /// Print a diff of the feature SQL generated by this other config against --config
+    #[arg(
+        long,
+        value_name = "PATH",
+        alias = "diff-template",
+        conflicts_with_all = ["db_url", "format", "group_by_table"]
+    )]
+    pub against: Option<String>,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
+}
+
+#[derive(Parser)]
+pub struct DiffArgs {
+    /// Database connection URL
+    #[arg(long, env = "AUTHKIT_DATABASE_URL")]
+    pub db_url: String,
+
+    /// Path to authkit.toml config file
+    #[arg(long, env = "AUTHKIT_CONFIG", default_value = "./authkit.toml")]
+    pub config: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "table")]
+    pub format: DiffFormat,
+
+    /// Exit non-zero when the database schema differs from the enabled features
+    #[arg(long)]
+    pub check: bool,
+
+    #[command(flatten)]
+    pub connect: ConnectArgs,
 }
 
 #[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
@@ -135,9 +787,119 @@ impl std::fmt::Display for DatabaseType {
     }
 }
 
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum SslMode {
+    /// Never use TLS
+    Disable,
+    /// Use TLS when the server supports it
+    Prefer,
+    /// Always use TLS, without verifying the certificate
+    Require,
+    /// Always use TLS and verify the certificate against the CA
+    VerifyCa,
+    /// Like verify-ca, and also check the host name
+    VerifyFull,
+}
+
+impl std::fmt::Display for SslMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SslMode::Disable => write!(f, "disable"),
+            SslMode::Prefer => write!(f, "prefer"),
+            SslMode::Require => write!(f, "require"),
+            SslMode::VerifyCa => write!(f, "verify-ca"),
+            SslMode::VerifyFull => write!(f, "verify-full"),
+        }
+    }
+}
+
 #[derive(Clone, Copy, ValueEnum)]
 pub enum OutputFormat {
     Sql,
     Json,
     Table,
 }
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum ListFormat {
+    Table,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// sqlx-cli: <version>_<name>.sql, or .up.sql/.down.sql pairs when reversible
+    Sqlx,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum NamingScheme {
+    /// 001_base.up.sql
+    Version,
+    /// 20240115093000_base.up.sql
+    Timestamp,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix line endings (\n)
+    Lf,
+    /// Windows line endings (\r\n)
+    Crlf,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum TransactionMode {
+    /// Commit each migration separately
+    PerMigration,
+    /// Apply all pending migrations atomically
+    All,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum DiffFormat {
+    Table,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum StatusFormat {
+    Human,
+    /// Migration states, summary counts and schema version as JSON, for dashboards
+    Json,
+    /// JUnit XML with one testcase per migration, for CI test reporting
+    Junit,
+    /// version,name,status,applied_at rows, for spreadsheets
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum HistoryFormat {
+    Table,
+    Json,
+    /// version,name,status,applied_at rows, for spreadsheets
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// 2025-01-19 10:30:00 UTC
+    Utc,
+    /// The same in the local timezone, with its offset
+    Local,
+    /// RFC 3339: 2025-01-19T10:30:00Z
+    Iso,
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Human,
+    /// JUnit XML with one testcase per migration, for CI test reporting
+    Junit,
+}
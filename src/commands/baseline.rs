@@ -0,0 +1,67 @@
+use colored::Colorize;
+
+use crate::cli::BaselineArgs;
+use crate::config::AuthKitConfig;
+use crate::database::{ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::migrations::{get_migrations_for_database, runner::MigrationRunner};
+use crate::output::note;
+
+pub async fn run(args: BaselineArgs) -> CliResult<()> {
+    // Load configuration
+    let config = AuthKitConfig::load(&args.config)?;
+
+    let connect = ConnectOptions::from(&args.connect).with_config(&config);
+    let db = Database::connect(&args.db_url, &connect).await?;
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+
+    runner.ensure_migrations_table().await?;
+
+    let applied = runner.get_applied_migrations().await?;
+    if !applied.is_empty() && !args.force {
+        return Err(CliError::migration(format!(
+            "_authkit_migrations already has {} row(s). Use --force to baseline anyway.",
+            applied.len()
+        )));
+    }
+
+    // Checksums must match the dialect of the database being baselined
+    let available = get_migrations_for_database(&config, db.db_type)?;
+    let to_record: Vec<_> = available
+        .iter()
+        .filter(|m| m.version <= args.version)
+        .collect();
+
+    if to_record.is_empty() {
+        return Err(CliError::migration(format!(
+            "No enabled feature migrations at or below version {}",
+            args.version
+        )));
+    }
+
+    note!(
+        "Baselining to version {} {}",
+        args.version,
+        "(no SQL will be executed)".dimmed()
+    );
+    note!();
+
+    for migration in &to_record {
+        runner.baseline_migration(migration).await?;
+        note!(
+            "  {} {:03}_{}",
+            "Recorded".green(),
+            migration.version,
+            migration.name
+        );
+    }
+
+    note!();
+    println!(
+        "{} Marked {} migration(s) as applied",
+        "✓".green(),
+        to_record.len()
+    );
+
+    Ok(())
+}
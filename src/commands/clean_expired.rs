@@ -0,0 +1,55 @@
+use colored::Colorize;
+
+use crate::cli::CleanExpiredArgs;
+use crate::database::{table_prefix_arg, ConnectOptions, Database};
+use crate::error::CliResult;
+use crate::output::note;
+use crate::schema::prefixed;
+
+/// Tables with an `expires_at` column (unix seconds)
+const EXPIRING_TABLES: &[&str] = &[
+    "sessions",
+    "verification",
+    "rate_limits",
+    "phone_verification",
+    "refresh_tokens",
+    "magic_links",
+];
+
+pub async fn run(args: CleanExpiredArgs) -> CliResult<()> {
+    let prefix = table_prefix_arg(args.table_prefix.as_deref())?;
+    let db = Database::connect(&args.db_url, &ConnectOptions::from(&args.connect)).await?;
+    let now = chrono::Utc::now().timestamp();
+
+    if args.dry_run {
+        println!("{}", "Dry run - no rows will be deleted".yellow());
+        note!();
+    }
+
+    let mut total = 0;
+    for table in EXPIRING_TABLES {
+        let table = &prefixed(table, prefix);
+        if !db.table_exists(table).await? {
+            note!("  - {} (table not found, skipped)", table);
+            continue;
+        }
+
+        let count = if args.dry_run {
+            db.count_expired(table, now).await? as u64
+        } else {
+            db.delete_expired(table, now).await?
+        };
+        let verb = if args.dry_run { "expired" } else { "deleted" };
+        note!("  - {}: {} {} row(s)", table, count, verb);
+        total += count;
+    }
+
+    note!();
+    if args.dry_run {
+        println!("{} Found {} expired row(s)", "✓".green(), total);
+    } else {
+        println!("{} Deleted {} expired row(s)", "✓".green(), total);
+    }
+
+    Ok(())
+}
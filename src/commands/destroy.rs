@@ -1,40 +1,85 @@
+use std::time::Duration;
+
 use colored::Colorize;
 use dialoguer::Confirm;
 
 use crate::cli::DestroyArgs;
-use crate::database::Database;
+use crate::config::Feature;
+use crate::database::{database_name, table_prefix_arg, ConnectOptions, Database};
 use crate::error::{CliError, CliResult};
-
-/// AuthKit tables in order they should be dropped (respecting foreign key constraints)
-const AUTHKIT_TABLES: &[&str] = &[
-    "verification",
-    "sessions",
-    "accounts",
-    "users",
-    "_authkit_migrations",
-];
+use crate::migrations::lock::MigrationLock;
+use crate::migrations::runner::MigrationRunner;
+use crate::output::{self, note};
+use crate::schema::{
+    authkit_tables, get_feature_migration, objects, tables_required_by, MIGRATIONS_LOCK_TABLE,
+    MIGRATIONS_TABLE,
+};
 
 pub async fn run(args: DestroyArgs) -> CliResult<()> {
-    let db = Database::connect(&args.db_url).await?;
+    let keep = args
+        .keep_feature
+        .as_deref()
+        .map(Feature::parse)
+        .transpose()?;
+    let prefix = table_prefix_arg(args.table_prefix.as_deref())?;
+
+    // Check the typed confirmation before touching the database
+    if let Some(expected) = &args.confirm_db {
+        if database_name(&args.db_url).as_deref() != Some(expected.as_str()) {
+            eprintln!(
+                "{} --confirm-db '{}' does not match the database in --db-url",
+                "✗".red(),
+                expected
+            );
+            return Err(CliError::Cancelled);
+        }
+    }
+
+    let db = Database::connect(&args.db_url, &ConnectOptions::from(&args.connect)).await?;
+
+    // With --keep-feature the tracking table survives so the kept migrations stay recorded
+    let mut kept_tables = keep
+        .map(|feature| tables_required_by(feature, prefix))
+        .unwrap_or_default();
+    if keep.is_some() {
+        kept_tables.push(MIGRATIONS_TABLE.to_string());
+    }
 
     println!();
-    println!(
-        "{}",
-        "⚠️  WARNING: This will permanently delete all AuthKit tables and data!"
+    match keep {
+        Some(feature) => println!(
+            "{}",
+            format!(
+                "⚠️  WARNING: This will permanently delete all AuthKit tables and data except those used by {}!",
+                feature.config_key()
+            )
             .red()
             .bold()
-    );
+        ),
+        None => println!(
+            "{}",
+            "⚠️  WARNING: This will permanently delete all AuthKit tables and data!"
+                .red()
+                .bold()
+        ),
+    }
     println!();
 
     // Show tables and row counts
     println!("Tables to be dropped:");
     let mut tables_to_drop = Vec::new();
+    let mut total_rows = 0;
 
-    for table in AUTHKIT_TABLES {
-        if db.table_exists(table).await? {
-            let count = db.count_rows(table).await.unwrap_or(0);
+    // Tables are listed in drop order (respecting foreign key constraints)
+    for table in authkit_tables(prefix) {
+        if kept_tables.contains(&table) {
+            continue;
+        }
+        if db.table_exists(&table).await? {
+            let count = db.count_rows(&table).await.unwrap_or(0);
             println!("  - {} ({} rows)", table, count);
-            tables_to_drop.push(*table);
+            tables_to_drop.push(table);
+            total_rows += count;
         }
     }
 
@@ -46,9 +91,51 @@ pub async fn run(args: DestroyArgs) -> CliResult<()> {
     }
 
     println!();
+    println!("Total rows to be deleted: {}", total_rows);
+
+    if let Some(feature) = keep {
+        println!();
+        println!("Tables kept:");
+        for table in &kept_tables {
+            if db.table_exists(table).await? {
+                println!("  - {}", table);
+            }
+        }
+        println!();
+        println!(
+            "{} Dropped tables only reference the kept ones, so rows in kept tables stay consistent.",
+            "Note:".yellow()
+        );
+        println!(
+            "      On PostgreSQL, CASCADE also drops any of your own foreign keys or views that"
+        );
+        println!(
+            "      depend on the dropped tables. {} records for features",
+            MIGRATIONS_TABLE
+        );
+        println!(
+            "      outside {} are removed, so `authkit migrate` recreates them if still enabled.",
+            feature.config_key()
+        );
+    }
+
+    println!();
+
+    if args.print_plan {
+        println!("Destroy plan ({} table(s)):", tables_to_drop.len());
+        for table in &tables_to_drop {
+            println!("    {};", db.drop_table_sql(table)?);
+        }
+        println!();
+    }
+
+    if args.dry_run || args.print_plan {
+        println!("{}", "Dry run - no tables were dropped".yellow());
+        return Ok(());
+    }
 
-    // Confirm unless --force
-    if !args.force {
+    // Confirm unless --force or --confirm-db (already checked above)
+    if !args.force && args.confirm_db.is_none() {
         let confirmed = Confirm::new()
             .with_prompt("Are you sure you want to destroy all tables?")
             .default(false)
@@ -62,17 +149,95 @@ pub async fn run(args: DestroyArgs) -> CliResult<()> {
         }
     }
 
-    println!();
+    // Hold the migration lock so a concurrent migrate doesn't recreate tables mid-drop
+    let lock = MigrationLock::acquire(&db.pool, db.db_type, Duration::ZERO).await?;
+    let result = drop_tables(&db, &tables_to_drop, total_rows, keep).await;
+    lock.release_or_warn().await;
+    result
+}
+
+async fn drop_tables(
+    db: &Database,
+    tables_to_drop: &[String],
+    total_rows: i64,
+    keep: Option<Feature>,
+) -> CliResult<()> {
+    note!();
 
     // Drop tables in order (respecting foreign keys)
-    for table in &tables_to_drop {
-        print!("Dropping {}... ", table);
+    for table in tables_to_drop {
+        if !output::is_quiet() {
+            print!("Dropping {}... ", table);
+        }
         db.drop_table(table).await?;
-        println!("{}", "done".green());
+        note!("{}", "done".green());
+    }
+
+    // On SQLite, acquiring the lock may have just created its table
+    if !tables_to_drop.iter().any(|t| t == MIGRATIONS_LOCK_TABLE)
+        && db.table_exists(MIGRATIONS_LOCK_TABLE).await?
+    {
+        db.drop_table(MIGRATIONS_LOCK_TABLE).await?;
+    }
+
+    note!();
+    println!(
+        "Destroyed {} tables containing {} rows",
+        tables_to_drop.len(),
+        total_rows
+    );
+    note!();
+
+    match keep {
+        Some(feature) => {
+            forget_dropped_features(db, feature).await?;
+            println!(
+                "{} AuthKit tables destroyed, keeping {}",
+                "✓".green(),
+                feature.config_key()
+            );
+        }
+        None => println!("{} All AuthKit tables destroyed", "✓".green()),
     }
 
+    Ok(())
+}
+
+/// Remove the migration records of features whose tables were dropped.
+///
+/// A migration that also altered kept tables (added columns or indexes)
+/// stays recorded, since re-applying it would collide with what is left.
+async fn forget_dropped_features(db: &Database, kept: Feature) -> CliResult<()> {
+    if !db.table_exists(MIGRATIONS_TABLE).await? {
+        return Ok(());
+    }
+
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+    let applied = runner.get_applied_migrations().await?;
+    let required = kept.with_dependencies();
+
+    println!("Migration records in {}:", MIGRATIONS_TABLE);
+    for feature in Feature::all() {
+        if required.contains(feature) || !applied.iter().any(|m| m.version == feature.version()) {
+            continue;
+        }
+
+        let migration = get_feature_migration(*feature, db.db_type);
+        let objects = objects::parse(&migration.up_sql);
+        let name = format!("{:03}_{}", migration.version, migration.name);
+
+        if !objects.tables.is_empty() && objects.columns.is_empty() {
+            runner.remove_migration_record(migration.version).await?;
+            println!("  - {} removed", name);
+        } else {
+            println!(
+                "  - {} kept {}",
+                name,
+                "(it also altered kept tables, which still carry its changes)".yellow()
+            );
+        }
+    }
     println!();
-    println!("{} All AuthKit tables destroyed", "✓".green());
 
     Ok(())
 }
@@ -0,0 +1,78 @@
+use colored::Colorize;
+
+use crate::cli::{DiffArgs, DiffFormat};
+use crate::config::AuthKitConfig;
+use crate::database::{ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::migrations::get_migrations_for_database;
+use crate::schema::diff::{diff, expected_tables};
+
+pub async fn run(args: DiffArgs) -> CliResult<()> {
+    let config = AuthKitConfig::load(&args.config)?;
+
+    let connect = ConnectOptions::from(&args.connect)
+        .with_config(&config)
+        .read_only();
+    let db = Database::connect(&args.db_url, &connect).await?;
+
+    // Compare against the dialect of the connected database, not the config's type
+    let migrations = get_migrations_for_database(&config, db.db_type)?;
+    let expected = expected_tables(&migrations);
+    let actual = db.list_tables().await?;
+    let result = diff(&expected, &actual, config.table_prefix());
+
+    match args.format {
+        DiffFormat::Json => {
+            let json = serde_json::json!({
+                "in_sync": result.is_empty(),
+                "missing_tables": result.missing_tables,
+                "extra_tables": result.extra_tables,
+                "missing_columns": result.missing_columns.iter().map(|(table, column)| {
+                    serde_json::json!({ "table": table, "column": column })
+                }).collect::<Vec<_>>(),
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).unwrap_or_default()
+            );
+        }
+        DiffFormat::Table => {
+            println!();
+            println!(
+                "Comparing {} against {}",
+                args.db_url.cyan(),
+                args.config.cyan()
+            );
+            println!();
+
+            if result.is_empty() {
+                println!(
+                    "{} Database schema matches the enabled features",
+                    "✓".green()
+                );
+                return Ok(());
+            }
+
+            for table in &result.missing_tables {
+                println!("  {} missing table {}", "✗".red(), table);
+            }
+            for (table, column) in &result.missing_columns {
+                println!("  {} missing column {}.{}", "✗".red(), table, column);
+            }
+            for table in &result.extra_tables {
+                println!(
+                    "  {} extra table {} (not created by any enabled feature)",
+                    "!".yellow(),
+                    table
+                );
+            }
+            println!();
+        }
+    }
+
+    if args.check && !result.is_empty() {
+        return Err(CliError::NotClean(result.problem_count()));
+    }
+
+    Ok(())
+}
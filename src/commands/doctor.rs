@@ -0,0 +1,42 @@
+use colored::Colorize;
+
+use crate::cli::DoctorArgs;
+use crate::database::{ConnectOptions, Database};
+use crate::error::CliResult;
+use crate::migrations::runner::MigrationRunner;
+use crate::schema::MIGRATIONS_TABLE;
+
+pub async fn run(args: DoctorArgs) -> CliResult<()> {
+    println!("Checking database connection...");
+    println!();
+
+    // Read-only: a missing SQLite file is reported instead of created
+    let options = ConnectOptions::from(&args.connect).read_only();
+    let db = match Database::connect(&args.db_url, &options).await {
+        Ok(db) => db,
+        Err(e) => {
+            println!("  {} Connection failed", "✗".red());
+            return Err(e);
+        }
+    };
+    println!("  {} Connected ({})", "✓".green(), db.db_type);
+
+    let version = db.server_version().await?;
+    println!("  {} Server version: {}", "✓".green(), version);
+
+    if !db.table_exists(MIGRATIONS_TABLE).await? {
+        println!(
+            "  {} {} not found (no migrations applied yet)",
+            "-".dimmed(),
+            MIGRATIONS_TABLE
+        );
+        return Ok(());
+    }
+    println!("  {} {} exists", "✓".green(), MIGRATIONS_TABLE);
+
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+    let applied = runner.get_applied_migrations().await?;
+    println!("  {} {} migration(s) applied", "✓".green(), applied.len());
+
+    Ok(())
+}
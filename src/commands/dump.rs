@@ -0,0 +1,48 @@
+use crate::cli::DumpArgs;
+use crate::database::{table_prefix_arg, ConnectOptions, Database};
+use crate::error::CliResult;
+use crate::migrations::runner::MigrationRunner;
+use crate::schema::{is_authkit_table, MIGRATIONS_LOCK_TABLE};
+
+pub async fn run(args: DumpArgs) -> CliResult<()> {
+    let prefix = table_prefix_arg(args.table_prefix.as_deref())?;
+    let db = Database::connect(
+        &args.db_url,
+        &ConnectOptions::from(&args.connect).read_only(),
+    )
+    .await?;
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+
+    // Read-only: an empty list if the tracking table doesn't exist yet
+    let applied = runner.get_applied_migrations().await?;
+
+    let tables: Vec<String> = db
+        .list_tables()
+        .await?
+        .into_iter()
+        .map(|t| t.name)
+        // The SQLite-only lock table is bookkeeping, not schema
+        .filter(|name| is_authkit_table(name, prefix) && name != MIGRATIONS_LOCK_TABLE)
+        .collect();
+
+    // Migrations are ordered by version and tables by name so dumps diff cleanly
+    let dump = serde_json::json!({
+        "database_type": db.db_type.to_string(),
+        "migrations": applied.iter().map(|m| {
+            serde_json::json!({
+                "version": m.version,
+                "name": m.name,
+                "applied_at": m.applied_at,
+                "checksum": m.checksum,
+            })
+        }).collect::<Vec<_>>(),
+        "tables": tables,
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&dump).unwrap_or_default()
+    );
+
+    Ok(())
+}
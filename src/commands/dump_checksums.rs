@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::cli::DumpChecksumsArgs;
+use crate::config::{Feature, IdType};
+use crate::error::{CliError, CliResult};
+use crate::migrations::{compute_checksum, Migration};
+use crate::schema::get_migrations_for_features;
+
+pub async fn run(args: DumpChecksumsArgs) -> CliResult<()> {
+    let migrations = get_migrations_for_features(Feature::all(), args.db, IdType::Text)?;
+
+    match &args.compare_dir {
+        Some(dir) => compare_dir(Path::new(dir), &migrations),
+        None => {
+            for migration in &migrations {
+                println!(
+                    "{:03} {:<20} {}",
+                    migration.version, migration.name, migration.checksum
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Recompute checksums of on-disk `.up.sql` files and compare them to the built-in SQL
+fn compare_dir(dir: &Path, migrations: &[Migration]) -> CliResult<()> {
+    let mut files: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".up.sql"))
+        .collect();
+    files.sort();
+
+    println!("Comparing {} against built-in SQL", dir.display());
+    println!();
+
+    if files.is_empty() {
+        println!("{} No .up.sql files found", "!".yellow());
+        return Ok(());
+    }
+
+    let mut mismatches = Vec::new();
+    for filename in &files {
+        let builtin = migrations
+            .iter()
+            .find(|m| format!("{:03}_{}.up.sql", m.version, m.name) == *filename);
+
+        let Some(migration) = builtin else {
+            println!(
+                "  {} {}: not a built-in feature, skipped",
+                "!".yellow(),
+                filename
+            );
+            continue;
+        };
+
+        let actual = compute_checksum(&fs::read_to_string(dir.join(filename))?);
+        if actual == migration.checksum {
+            println!("  {} {}", "✓".green(), filename);
+        } else {
+            println!("  {} {}: checksum mismatch", "✗".red(), filename);
+            println!("      expected: {}", migration.checksum);
+            println!("      actual:   {}", actual);
+            mismatches.push((migration.version, migration.checksum.clone(), actual));
+        }
+    }
+    println!();
+
+    if let Some((version, expected, actual)) = mismatches.into_iter().next() {
+        return Err(CliError::ChecksumMismatch {
+            version,
+            expected,
+            actual,
+        });
+    }
+
+    println!("{} All migration files match", "✓".green());
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::fs;
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::cli::{ExportArgs, ExportFormat, NamingScheme};
+use crate::config::AuthKitConfig;
+use crate::error::{CliError, CliResult};
+use crate::migrations::{get_migrations_from_config, Migration};
+use crate::output::note;
+
+pub async fn run(args: ExportArgs) -> CliResult<()> {
+    let config = AuthKitConfig::load(&args.config)?;
+    let migrations = get_migrations_from_config(&config)?;
+
+    if migrations.is_empty() {
+        println!("{} No features enabled. Nothing to export.", "!".yellow());
+        return Ok(());
+    }
+
+    let files = match args.format {
+        ExportFormat::Sqlx => sqlx_files(&migrations, args.reversible, args.naming),
+    };
+
+    let output_dir = Path::new(&args.output);
+    fs::create_dir_all(output_dir)?;
+
+    if !args.force {
+        if let Some((filename, _)) = files.iter().find(|(f, _)| output_dir.join(f).exists()) {
+            return Err(CliError::FileExists(
+                output_dir.join(filename).display().to_string(),
+            ));
+        }
+    }
+
+    note!(
+        "Exporting {} migrations in sqlx format to {}",
+        config.database_type()?,
+        output_dir.display()
+    );
+    note!();
+
+    for (filename, content) in &files {
+        fs::write(output_dir.join(filename), content)?;
+        note!("  {} {}", "Created".green(), filename);
+    }
+
+    note!();
+    println!(
+        "{} Exported {} migration files ({} features)",
+        "✓".green(),
+        files.len(),
+        migrations.len()
+    );
+    note!(
+        "  Apply them with `sqlx migrate run`; sqlx tracks them in its own _sqlx_migrations table"
+    );
+
+    Ok(())
+}
+
+/// File names and contents following sqlx-cli's conventions: `<version>_<description>.sql`
+/// for simple migrations, `<version>_<description>.up.sql`/`.down.sql` for reversible ones
+fn sqlx_files(
+    migrations: &[Migration],
+    reversible: bool,
+    naming: NamingScheme,
+) -> Vec<(String, String)> {
+    // Timestamps are offset by the feature version so files sort in feature order
+    let now = chrono::Utc::now();
+    let mut files = Vec::new();
+    for migration in migrations {
+        let prefix = match naming {
+            NamingScheme::Version => format!("{:03}", migration.version),
+            NamingScheme::Timestamp => (now + chrono::Duration::seconds(migration.version as i64))
+                .format("%Y%m%d%H%M%S")
+                .to_string(),
+        };
+        if reversible {
+            files.push((
+                format!("{}_{}.up.sql", prefix, migration.name),
+                migration.up_sql.clone(),
+            ));
+            files.push((
+                format!("{}_{}.down.sql", prefix, migration.name),
+                migration.down_sql.clone(),
+            ));
+        } else {
+            files.push((
+                format!("{}_{}.sql", prefix, migration.name),
+                migration.up_sql.clone(),
+            ));
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::DatabaseType;
+    use crate::config::{Feature, IdType};
+    use crate::schema::get_migrations_for_features;
+
+    /// Parse a file name the way sqlx does: version up to the first `_`,
+    /// then the description, then the migration kind from the extension
+    fn parse_sqlx(filename: &str) -> (i64, String, &str) {
+        let (version, rest) = filename.split_once('_').unwrap();
+        let (description, kind) = if let Some(d) = rest.strip_suffix(".up.sql") {
+            (d, "up")
+        } else if let Some(d) = rest.strip_suffix(".down.sql") {
+            (d, "down")
+        } else {
+            (rest.strip_suffix(".sql").unwrap(), "simple")
+        };
+        (version.parse().unwrap(), description.to_string(), kind)
+    }
+
+    fn migrations() -> Vec<Migration> {
+        get_migrations_for_features(
+            &[Feature::EmailPassword, Feature::EmailVerification],
+            DatabaseType::Sqlite,
+            IdType::Text,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_simple_files_round_trip() {
+        let migrations = migrations();
+        let files = sqlx_files(&migrations, false, NamingScheme::Version);
+
+        let names: Vec<&str> = files.iter().map(|(f, _)| f.as_str()).collect();
+        assert_eq!(names, ["001_base.sql", "002_email_verification.sql"]);
+
+        for ((filename, content), migration) in files.iter().zip(&migrations) {
+            let (version, description, kind) = parse_sqlx(filename);
+            assert_eq!(version, migration.version as i64);
+            assert_eq!(description, migration.name);
+            assert_eq!(kind, "simple");
+            assert_eq!(content, &migration.up_sql);
+        }
+    }
+
+    #[test]
+    fn test_reversible_files_round_trip() {
+        let migrations = migrations();
+        let files = sqlx_files(&migrations, true, NamingScheme::Version);
+        assert_eq!(files.len(), 4);
+
+        for (pair, migration) in files.chunks(2).zip(&migrations) {
+            let (up_version, up_name, up_kind) = parse_sqlx(&pair[0].0);
+            let (down_version, down_name, down_kind) = parse_sqlx(&pair[1].0);
+            assert_eq!((up_version, up_kind), (migration.version as i64, "up"));
+            assert_eq!(
+                (down_version, down_kind),
+                (migration.version as i64, "down")
+            );
+            assert_eq!(up_name, migration.name);
+            assert_eq!(down_name, migration.name);
+            assert_eq!(pair[0].1, migration.up_sql);
+            assert_eq!(pair[1].1, migration.down_sql);
+        }
+    }
+
+    #[test]
+    fn test_timestamp_versions_sort_in_feature_order() {
+        let files = sqlx_files(&migrations(), false, NamingScheme::Timestamp);
+        let versions: Vec<i64> = files.iter().map(|(f, _)| parse_sqlx(f).0).collect();
+        assert_eq!(versions.len(), 2);
+        assert!(versions[0] < versions[1]);
+    }
+}
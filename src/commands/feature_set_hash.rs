@@ -0,0 +1,18 @@
+use crate::cli::FeatureSetHashArgs;
+use crate::config::AuthKitConfig;
+use crate::error::CliResult;
+use crate::migrations::{feature_set_hash, get_migrations_for_database};
+
+pub async fn run(args: FeatureSetHashArgs) -> CliResult<()> {
+    let config = AuthKitConfig::load(&args.config)?;
+    let db_type = match args.db {
+        Some(db_type) => db_type,
+        None => config.database_type()?,
+    };
+
+    // Only the hash is printed so deploy scripts can compare it directly
+    let migrations = get_migrations_for_database(&config, db_type)?;
+    println!("{}", feature_set_hash(&migrations));
+
+    Ok(())
+}
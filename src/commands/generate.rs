@@ -1,12 +1,56 @@
 use std::fs;
-use std::path::Path;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
 use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
 
-use crate::cli::GenerateArgs;
-use crate::config::AuthKitConfig;
+use crate::cli::{GenerateArgs, LineEnding, MessageFormat, NamingScheme};
+use crate::config::{AuthKitConfig, Feature};
 use crate::error::{CliError, CliResult};
-use crate::migrations::get_migrations_from_config;
+use crate::migrations::{compute_checksum, get_migrations_from_config, Migration};
+use crate::output::{self, enote, note};
+use crate::schema::{objects, validate_index_names};
+
+/// File stem of the migration written by `--squash`
+const SQUASHED_NAME: &str = "001_authkit";
+
+/// State of a migration file compared to what is already on disk
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FileState {
+    /// File does not exist yet
+    New,
+    /// File exists with identical content
+    Unchanged,
+    /// File exists with different content
+    Changed,
+}
+
+/// Resolve `--feature`, which must name a feature enabled in the config
+fn selected_feature(config: &AuthKitConfig, name: &str, config_path: &str) -> CliResult<Feature> {
+    let feature = Feature::from_key(name).ok_or_else(|| {
+        CliError::FeatureNotEnabled(format!(
+            "unknown feature '{}'; `authkit list-features` lists the available features",
+            name
+        ))
+    })?;
+    if !config.enabled_features().contains(&feature) {
+        return Err(CliError::FeatureNotEnabled(format!(
+            "{} is not enabled in {}",
+            feature.config_key(),
+            config_path
+        )));
+    }
+    Ok(feature)
+}
+
+/// A migration file to be written
+struct PlannedFile {
+    filename: String,
+    path: PathBuf,
+    content: String,
+    state: FileState,
+}
 
 pub async fn run(args: GenerateArgs) -> CliResult<()> {
     // Load configuration
@@ -14,70 +58,404 @@ pub async fn run(args: GenerateArgs) -> CliResult<()> {
     let db_type = config.database_type()?;
 
     let db_name = db_type.to_string();
-    let migrations = get_migrations_from_config(&config);
+    let mut migrations = get_migrations_from_config(&config)?;
+
+    let selected = args
+        .feature
+        .as_deref()
+        .map(|name| selected_feature(&config, name, &args.config))
+        .transpose()?;
+    if let Some(feature) = selected {
+        migrations.retain(|m| m.version == feature.version());
+    }
 
     if migrations.is_empty() {
         println!("{} No features enabled. Nothing to generate.", "!".yellow());
         return Ok(());
     }
 
-    let output_dir = Path::new(&args.output);
+    if args.strict_sql {
+        validate_index_names(&migrations)?;
+    }
+
+    // File names and contents, in the order they are written
+    let mut rendered = Vec::new();
+    let mut squashed_checksum = None;
+    if args.combined {
+        let (up_sql, down_sql) = combine(&migrations, args.group_by_table);
+        rendered.push(("schema.up.sql".to_string(), encode(up_sql, &args)));
+        rendered.push(("schema.down.sql".to_string(), encode(down_sql, &args)));
+    } else if args.squash {
+        let (up_sql, down_sql) = squash(&migrations);
+        squashed_checksum = Some(compute_checksum(&up_sql));
+        rendered.push((format!("{}.up.sql", SQUASHED_NAME), encode(up_sql, &args)));
+        rendered.push((
+            format!("{}.down.sql", SQUASHED_NAME),
+            encode(down_sql, &args),
+        ));
+    } else {
+        // Timestamps are offset by the feature version so files sort in feature order
+        let now = chrono::Utc::now();
+        for migration in &migrations {
+            let prefix = match args.naming {
+                NamingScheme::Version => format!("{:03}", migration.version),
+                NamingScheme::Timestamp => (now
+                    + chrono::Duration::seconds(migration.version as i64))
+                .format("%Y%m%d%H%M%S")
+                .to_string(),
+            };
+            let up_filename = format!("{}_{}.up.sql", prefix, migration.name);
+            let down_filename = format!("{}_{}.down.sql", prefix, migration.name);
+
+            rendered.push((up_filename, encode(migration.up_sql.to_string(), &args)));
+            rendered.push((down_filename, encode(migration.down_sql.to_string(), &args)));
+        }
+    }
+
+    if args.stdout {
+        for (filename, content) in &rendered {
+            println!("-- file: {}", filename);
+            println!("{}", content.trim_end());
+            println!();
+        }
+        return Ok(());
+    }
+
+    // Each database gets its own subdirectory, so one output directory can
+    // hold both the sqlite/ and postgres/ migration sets
+    let output_dir = Path::new(&args.output).join(&db_name);
 
     // Create output directory
-    fs::create_dir_all(output_dir)?;
+    fs::create_dir_all(&output_dir)?;
 
-    println!(
+    let files = rendered
+        .into_iter()
+        .map(|(filename, content)| plan_file(&output_dir, filename, content))
+        .collect::<CliResult<Vec<_>>>()?;
+
+    // Files of the other features are expected next to a single feature's files
+    let stray = if selected.is_some() {
+        Vec::new()
+    } else {
+        stray_files(&output_dir, &files)?
+    };
+    let (stale, unrelated): (Vec<_>, Vec<_>) =
+        stray.into_iter().partition(|name| is_authkit_file(name));
+    let leftover = if args.clean {
+        for name in &stale {
+            fs::remove_file(output_dir.join(name))?;
+        }
+        unrelated
+    } else {
+        [stale, unrelated].concat()
+    };
+    if !args.force && !leftover.is_empty() {
+        return Err(CliError::OutputNotEmpty(leftover.join(", ")));
+    }
+
+    if args.output_format == MessageFormat::Json {
+        return write_json_report(&files, args.force);
+    }
+
+    note!(
         "Generating {} migrations to {}",
         db_name,
         output_dir.display()
     );
-    println!();
+    note!();
 
     // Show enabled features
-    println!("Enabled features:");
+    note!("Enabled features:");
     for feature in config.enabled_features() {
-        println!("  {} {}", "✓".green(), feature.display_name());
+        if selected.is_some() && selected != Some(feature) {
+            continue;
+        }
+        note!("  {} {}", "✓".green(), feature.display_name());
     }
-    println!();
+    note!();
 
-    for migration in &migrations {
-        let up_filename = format!("{:03}_{}.up.sql", migration.version, migration.name);
-        let down_filename = format!("{:03}_{}.down.sql", migration.version, migration.name);
+    // Check if files exist
+    if !args.force {
+        let blocking = |f: &&PlannedFile| match f.state {
+            FileState::New => false,
+            FileState::Unchanged => !args.skip_unchanged,
+            FileState::Changed => true,
+        };
+        if let Some(existing) = files.iter().find(blocking) {
+            return Err(CliError::FileExists(existing.path.display().to_string()));
+        }
+    }
 
-        let up_path = output_dir.join(&up_filename);
-        let down_path = output_dir.join(&down_filename);
+    let pb = if std::io::stderr().is_terminal() && !output::is_quiet() {
+        ProgressBar::new(files.len() as u64)
+    } else {
+        ProgressBar::hidden()
+    };
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner} [{bar:40}] {pos}/{len} {msg}")
+            .expect("Invalid progress bar template")
+            .progress_chars("#>-"),
+    );
 
-        // Check if files exist
-        if !args.force {
-            if up_path.exists() {
-                return Err(CliError::FileExists(up_path.display().to_string()));
-            }
-            if down_path.exists() {
-                return Err(CliError::FileExists(down_path.display().to_string()));
-            }
+    let mut created = Vec::new();
+    let mut skipped = Vec::new();
+    for file in &files {
+        if args.skip_unchanged && file.state == FileState::Unchanged {
+            skipped.push(file);
+        } else {
+            write_atomic(&file.path, &file.content)?;
+            created.push(file);
         }
+        pb.set_message(format!(
+            "created {}, skipped {}",
+            created.len(),
+            skipped.len()
+        ));
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
 
-        // Write files
-        fs::write(&up_path, migration.up_sql)?;
-        fs::write(&down_path, migration.down_sql)?;
-
-        println!("  {} {}", "Created".green(), up_filename);
-        println!("  {} {}", "Created".green(), down_filename);
+    for file in &created {
+        note!("  {} {}", "Created".green(), file.filename);
+    }
+    for file in &skipped {
+        note!("  {} {} (unchanged)", "Skipped".dimmed(), file.filename);
     }
 
-    println!();
-    println!(
-        "{} Generated {} migration files ({} features)",
-        "✓".green(),
-        migrations.len() * 2,
-        migrations.len()
-    );
-    println!();
-    println!("Next steps:");
-    println!(
+    note!();
+    if skipped.is_empty() {
+        println!(
+            "{} Generated {} migration files ({} features)",
+            "✓".green(),
+            files.len(),
+            migrations.len()
+        );
+    } else {
+        println!(
+            "{} Generated {} of {} migration files, {} skipped as unchanged ({} features)",
+            "✓".green(),
+            created.len(),
+            files.len(),
+            skipped.len(),
+            migrations.len()
+        );
+    }
+    if let Some(checksum) = &squashed_checksum {
+        note!(
+            "  Squashed into {} (checksum: {})",
+            SQUASHED_NAME.cyan(),
+            checksum
+        );
+        enote!(
+            "{} Only apply a squashed migration to a fresh database",
+            "Note:".yellow()
+        );
+    }
+    note!();
+    note!("Next steps:");
+    note!(
         "  Run {} to apply migrations",
         "authkit migrate --db-url <URL>".cyan()
     );
 
     Ok(())
 }
+
+/// Concatenate all migrations into one UP script and one DOWN script (in reverse order)
+fn combine(migrations: &[Migration], group_by_table: bool) -> (String, String) {
+    let header = |m: &Migration| {
+        format!(
+            "-- ============================================================\n-- Feature: {} (version {:03})\n-- ============================================================\n",
+            m.name, m.version
+        )
+    };
+
+    let up = migrations
+        .iter()
+        .map(|m| {
+            // Grouping is per feature: a later feature's index may need its ALTER TABLE first
+            let up_sql = if group_by_table {
+                objects::group_by_table(&m.up_sql)
+            } else {
+                m.up_sql.to_string()
+            };
+            format!("{}{}", header(m), up_sql.trim_start_matches('\n'))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let down = migrations
+        .iter()
+        .rev()
+        .map(|m| format!("{}{}", header(m), m.down_sql.trim_start_matches('\n')))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    (up, down)
+}
+
+/// Concatenate all migrations into the single migration written by `--squash`.
+/// Its one tracking entry can't record which features a database already has,
+/// so the header warns against applying it anywhere but a fresh database.
+fn squash(migrations: &[Migration]) -> (String, String) {
+    let names: Vec<&str> = migrations.iter().map(|m| m.name.as_str()).collect();
+    let header = format!(
+        "-- AuthKit squashed migration: {}\n-- Only apply this to a fresh database; existing databases should keep the per-feature migrations\n\n",
+        names.join(", ")
+    );
+
+    let (up, down) = combine(migrations, false);
+    (format!("{}{}", header, up), format!("{}{}", header, down))
+}
+
+/// Apply the requested line endings and byte order mark to a file's content
+fn encode(content: String, args: &GenerateArgs) -> String {
+    let content = match args.line_ending {
+        LineEnding::Lf => content,
+        LineEnding::Crlf => content.replace("\r\n", "\n").replace('\n', "\r\n"),
+    };
+    if args.bom {
+        format!("\u{feff}{}", content)
+    } else {
+        content
+    }
+}
+
+/// Names of `.sql` files in the output directory that this run won't write
+fn stray_files(output_dir: &Path, files: &[PlannedFile]) -> CliResult<Vec<String>> {
+    let mut stray = Vec::new();
+    for entry in fs::read_dir(output_dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if name.ends_with(".sql") && !files.iter().any(|f| f.filename == name) {
+            stray.push(name);
+        }
+    }
+    stray.sort();
+    Ok(stray)
+}
+
+/// Whether a file name looks like one `generate` writes, e.g. `002_email_verification.up.sql`
+fn is_authkit_file(name: &str) -> bool {
+    let Some(stem) = name
+        .strip_suffix(".up.sql")
+        .or_else(|| name.strip_suffix(".down.sql"))
+    else {
+        return false;
+    };
+    if stem == "schema" || stem == SQUASHED_NAME {
+        return true;
+    }
+
+    match stem.split_once('_') {
+        Some((prefix, feature)) => {
+            !prefix.is_empty()
+                && prefix.chars().all(|c| c.is_ascii_digit())
+                && Feature::all().iter().any(|f| f.migration_name() == feature)
+        }
+        None => false,
+    }
+}
+
+/// Compare a migration file against the output directory
+fn plan_file(output_dir: &Path, filename: String, content: String) -> CliResult<PlannedFile> {
+    let path = output_dir.join(&filename);
+
+    let state = if !path.exists() {
+        FileState::New
+    } else if fs::read_to_string(&path)? == content {
+        FileState::Unchanged
+    } else {
+        FileState::Changed
+    };
+
+    Ok(PlannedFile {
+        filename,
+        path,
+        content,
+        state,
+    })
+}
+
+/// Write through a temporary file so an interrupted run never leaves a
+/// half-written migration behind (it would look like a conflict on re-run)
+fn write_atomic(path: &Path, content: &str) -> CliResult<()> {
+    let tmp = path.with_extension("sql.tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Write new files, skip unchanged ones and report conflicts as a single JSON object
+fn write_json_report(files: &[PlannedFile], force: bool) -> CliResult<()> {
+    let mut created = Vec::new();
+    let mut skipped = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for file in files {
+        match file.state {
+            FileState::Unchanged => skipped.push(file),
+            FileState::Changed if !force => conflicts.push(file),
+            FileState::New | FileState::Changed => {
+                write_atomic(&file.path, &file.content)?;
+                created.push(file);
+            }
+        }
+    }
+
+    let names = |files: &[&PlannedFile]| -> Vec<String> {
+        files.iter().map(|f| f.filename.clone()).collect()
+    };
+    let report = serde_json::json!({
+        "created": names(&created),
+        "skipped": names(&skipped),
+        "conflicts": names(&conflicts),
+        "success": conflicts.is_empty(),
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    );
+
+    match conflicts.first() {
+        Some(conflict) => Err(CliError::FileExists(conflict.path.display().to_string())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_authkit_file() {
+        assert!(is_authkit_file("002_email_verification.up.sql"));
+        assert!(is_authkit_file("20240115093000_base.down.sql"));
+        assert!(is_authkit_file("schema.up.sql"));
+        assert!(is_authkit_file("001_authkit.down.sql"));
+        assert!(!is_authkit_file("002_add_widgets.up.sql"));
+        assert!(!is_authkit_file("base.up.sql"));
+        assert!(!is_authkit_file("001_base.sql"));
+    }
+
+    #[test]
+    fn test_squash_orders_up_and_down() {
+        let migrations = crate::schema::get_migrations_for_features(
+            &[Feature::EmailPassword, Feature::ApiKeys],
+            crate::cli::DatabaseType::Sqlite,
+            crate::config::IdType::Text,
+        )
+        .unwrap();
+        let (up, down) = squash(&migrations);
+
+        assert!(up.starts_with("-- AuthKit squashed migration: base, api_keys\n"));
+        assert!(
+            up.find("CREATE TABLE IF NOT EXISTS users").unwrap()
+                < up.find("CREATE TABLE IF NOT EXISTS api_keys").unwrap()
+        );
+        assert!(
+            down.find("DROP TABLE IF EXISTS api_keys").unwrap()
+                < down.find("DROP TABLE IF EXISTS users").unwrap()
+        );
+    }
+}
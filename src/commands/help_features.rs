@@ -0,0 +1,65 @@
+use colored::Colorize;
+
+use crate::cli::DatabaseType;
+use crate::config::Feature;
+use crate::error::CliResult;
+use crate::schema::{get_feature_migration, objects};
+
+/// Print every feature with the objects it creates, derived from the feature SQL itself
+pub fn run() -> CliResult<()> {
+    println!("AuthKit features");
+    println!();
+
+    for feature in Feature::all() {
+        let migration = get_feature_migration(*feature, DatabaseType::Postgres);
+        let objects = objects::parse(&migration.up_sql);
+
+        println!(
+            "{} - {}",
+            feature.config_key().cyan().bold(),
+            feature.display_name()
+        );
+        println!("  Version:    {}", feature.version());
+
+        let always = if *feature == Feature::EmailPassword {
+            " (always enabled)"
+        } else {
+            ""
+        };
+        println!("  Enable:     {} = true{}", feature.config_key(), always);
+
+        let requires: Vec<&str> = feature
+            .dependencies()
+            .iter()
+            .map(|d| d.config_key())
+            .collect();
+        if requires.is_empty() {
+            println!("  Requires:   -");
+        } else {
+            println!("  Requires:   {}", requires.join(", "));
+        }
+
+        for (i, table) in objects.tables.iter().enumerate() {
+            let label = if i == 0 { "Tables:" } else { "" };
+            println!(
+                "  {:<11} {} ({})",
+                label,
+                table.name,
+                table.columns.join(", ")
+            );
+        }
+
+        if !objects.columns.is_empty() {
+            let columns: Vec<String> = objects
+                .columns
+                .iter()
+                .map(|c| format!("{}.{}", c.table, c.name))
+                .collect();
+            println!("  Columns:    {}", columns.join(", "));
+        }
+
+        println!();
+    }
+
+    Ok(())
+}
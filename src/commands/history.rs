@@ -0,0 +1,99 @@
+use colored::Colorize;
+use tabled::{Table, Tabled};
+
+use crate::cli::{HistoryArgs, HistoryFormat, TimeFormat};
+use crate::commands::status::format_applied_at;
+use crate::csv;
+use crate::database::{ConnectOptions, Database};
+use crate::error::CliResult;
+use crate::migrations::runner::MigrationRunner;
+use crate::schema::MIGRATIONS_TABLE;
+
+#[derive(Tabled)]
+struct HistoryRow {
+    #[tabled(rename = "#")]
+    version: String,
+    #[tabled(rename = "Feature")]
+    name: String,
+    #[tabled(rename = "Applied At")]
+    applied_at: String,
+    #[tabled(rename = "Duration")]
+    duration: String,
+}
+
+pub async fn run(args: HistoryArgs) -> CliResult<()> {
+    let options = ConnectOptions::from(&args.connect).read_only();
+    let db = Database::connect(&args.db_url, &options).await?;
+
+    let mut applied = if db.table_exists(MIGRATIONS_TABLE).await? {
+        MigrationRunner::new(&db.pool, db.db_type)
+            .get_applied_migrations()
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    // Oldest first; migrations applied in the same second keep version order
+    applied.sort_by_key(|m| (m.applied_at, m.version));
+    if let Some(limit) = args.limit {
+        applied.drain(..applied.len().saturating_sub(limit));
+    }
+
+    if args.format == HistoryFormat::Csv {
+        let rows: Vec<Vec<String>> = applied
+            .iter()
+            .map(|m| {
+                vec![
+                    m.version.to_string(),
+                    m.name.clone(),
+                    "applied".to_string(),
+                    format_applied_at(m.applied_at, TimeFormat::Iso).unwrap_or_default(),
+                ]
+            })
+            .collect();
+        print!("{}", csv::render(&csv::MIGRATION_HEADER, &rows));
+        return Ok(());
+    }
+
+    if args.format == HistoryFormat::Json {
+        let history: Vec<_> = applied
+            .iter()
+            .map(|m| {
+                serde_json::json!({
+                    "version": m.version,
+                    "name": m.name,
+                    "applied_at": m.applied_at,
+                    "execution_ms": m.execution_ms,
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&history).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    if applied.is_empty() {
+        println!("{} No migrations applied", "!".yellow());
+        return Ok(());
+    }
+
+    let rows: Vec<HistoryRow> = applied
+        .iter()
+        .map(|m| HistoryRow {
+            version: format!("{:03}", m.version),
+            name: m.name.clone(),
+            applied_at: format_applied_at(m.applied_at, TimeFormat::Utc)
+                .unwrap_or_else(|| "-".to_string()),
+            duration: m
+                .execution_ms
+                .map(|ms| format!("{}ms", ms))
+                .unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+
+    println!("{}", Table::new(rows));
+
+    Ok(())
+}
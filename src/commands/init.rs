@@ -1,24 +1,52 @@
+use std::fs;
 use std::path::Path;
 
 use colored::Colorize;
 
-use crate::cli::InitArgs;
-use crate::config::AuthKitConfig;
+use crate::cli::{DatabaseType, InitArgs};
+use crate::config::{AuthKitConfig, Feature};
 use crate::error::{CliError, CliResult};
+use crate::output::note;
 
 pub async fn run(args: InitArgs) -> CliResult<()> {
+    if args.print_example {
+        print!(
+            "{}",
+            AuthKitConfig::example_toml(args.db.unwrap_or(DatabaseType::Postgres))
+        );
+        return Ok(());
+    }
+
     let config_path = Path::new(&args.output);
 
     // Check if file already exists
-    if config_path.exists() && !args.force {
+    if config_path.exists() && !args.force && !args.merge {
         return Err(CliError::FileExists(format!(
-            "{}. Use --force to overwrite.",
+            "{}. Use --merge to update it or --force to overwrite.",
             config_path.display()
         )));
     }
 
-    // Create default config
-    let config = AuthKitConfig::default_config(args.db);
+    let mut features = Vec::new();
+    for key in &args.features {
+        for feature in Feature::parse(key.trim())?.with_dependencies() {
+            if !features.contains(&feature) {
+                features.push(feature);
+            }
+        }
+    }
+
+    if args.merge && config_path.exists() {
+        return merge(&args, config_path, &features);
+    }
+
+    // Create default config, then turn on the requested features and what they need
+    let db_type = args.db.unwrap_or(DatabaseType::Postgres);
+    let mut config = AuthKitConfig::default_config(db_type);
+    for feature in &features {
+        config.features.set(*feature, true);
+    }
+    config.validate()?;
 
     // Create parent directories if needed
     if let Some(parent) = config_path.parent() {
@@ -30,33 +58,224 @@ pub async fn run(args: InitArgs) -> CliResult<()> {
     // Save config to file
     config.save(config_path)?;
 
-    println!();
+    note!();
     println!("{} Created {}", "✓".green(), config_path.display());
-    println!();
-    println!("Configuration file created with:");
-    println!("  Database: {}", args.db.to_string().cyan());
-    println!("  Features:");
-    println!("    - {} (base)", "email_password".green());
-    println!();
-    println!("To enable additional features, edit the config file:");
-    println!();
-    println!("  [features]");
-    println!("  email_password = true");
-    println!("  email_verification = true  # Enable this for email verification");
-    println!();
-    println!("Next steps:");
-    println!(
-        "  1. Edit {} to enable features",
-        config_path.display().to_string().cyan()
-    );
-    println!(
+    note!();
+    note!("Configuration file created with:");
+    note!("  Database: {}", db_type.to_string().cyan());
+    note!("  Features:");
+    for feature in config.enabled_features() {
+        if feature == Feature::EmailPassword {
+            note!("    - {} (base)", feature.config_key().green());
+        } else {
+            note!("    - {}", feature.config_key().green());
+        }
+    }
+    note!();
+
+    if args.features.is_empty() {
+        note!(
+            "To enable additional features, edit the config file. {} lists every feature flag.",
+            "authkit init --print-example".cyan()
+        );
+        note!();
+    }
+
+    note!("Next steps:");
+    if args.features.is_empty() {
+        note!(
+            "  1. Edit {} to enable features",
+            config_path.display().to_string().cyan()
+        );
+    } else {
+        note!(
+            "  1. Review {} (other features can be enabled there)",
+            config_path.display().to_string().cyan()
+        );
+    }
+    note!(
         "  2. Run {} to generate migrations",
         "authkit generate".cyan()
     );
-    println!(
+    note!(
         "  3. Run {} to apply migrations",
         "authkit migrate --db-url <URL>".cyan()
     );
 
     Ok(())
 }
+
+/// Apply `--db` and `--features` to an existing config by editing its text,
+/// so comments, other settings and `[env.*]` profiles survive
+fn merge(args: &InitArgs, config_path: &Path, features: &[Feature]) -> CliResult<()> {
+    let content = fs::read_to_string(config_path)?;
+    let merged = merge_toml(&content, args.db, features);
+
+    // The edit must parse and say what was asked; anything else is a layout we can't edit
+    let config = AuthKitConfig::parse_profile(&merged, None)?;
+    let enabled = config.enabled_features();
+    if features.iter().any(|f| !enabled.contains(f))
+        || args
+            .db
+            .is_some_and(|db| config.database.db_type != db.to_string())
+    {
+        return Err(CliError::ConfigParse(format!(
+            "could not merge into {}; edit it by hand or use --force to overwrite",
+            config_path.display()
+        )));
+    }
+    config.validate()?;
+
+    if merged == content {
+        println!(
+            "{} {} already up to date",
+            "✓".green(),
+            config_path.display()
+        );
+        return Ok(());
+    }
+    fs::write(config_path, &merged)?;
+
+    println!("{} Updated {}", "✓".green(), config_path.display());
+    note!();
+    note!("  Database: {}", config.database.db_type.cyan());
+    note!("  Features:");
+    for feature in enabled {
+        note!("    - {}", feature.config_key().green());
+    }
+
+    Ok(())
+}
+
+/// Set `type` in `[database]` and turn `features` on in `[features]`, line by line.
+///
+/// Existing keys are rewritten in place (keeping trailing comments); missing ones
+/// are added at the end of their section, which is created if needed.
+fn merge_toml(content: &str, db: Option<DatabaseType>, features: &[Feature]) -> String {
+    let mut wanted: Vec<(&str, String, String)> = Vec::new();
+    if let Some(db) = db {
+        wanted.push(("database", "type".to_string(), format!("\"{}\"", db)));
+    }
+    for feature in features {
+        wanted.push((
+            "features",
+            feature.config_key().to_string(),
+            "true".to_string(),
+        ));
+    }
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut found = vec![false; wanted.len()];
+    // Index of the last key line in each section, where missing keys go
+    let mut section_end: Vec<(String, usize)> = Vec::new();
+    let mut section = String::new();
+
+    for (i, line) in lines.iter_mut().enumerate() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            section = name.trim().to_string();
+            section_end.push((section.clone(), i));
+            continue;
+        }
+        let Some((key, rest)) = trimmed.split_once('=') else {
+            continue;
+        };
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(end) = section_end.last_mut() {
+            end.1 = i;
+        }
+
+        let key = key.trim();
+        let canonical = if key == "passkeys" { "webauthn" } else { key };
+        let mut replacement = None;
+        for (j, (wanted_section, wanted_key, value)) in wanted.iter().enumerate() {
+            if section == *wanted_section && canonical == wanted_key {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                replacement = Some(match rest.find('#') {
+                    Some(c) => format!("{}{} = {}  {}", indent, key, value, &rest[c..]),
+                    None => format!("{}{} = {}", indent, key, value),
+                });
+                found[j] = true;
+            }
+        }
+        if let Some(replacement) = replacement {
+            *line = replacement;
+        }
+    }
+
+    // Insert missing keys bottom-up so earlier indexes stay valid
+    let mut missing_by_section: Vec<(&str, Vec<String>)> = Vec::new();
+    for ((section, key, value), found) in wanted.iter().zip(&found) {
+        if *found {
+            continue;
+        }
+        let line = format!("{} = {}", key, value);
+        match missing_by_section.iter_mut().find(|(s, _)| s == section) {
+            Some((_, keys)) => keys.push(line),
+            None => missing_by_section.push((section, vec![line])),
+        }
+    }
+
+    let mut appended = Vec::new();
+    let mut inserts: Vec<(usize, Vec<String>)> = Vec::new();
+    for (section, keys) in missing_by_section {
+        match section_end.iter().find(|(s, _)| s == section) {
+            Some((_, end)) => inserts.push((end + 1, keys)),
+            None => {
+                appended.push(String::new());
+                appended.push(format!("[{}]", section));
+                appended.extend(keys);
+            }
+        }
+    }
+    inserts.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+    for (at, keys) in inserts {
+        lines.splice(at..at, keys);
+    }
+    lines.extend(appended);
+
+    let mut merged = lines.join("\n");
+    if content.ends_with('\n') || content.is_empty() {
+        merged.push('\n');
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_toml_rewrites_keys_in_place() {
+        let content = "# Shared config\n[database]\ntype = \"postgres\"  # prod\n\n[features]\nemail_password = true\npasskeys = false\n\n[schema]\nid_type = \"text\"\n";
+        let merged = merge_toml(
+            content,
+            Some(DatabaseType::Sqlite),
+            &[Feature::EmailVerification, Feature::WebAuthn],
+        );
+        assert_eq!(
+            merged,
+            "# Shared config\n[database]\ntype = \"sqlite\"  # prod\n\n[features]\nemail_password = true\npasskeys = true\nemail_verification = true\n\n[schema]\nid_type = \"text\"\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_toml_adds_missing_section() {
+        let merged = merge_toml("[database]\ntype = \"sqlite\"\n", None, &[Feature::OAuth]);
+        assert_eq!(
+            merged,
+            "[database]\ntype = \"sqlite\"\n\n[features]\noauth = true\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_toml_ignores_env_profiles() {
+        let content = "[database]\ntype = \"sqlite\"\n\n[features]\nemail_password = true\n\n[env.prod.features]\noauth = false\n";
+        let merged = merge_toml(content, None, &[Feature::OAuth]);
+        assert!(merged.contains(
+            "email_password = true\noauth = true\n\n[env.prod.features]\noauth = false\n"
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+use tabled::{Table, Tabled};
+
+use crate::cli::{ListFeaturesArgs, ListFormat};
+use crate::config::Feature;
+use crate::error::CliResult;
+
+#[derive(Tabled)]
+struct FeatureRow {
+    #[tabled(rename = "#")]
+    version: u32,
+    #[tabled(rename = "Key")]
+    key: &'static str,
+    #[tabled(rename = "Name")]
+    name: &'static str,
+    #[tabled(rename = "Migration")]
+    migration: &'static str,
+    #[tabled(rename = "Base")]
+    base: &'static str,
+    #[tabled(rename = "Requires")]
+    requires: String,
+}
+
+pub async fn run(args: ListFeaturesArgs) -> CliResult<()> {
+    match args.format {
+        ListFormat::Json => {
+            let features: Vec<serde_json::Value> = Feature::all()
+                .iter()
+                .map(|feature| {
+                    serde_json::json!({
+                        "key": feature.config_key(),
+                        "name": feature.display_name(),
+                        "migration": feature.migration_name(),
+                        "version": feature.version(),
+                        "base": *feature == Feature::EmailPassword,
+                        "requires": requires(*feature),
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&features).unwrap_or_default()
+            );
+        }
+        ListFormat::Table => {
+            let rows: Vec<FeatureRow> = Feature::all()
+                .iter()
+                .map(|feature| FeatureRow {
+                    version: feature.version(),
+                    key: feature.config_key(),
+                    name: feature.display_name(),
+                    migration: feature.migration_name(),
+                    base: if *feature == Feature::EmailPassword {
+                        "yes"
+                    } else {
+                        ""
+                    },
+                    requires: requires(*feature).join(", "),
+                })
+                .collect();
+            println!("{}", Table::new(&rows));
+        }
+    }
+
+    Ok(())
+}
+
+/// Config keys of the features this one requires
+fn requires(feature: Feature) -> Vec<&'static str> {
+    feature
+        .dependencies()
+        .iter()
+        .map(|d| d.config_key())
+        .collect()
+}
@@ -1,89 +1,562 @@
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Instant;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tabled::{Table, Tabled};
 
-use crate::cli::MigrateArgs;
-use crate::config::AuthKitConfig;
-use crate::database::Database;
-use crate::error::CliResult;
+use crate::cli::{DatabaseType, MigrateArgs, TransactionMode};
+use crate::config::{AuthKitConfig, Feature};
+use crate::database::{
+    quote_ident, redact_url, retry_on_lock, ConnectOptions, Database, TableInfo,
+};
+use crate::error::{CliError, CliResult};
+use crate::migrations::checks::find_duplicate_emails;
+use crate::migrations::lock::MigrationLock;
+use crate::migrations::plan::Plan;
 use crate::migrations::runner::MigrationRunner;
-use crate::schema;
+use crate::migrations::{get_migrations_for_database, AppliedMigration, Migration, MigrationState};
+use crate::output::{self, enote, note};
+use crate::schema::{get_feature_migration_for, is_authkit_table, objects};
 
 pub async fn run(args: MigrateArgs) -> CliResult<()> {
     // Load configuration
     let config = AuthKitConfig::load(&args.config)?;
     let db_type = config.database_type()?;
 
-    println!("Configuration: {}", args.config.cyan());
-    println!("Database type: {}", db_type.to_string().cyan());
-    println!();
+    note!("Configuration: {}", args.config.cyan());
+    note!("Database type: {}", db_type.to_string().cyan());
+    note!();
+
+    if args.concurrent_indexes && args.transaction_mode == TransactionMode::All {
+        return Err(CliError::Other(
+            "--concurrent-indexes can't be used with --transaction-mode all: CREATE INDEX CONCURRENTLY cannot run inside a transaction"
+                .to_string(),
+        ));
+    }
+
+    let skipped = skipped_features(&args, &config)?;
 
     // Show enabled features
-    println!("Enabled features:");
+    note!("Enabled features:");
     for feature in config.enabled_features() {
-        println!("  {} {}", "✓".green(), feature.display_name());
+        if skipped.contains(&feature) {
+            note!(
+                "  {} {} {}",
+                "-".yellow(),
+                feature.display_name(),
+                "(skipped by --skip-feature)".yellow()
+            );
+        } else {
+            note!("  {} {}", "✓".green(), feature.display_name());
+        }
+    }
+    note!();
+
+    let targets = collect_targets(&args)?;
+
+    // A single database keeps the plain output and error behaviour
+    if let [db_url] = targets.as_slice() {
+        return retry_on_lock(args.retry_on_lock, || {
+            migrate_database(&args, &config, db_type, db_url)
+        })
+        .await
+        .map(|_| ());
+    }
+
+    if args.emit_metrics.is_some()
+        || args.report.is_some()
+        || args.plan_out.is_some()
+        || args.plan_file.is_some()
+    {
+        return Err(CliError::Other(
+            "--emit-metrics, --report, --plan-out and --plan-file can only be used with a single database"
+                .to_string(),
+        ));
+    }
+
+    let mut results = Vec::new();
+    let mut failures = 0;
+    for db_url in &targets {
+        note!("{} {}", "==>".cyan(), redact_url(db_url));
+
+        let result = retry_on_lock(args.retry_on_lock, || {
+            migrate_database(&args, &config, db_type, db_url)
+        })
+        .await;
+        note!();
+
+        match result {
+            Ok(applied) => results.push(TargetRow {
+                database: redact_url(db_url),
+                status: "OK".green().to_string(),
+                applied: applied.to_string(),
+            }),
+            Err(e) => {
+                failures += 1;
+                eprintln!("{} {}", "✗".red(), e);
+                enote!();
+                results.push(TargetRow {
+                    database: redact_url(db_url),
+                    status: "Failed".red().to_string(),
+                    applied: "-".to_string(),
+                });
+
+                if !args.continue_on_error {
+                    println!("{}", Table::new(&results));
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    println!("{}", Table::new(&results));
+
+    if failures > 0 {
+        return Err(CliError::migration(format!(
+            "{} of {} database(s) failed to migrate",
+            failures,
+            targets.len()
+        )));
     }
-    println!();
 
-    println!("Connecting to database...");
+    Ok(())
+}
 
-    let db = Database::connect(&args.db_url).await?;
+#[derive(Tabled)]
+struct TargetRow {
+    #[tabled(rename = "Database")]
+    database: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Applied")]
+    applied: String,
+}
+
+/// Features named by `--skip-feature`, rejecting skips that enabled features depend on
+fn skipped_features(args: &MigrateArgs, config: &AuthKitConfig) -> CliResult<Vec<Feature>> {
+    let skipped = args
+        .skip_feature
+        .iter()
+        .map(|key| Feature::parse(key))
+        .collect::<CliResult<Vec<_>>>()?;
+
+    for feature in config.enabled_features() {
+        if skipped.contains(&feature) {
+            continue;
+        }
+        if let Some(dependency) = feature.dependencies().iter().find(|d| skipped.contains(d)) {
+            return Err(CliError::Other(format!(
+                "cannot skip {}: the enabled feature {} depends on it",
+                dependency.config_key(),
+                feature.config_key()
+            )));
+        }
+    }
+
+    Ok(skipped)
+}
+
+/// Collect database URLs from repeated `--db-url` flags and `--db-url-file`
+fn collect_targets(args: &MigrateArgs) -> CliResult<Vec<String>> {
+    let mut targets = args.db_url.clone();
+
+    if let Some(path) = &args.db_url_file {
+        let content = fs::read_to_string(path)?;
+        targets.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from),
+        );
+    }
+
+    if targets.is_empty() {
+        return Err(CliError::Other("No database URLs given".to_string()));
+    }
+
+    Ok(targets)
+}
+
+/// Apply pending migrations to a single database, returning how many were applied
+async fn migrate_database(
+    args: &MigrateArgs,
+    config: &AuthKitConfig,
+    db_type: DatabaseType,
+    db_url: &str,
+) -> CliResult<usize> {
+    enote!("Connecting to database...");
+
+    let connect = ConnectOptions::from(&args.connect).with_config(config);
+    let db = Database::connect(db_url, &connect).await?;
 
     // Verify database type matches config
     if db.db_type != db_type {
-        println!(
+        if !args.allow_type_mismatch {
+            return Err(CliError::DatabaseTypeMismatch {
+                config: db_type,
+                actual: db.db_type,
+            });
+        }
+        eprintln!(
             "{} Database URL is {} but config specifies {}",
             "Warning:".yellow(),
             format!("{:?}", db.db_type).to_lowercase(),
-            db_type.to_string()
+            db_type
         );
     }
 
     let runner = MigrationRunner::new(&db.pool, db.db_type);
 
+    if args.report_duplicates {
+        check_duplicate_emails(&db, config.table_prefix()).await?;
+        return Ok(0);
+    }
+
+    if args.require_empty && !args.allow_dirty {
+        check_empty(&db, config.table_prefix()).await?;
+    }
+
+    // Serialize concurrent runs so they don't apply the same migration twice
+    let lock = MigrationLock::acquire(&db.pool, db.db_type, Duration::from_secs(args.lock_timeout))
+        .await?;
+    let result = apply_pending(args, config, &db, &runner).await;
+    lock.release_or_warn().await;
+    result
+}
+
+/// Whether every table and column a migration creates is already in the database
+async fn objects_exist(db: &Database, migration: &Migration) -> CliResult<bool> {
+    let objects = objects::parse(&migration.up_sql);
+    if objects.tables.is_empty() && objects.columns.is_empty() {
+        return Ok(false);
+    }
+
+    for table in &objects.tables {
+        // Custom migrations may use names like `app.orders` that table_exists rejects
+        if quote_ident(db.db_type, &table.name).is_err() || !db.table_exists(&table.name).await? {
+            return Ok(false);
+        }
+    }
+    for column in &objects.columns {
+        if !db
+            .table_columns(&column.table)
+            .await?
+            .contains(&column.name)
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Tables and columns an applied migration created that are gone from the database
+fn missing_objects(migration: &Migration, actual: &[TableInfo]) -> Vec<String> {
+    let objects = objects::parse(&migration.up_sql);
+    let columns_of = |table: &str| actual.iter().find(|t| t.name == table).map(|t| &t.columns);
+
+    let mut missing = Vec::new();
+    for table in &objects.tables {
+        if columns_of(&table.name).is_none() {
+            missing.push(format!("table {}", table.name));
+        }
+    }
+    for column in &objects.columns {
+        if !columns_of(&column.table).is_some_and(|columns| columns.contains(&column.name)) {
+            missing.push(format!("column {}.{}", column.table, column.name));
+        }
+    }
+    missing
+}
+
+/// Re-run applied migrations whose objects were dropped by hand.
+///
+/// The up SQL is `IF NOT EXISTS`-safe except for SQLite's `ADD COLUMN`, so
+/// statements adding a column that still exists are skipped.
+async fn repair_applied(
+    args: &MigrateArgs,
+    db: &Database,
+    runner: &MigrationRunner<'_>,
+    available: &[Migration],
+    applied: &[AppliedMigration],
+) -> CliResult<()> {
+    note!("Checking applied migrations for dropped tables and columns...");
+
+    let mut repaired = 0;
+    for migration in available
+        .iter()
+        .filter(|m| applied.iter().any(|a| a.version == m.version))
+    {
+        // Earlier repairs recreate tables, so look at the database again each time
+        let actual = db.list_tables().await?;
+        let missing = missing_objects(migration, &actual);
+        if missing.is_empty() {
+            continue;
+        }
+
+        let name = format!("{:03}_{}", migration.version, migration.name);
+        if args.dry_run {
+            println!("  Would repair {} (missing {})", name, missing.join(", "));
+            repaired += 1;
+            continue;
+        }
+
+        let mut statements = Vec::new();
+        for (index, sql) in MigrationRunner::split_statements(&migration.up_sql)
+            .into_iter()
+            .enumerate()
+        {
+            let added = objects::parse(&sql).columns;
+            let exists = added.iter().all(|column| {
+                actual
+                    .iter()
+                    .any(|t| t.name == column.table && t.columns.contains(&column.name))
+            });
+            if added.is_empty() || !exists {
+                statements.push((index, sql));
+            }
+        }
+        runner.reapply_statements(migration, &statements).await?;
+        println!(
+            "  {} {} (recreated {})",
+            "Repaired".green(),
+            name,
+            missing.join(", ")
+        );
+        repaired += 1;
+    }
+
+    if repaired == 0 {
+        note!("  {} All applied migrations are intact", "✓".green());
+    }
+    note!();
+
+    Ok(())
+}
+
+/// Apply pending migrations while holding the migration lock
+async fn apply_pending(
+    args: &MigrateArgs,
+    config: &AuthKitConfig,
+    db: &Database,
+    runner: &MigrationRunner<'_>,
+) -> CliResult<usize> {
+    if !args.allow_dirty && !args.baseline_existing && !args.fake {
+        check_untracked(config, db, runner).await?;
+    }
+
     // Ensure migrations table exists
     runner.ensure_migrations_table().await?;
 
+    // An edited feature that was already applied must not slip through on deploy
+    if args.strict {
+        runner.verify_checksums(config).await?;
+    }
+
     // Get migration status - use actual database type, not config type
-    let features = config.enabled_features();
-    let available = schema::get_migrations_for_features(&features, db.db_type);
+    let available = get_migrations_for_database(config, db.db_type)?;
     let applied = runner.get_applied_migrations().await?;
-    let pending = runner.get_pending_migrations(&available, &applied);
+    let skipped = skipped_features(args, config)?;
+    let mut pending: Vec<&Migration> = runner
+        .get_pending_migrations(&available, &applied)
+        .into_iter()
+        .filter(|m| !skipped.iter().any(|f| f.migration_name() == m.name))
+        .collect();
+
+    if args.require_clean {
+        check_clean(runner, &available, &applied)?;
+    }
+
+    if args.print_plan {
+        print_plan(args, db.db_type, &pending)?;
+        return Ok(0);
+    }
+
+    if let Some(path) = &args.plan_file {
+        Plan::load(path)?.check(db.db_type, &pending)?;
+        note!(
+            "{} Pending migrations match the plan in {}",
+            "✓".green(),
+            path
+        );
+    }
+
+    if args.repair {
+        repair_applied(args, db, runner, &available, &applied).await?;
+    }
+
+    // A run that crashed before recording leaves objects behind without a tracking row
+    let mut existing = Vec::new();
+    for migration in pending.iter().filter(|_| !args.fake) {
+        if objects_exist(db, migration).await? {
+            existing.push(migration.version);
+        }
+    }
+    if !existing.is_empty() {
+        for migration in pending.iter().filter(|m| existing.contains(&m.version)) {
+            let name = format!("{:03}_{}", migration.version, migration.name);
+            if !args.baseline_existing {
+                eprintln!(
+                    "{} {} is not tracked, but its tables and columns already exist",
+                    "Warning:".yellow(),
+                    name
+                );
+            } else if args.dry_run {
+                println!("  Would record (already exists): {}", name);
+            } else {
+                runner.baseline_migration(migration).await?;
+                note!("  {} {} (already exists)", "Recorded".green(), name);
+            }
+        }
+        if args.baseline_existing {
+            pending.retain(|m| !existing.contains(&m.version));
+        } else {
+            eprintln!("  Use --baseline-existing to record them without running their SQL");
+        }
+        note!();
+    }
 
     if pending.is_empty() {
-        println!();
+        note!();
         println!("{} Database is already up to date", "✓".green());
-        return Ok(());
+        if let Some(path) = &args.emit_metrics {
+            write_metrics(path, runner, &available, db.db_type).await?;
+        }
+        if let Some(path) = &args.report {
+            RunReport::default().write(path)?;
+        }
+        return Ok(0);
     }
 
-    println!("Found {} pending migration(s)", pending.len());
-    println!();
+    note!("Found {} pending migration(s)", pending.len());
+    note!();
+
+    if args.fake {
+        return fake_pending(args, runner, &available, db.db_type, &pending).await;
+    }
+
+    // The normalized unique index fails on existing case/whitespace variants
+    let case_insensitive = Feature::EmailCaseInsensitive.migration_name();
+    if pending.iter().any(|m| m.name == case_insensitive) {
+        check_duplicate_emails(db, config.table_prefix()).await?;
+    }
 
     if args.dry_run {
-        println!("{}", "Dry run - no changes will be made".yellow());
-        println!();
+        note!("{}", "Dry run - no changes will be made".yellow());
+        note!();
+        for hook in &config.hooks.pre_migrate {
+            println!("  Would run pre_migrate hook: {}", hook);
+        }
         for migration in &pending {
             println!("  Would apply: {:03}_{}", migration.version, migration.name);
         }
-        return Ok(());
+        for hook in &config.hooks.post_migrate {
+            println!("  Would run post_migrate hook: {}", hook);
+        }
+        return Ok(0);
+    }
+
+    for hook in &config.hooks.pre_migrate {
+        runner.run_hook(hook).await?;
+        note!("  {} pre_migrate hook {}", "Ran".green(), hook);
     }
 
-    // Apply migrations with progress
-    let pb = ProgressBar::new(pending.len() as u64);
+    // Apply migrations with progress, drawn on stderr so stdout stays pipeable;
+    // --progress-json replaces the bar with machine-readable events, --quiet drops it
+    let target = if args.progress_json || output::is_quiet() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    };
+    let pb = ProgressBar::with_draw_target(Some(pending.len() as u64), target);
+    let template = if colored::control::SHOULD_COLORIZE.should_colorize() {
+        "{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}"
+    } else {
+        "{spinner} [{bar:40}] {pos}/{len} {msg}"
+    };
     pb.set_style(
         ProgressStyle::default_bar()
-            .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .template(template)
             .expect("Invalid progress bar template")
             .progress_chars("#>-"),
     );
 
-    for migration in &pending {
+    // Concurrent builds are a PostgreSQL feature; SQLite keeps indexes in the migration transaction
+    let concurrent_indexes = args.concurrent_indexes && db.db_type == DatabaseType::Postgres;
+    if args.concurrent_indexes && !concurrent_indexes {
+        eprintln!(
+            "{} --concurrent-indexes only applies to PostgreSQL; ignoring it",
+            "Warning:".yellow()
+        );
+    }
+
+    // In `all` mode a single transaction spans every pending migration
+    let mut tx = match args.transaction_mode {
+        TransactionMode::All => Some(db.pool.begin().await?),
+        TransactionMode::PerMigration => None,
+    };
+
+    let mut report = RunReport::default();
+
+    for (i, migration) in pending.iter().enumerate() {
         let migration_name = format!("{:03}_{}", migration.version, migration.name);
         pb.set_message(migration_name.clone());
+        emit_progress(
+            args,
+            serde_json::json!({ "event": "start", "version": migration.version, "name": migration.name }),
+        );
 
         let start = Instant::now();
-        runner.apply_migration(migration).await?;
+        let result = match tx.as_mut() {
+            Some(tx) => runner.apply_migration_in(tx, migration).await,
+            None if concurrent_indexes => {
+                runner.apply_migration_concurrent_indexes(migration).await
+            }
+            None => runner.apply_migration(migration).await,
+        };
+        if let Err(e) = result {
+            emit_progress(
+                args,
+                serde_json::json!({ "event": "failed", "version": migration.version, "error": e.to_string() }),
+            );
+
+            // Each migration has its own transaction, so a failure leaves nothing recorded
+            if args.continue_on_error && tx.is_none() {
+                pb.println(format!("  {} {}", "Failed".red(), migration_name));
+                report.failed.push((migration_name, e.to_string()));
+                pb.inc(1);
+                continue;
+            }
+
+            pb.finish_and_clear();
+
+            report.failed.push((migration_name, e.to_string()));
+            report.not_attempted = pending[i + 1..]
+                .iter()
+                .map(|m| format!("{:03}_{}", m.version, m.name))
+                .collect();
+            report.rolled_back = tx.is_some();
+            report.print_summary();
+            if let Some(path) = &args.report {
+                report.write(path)?;
+            }
+
+            if tx.is_some() {
+                println!(
+                    "{} Rolled back all {} pending migration(s)",
+                    "✗".red(),
+                    pending.len()
+                );
+            }
+            return Err(e);
+        }
         let elapsed = start.elapsed();
+        report.applied.push(migration_name.clone());
+        emit_progress(
+            args,
+            serde_json::json!({ "event": "done", "version": migration.version, "elapsed_ms": elapsed.as_millis() as u64 }),
+        );
 
         pb.println(format!(
             "  {} {} ({}ms)",
@@ -94,14 +567,367 @@ pub async fn run(args: MigrateArgs) -> CliResult<()> {
         pb.inc(1);
     }
 
+    if let Some(tx) = tx {
+        tx.commit().await?;
+    }
+
     pb.finish_and_clear();
 
-    println!();
+    if !report.failed.is_empty() {
+        report.print_summary();
+        if let Some(path) = &args.report {
+            report.write(path)?;
+        }
+        return Err(CliError::migration(format!(
+            "{} of {} pending migration(s) failed",
+            report.failed.len(),
+            pending.len()
+        )));
+    }
+
+    for hook in &config.hooks.post_migrate {
+        runner.run_hook(hook).await?;
+        note!("  {} post_migrate hook {}", "Ran".green(), hook);
+    }
+
+    note!();
     println!(
         "{} Applied {} migration(s) successfully",
         "✓".green(),
         pending.len()
     );
 
+    if let Some(path) = &args.emit_metrics {
+        write_metrics(path, runner, &available, db.db_type).await?;
+    }
+    if let Some(path) = &args.report {
+        report.write(path)?;
+    }
+
+    Ok(pending.len())
+}
+
+/// Record the pending migrations as applied without running any of their SQL
+async fn fake_pending(
+    args: &MigrateArgs,
+    runner: &MigrationRunner<'_>,
+    available: &[Migration],
+    db_type: DatabaseType,
+    pending: &[&Migration],
+) -> CliResult<usize> {
+    let mut report = RunReport::default();
+    for migration in pending {
+        runner.baseline_migration(migration).await?;
+        let name = format!("{:03}_{}", migration.version, migration.name);
+        note!("  {} {}", "Faked".yellow(), name);
+        report.applied.push(name);
+    }
+
+    note!();
+    println!(
+        "{} Recorded {} migration(s) without running their SQL",
+        "✓".green(),
+        pending.len()
+    );
+
+    if let Some(path) = &args.emit_metrics {
+        write_metrics(path, runner, available, db_type).await?;
+    }
+    if let Some(path) = &args.report {
+        report.write(path)?;
+    }
+
+    Ok(pending.len())
+}
+
+/// Print a migration script's statements exactly as the runner sends them to the database
+pub(crate) fn print_statements(sql: &str) {
+    for (index, statement) in MigrationRunner::split_statements(sql).iter().enumerate() {
+        println!("    -- statement {}", index);
+        for line in statement.lines() {
+            println!("    {}", line);
+        }
+        println!("    ;");
+    }
+}
+
+/// Show the pending migrations and optionally save them as a plan for review
+fn print_plan(args: &MigrateArgs, db_type: DatabaseType, pending: &[&Migration]) -> CliResult<()> {
+    let plan = Plan::new(db_type, pending);
+
+    println!("Migration plan ({} pending):", plan.migrations.len());
+    for migration in pending {
+        println!(
+            "  {:03}_{}  {}",
+            migration.version, migration.name, migration.checksum
+        );
+
+        print_statements(&migration.up_sql);
+        println!();
+    }
+
+    if let Some(path) = &args.plan_out {
+        plan.write(path)?;
+        println!();
+        println!("{} Plan written to {}", "✓".green(), path);
+        println!(
+            "  Apply it with {}",
+            format!("authkit migrate --plan-file {}", path).cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Write a `--progress-json` event as a single line on stderr
+fn emit_progress(args: &MigrateArgs, event: serde_json::Value) {
+    if args.progress_json {
+        eprintln!("{}", event);
+    }
+}
+
+/// Outcome of each pending migration in a single run
+#[derive(Default)]
+struct RunReport {
+    applied: Vec<String>,
+    /// Migrations that failed and their error messages, in the order they ran
+    failed: Vec<(String, String)>,
+    not_attempted: Vec<String>,
+    /// Whether the applied migrations were undone with the run's transaction
+    rolled_back: bool,
+}
+
+impl RunReport {
+    fn print_summary(&self) {
+        println!();
+        println!("Migration run summary:");
+
+        let applied_label = if self.rolled_back {
+            "rolled back"
+        } else {
+            "applied"
+        };
+        for name in &self.applied {
+            println!("  {} {} ({})", "✓".green(), name, applied_label);
+        }
+        for (name, error) in &self.failed {
+            println!("  {} {} (failed)", "✗".red(), name);
+            println!("      {}", error);
+        }
+        for name in &self.not_attempted {
+            println!("  {} {} (not attempted)", "-".dimmed(), name);
+        }
+        println!();
+    }
+
+    fn write(&self, path: &str) -> CliResult<()> {
+        let failures: Vec<_> = self
+            .failed
+            .iter()
+            .map(|(name, error)| serde_json::json!({ "migration": name, "error": error }))
+            .collect();
+        let report = serde_json::json!({
+            "success": self.failed.is_empty(),
+            "applied": self.applied,
+            "failed": failures.first(),
+            "failures": failures,
+            "not_attempted": self.not_attempted,
+            "rolled_back": self.rolled_back,
+        });
+
+        fs::write(
+            path,
+            serde_json::to_string_pretty(&report).unwrap_or_default(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Write schema state as a Prometheus textfile for node_exporter's textfile collector
+async fn write_metrics(
+    path: &str,
+    runner: &MigrationRunner<'_>,
+    available: &[Migration],
+    db_type: DatabaseType,
+) -> CliResult<()> {
+    let applied = runner.get_applied_migrations().await?;
+    let pending = runner.get_pending_migrations(available, &applied).len();
+    let schema_version = applied.last().map(|m| m.version).unwrap_or(0);
+    let now = chrono::Utc::now().timestamp();
+
+    let metrics = [
+        (
+            "authkit_schema_version",
+            "gauge",
+            "Highest applied AuthKit migration version.",
+            schema_version as i64,
+        ),
+        (
+            "authkit_migrations_applied_total",
+            "counter",
+            "Number of AuthKit migrations applied to the database.",
+            applied.len() as i64,
+        ),
+        (
+            "authkit_pending_migrations",
+            "gauge",
+            "Number of AuthKit migrations enabled in config but not yet applied.",
+            pending as i64,
+        ),
+        (
+            "authkit_last_migrate_timestamp_seconds",
+            "gauge",
+            "Unix time of the last successful authkit migrate run.",
+            now,
+        ),
+    ];
+
+    let mut content = String::new();
+    for (name, kind, help, value) in metrics {
+        content.push_str(&format!("# HELP {} {}\n", name, help));
+        content.push_str(&format!("# TYPE {} {}\n", name, kind));
+        content.push_str(&format!("{}{{db_type=\"{}\"}} {}\n", name, db_type, value));
+    }
+
+    // Write to a temporary file and rename so the collector never reads a partial file
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)?;
+
+    note!("Metrics written to {}", Path::new(path).display());
+
+    Ok(())
+}
+
+/// Ensure the database holds nothing but AuthKit tables
+async fn check_empty(db: &Database, prefix: &str) -> CliResult<()> {
+    let foreign: Vec<String> = db
+        .list_tables()
+        .await?
+        .into_iter()
+        .map(|t| t.name)
+        .filter(|name| !is_authkit_table(name, prefix))
+        .collect();
+
+    if !foreign.is_empty() {
+        eprintln!(
+            "{} Refusing to migrate: found {} non-AuthKit table(s)",
+            "✗".red(),
+            foreign.len()
+        );
+        eprintln!("  Use --allow-dirty if this is the right database");
+        eprintln!();
+        return Err(CliError::DatabaseNotEmpty(foreign.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// Refuse a database whose base tables exist without any migration records,
+/// since they may come from another tool with an incompatible schema
+async fn check_untracked(
+    config: &AuthKitConfig,
+    db: &Database,
+    runner: &MigrationRunner<'_>,
+) -> CliResult<()> {
+    if !runner.get_applied_migrations().await?.is_empty() {
+        return Ok(());
+    }
+
+    let base = get_feature_migration_for(config, Feature::EmailPassword, db.db_type);
+    let mut found = Vec::new();
+    for table in objects::parse(&base.up_sql).tables {
+        if db.table_exists(&table.name).await? {
+            found.push(table.name);
+        }
+    }
+
+    if !found.is_empty() {
+        eprintln!(
+            "{} Refusing to migrate: {} already exist(s), but no AuthKit migrations are recorded",
+            "✗".red(),
+            found.join(", ")
+        );
+        eprintln!(
+            "  Use --baseline-existing if AuthKit created them, or --allow-dirty to migrate anyway"
+        );
+        eprintln!();
+        return Err(CliError::UntrackedTables(found.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// Report users whose emails collide once lower-cased and trimmed
+async fn check_duplicate_emails(db: &Database, prefix: &str) -> CliResult<()> {
+    enote!("Checking for case-insensitive duplicate emails...");
+
+    let users = format!("{}users", prefix);
+    if !db.table_exists(&users).await? {
+        enote!("  {} No users table yet", "✓".green());
+        enote!();
+        return Ok(());
+    }
+
+    let duplicates = find_duplicate_emails(&db.pool, db.db_type, &users).await?;
+    if duplicates.is_empty() {
+        enote!("  {} No duplicate emails found", "✓".green());
+        enote!();
+        return Ok(());
+    }
+
+    for (id, email) in &duplicates {
+        eprintln!("  {} {} {:?}", "✗".red(), id, email);
+    }
+    eprintln!();
+    eprintln!("  Merge or fix these users before enabling email_case_insensitive");
+    enote!();
+
+    Err(CliError::DuplicateEmails(duplicates.len()))
+}
+
+/// Ensure no applied migration is missing or has drifted from its checksum
+fn check_clean(
+    runner: &MigrationRunner<'_>,
+    available: &[Migration],
+    applied: &[AppliedMigration],
+) -> CliResult<()> {
+    enote!("Verifying database state...");
+
+    let missing: Vec<_> = runner
+        .get_migration_status(available, applied)
+        .into_iter()
+        .filter(|(_, _, state, _)| *state == MigrationState::Missing)
+        .collect();
+    let mismatches = runner.get_checksum_mismatches(available, applied);
+
+    for (version, name, _, _) in &missing {
+        eprintln!(
+            "  {} {:03}_{}: applied but not found in config",
+            "✗".red(),
+            version,
+            name
+        );
+    }
+
+    for (version, expected, actual) in &mismatches {
+        eprintln!(
+            "  {} {:03}: checksum mismatch (expected {}, got {})",
+            "✗".red(),
+            version,
+            expected,
+            actual
+        );
+    }
+
+    let problems = missing.len() + mismatches.len();
+    if problems > 0 {
+        enote!();
+        return Err(CliError::NotClean(problems));
+    }
+
+    enote!("  {} Database is clean", "✓".green());
+    enote!();
+
     Ok(())
 }
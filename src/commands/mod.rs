@@ -1,6 +1,23 @@
+pub mod baseline;
+pub mod clean_expired;
 pub mod destroy;
+pub mod diff;
+pub mod doctor;
+pub mod dump;
+pub mod dump_checksums;
+pub mod export;
+pub mod feature_set_hash;
 pub mod generate;
+pub mod help_features;
+pub mod history;
 pub mod init;
+pub mod list_features;
 pub mod migrate;
+pub mod redo;
+pub mod repair;
+pub mod rollback;
 pub mod schema;
 pub mod status;
+pub mod sync;
+pub mod tracking;
+pub mod verify;
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use colored::Colorize;
+
+use crate::cli::RedoArgs;
+use crate::config::{AuthKitConfig, Feature};
+use crate::database::{ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::migrations::lock::MigrationLock;
+use crate::migrations::{get_migrations_for_database, runner::MigrationRunner};
+use crate::output::note;
+use crate::schema::MIGRATIONS_TABLE;
+
+pub async fn run(args: RedoArgs) -> CliResult<()> {
+    // Load configuration
+    let config = AuthKitConfig::load(&args.config)?;
+
+    let connect = ConnectOptions::from(&args.connect).with_config(&config);
+    let db = Database::connect(&args.db_url, &connect).await?;
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+
+    if !db.table_exists(MIGRATIONS_TABLE).await? {
+        return Err(CliError::migration("No migrations have been applied yet"));
+    }
+
+    let applied = runner.get_applied_migrations().await?;
+    let Some(latest) = applied.iter().max_by_key(|m| m.version) else {
+        return Err(CliError::migration("No migrations have been applied yet"));
+    };
+
+    // Checksums must match the dialect of the database being redone
+    let available = get_migrations_for_database(&config, db.db_type)?;
+    let Some(migration) = available.iter().find(|m| m.version == latest.version) else {
+        return Err(CliError::migration(format!(
+            "Latest migration {:03}_{} is not enabled in the config",
+            latest.version, latest.name
+        )));
+    };
+
+    // Rolling back the base migration drops every user
+    if migration.version == Feature::EmailPassword.version() && !args.force {
+        let users = db
+            .count_rows(&format!("{}users", config.table_prefix()))
+            .await
+            .unwrap_or(0);
+        if users > 0 {
+            return Err(CliError::migration(format!(
+                "Redoing {:03}_{} would delete {} user(s). Use --force to redo anyway.",
+                migration.version, migration.name, users
+            )));
+        }
+    }
+
+    let name = format!("{:03}_{}", migration.version, migration.name);
+
+    // Fail fast rather than wait: redo is a development tool
+    let lock = MigrationLock::acquire(&db.pool, db.db_type, Duration::ZERO).await?;
+
+    // Down and up run in one transaction so a failed up leaves the old version in place
+    let result = async {
+        let mut tx = db.pool.begin().await?;
+        runner.rollback_migration_in(&mut tx, migration).await?;
+        runner.apply_migration_in(&mut tx, migration).await?;
+        tx.commit().await?;
+        Ok::<_, CliError>(())
+    }
+    .await;
+    lock.release_or_warn().await;
+    result?;
+
+    note!("  {} {}", "Rolled back".yellow(), name);
+    note!("  {} {}", "Applied".green(), name);
+    note!();
+    if !migration.matches_checksum(&latest.checksum) {
+        println!(
+            "{} Redid {} (checksum {}... -> {}...)",
+            "✓".green(),
+            name,
+            &latest.checksum[..latest.checksum.len().min(8)],
+            &migration.checksum[..8]
+        );
+    } else {
+        println!("{} Redid {}", "✓".green(), name);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use colored::Colorize;
+
+use crate::cli::RepairArgs;
+use crate::config::AuthKitConfig;
+use crate::database::{ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::migrations::{get_migrations_for_database, runner::MigrationRunner};
+use crate::output::note;
+
+pub async fn run(args: RepairArgs) -> CliResult<()> {
+    let config = AuthKitConfig::load(&args.config)?;
+
+    let connect = ConnectOptions::from(&args.connect).with_config(&config);
+    let db = Database::connect(&args.db_url, &connect).await?;
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+
+    runner.ensure_migrations_table().await?;
+
+    // Checksums must match the dialect of the database being repaired
+    let available = get_migrations_for_database(&config, db.db_type)?;
+    let applied = runner.get_applied_migrations().await?;
+
+    // Validate everything before changing any row
+    let mut to_record = Vec::new();
+    for version in &args.mark_applied {
+        let migration = available
+            .iter()
+            .find(|m| m.version == *version)
+            .ok_or_else(|| {
+                CliError::migration(format!(
+                    "version {} is not an enabled feature or custom migration",
+                    version
+                ))
+            })?;
+        to_record.push(migration);
+    }
+    for version in &args.mark_unapplied {
+        if !applied.iter().any(|m| m.version == *version) {
+            return Err(CliError::migration(format!(
+                "version {} is not recorded in _authkit_migrations",
+                version
+            )));
+        }
+    }
+
+    note!(
+        "Repairing _authkit_migrations {}",
+        "(no SQL will be executed)".dimmed()
+    );
+    note!();
+
+    for migration in to_record {
+        runner.baseline_migration(migration).await?;
+        note!(
+            "  {} {:03}_{}",
+            "Marked applied".green(),
+            migration.version,
+            migration.name
+        );
+    }
+
+    for version in &args.mark_unapplied {
+        runner.remove_migration_record(*version).await?;
+        let name = applied
+            .iter()
+            .find(|m| m.version == *version)
+            .map(|m| m.name.as_str())
+            .unwrap_or_default();
+        note!("  {} {:03}_{}", "Marked unapplied".yellow(), version, name);
+    }
+
+    note!();
+    println!(
+        "{} Updated {} tracking row(s)",
+        "✓".green(),
+        args.mark_applied.len() + args.mark_unapplied.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use colored::Colorize;
+use dialoguer::Confirm;
+
+use crate::cli::RollbackArgs;
+use crate::commands::migrate::print_statements;
+use crate::config::{AuthKitConfig, Feature};
+use crate::database::{ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::migrations::lock::MigrationLock;
+use crate::migrations::{
+    get_migrations_for_database, runner::MigrationRunner, AppliedMigration, Migration,
+};
+use crate::output::note;
+use crate::schema::get_feature_migration_for;
+
+pub async fn run(args: RollbackArgs) -> CliResult<()> {
+    let config = AuthKitConfig::load(&args.config)?;
+
+    let connect = ConnectOptions::from(&args.connect).with_config(&config);
+    let db = Database::connect(&args.db_url, &connect).await?;
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+
+    let available = get_migrations_for_database(&config, db.db_type)?;
+    let applied = runner.get_applied_migrations().await?;
+
+    // Without --to only the most recent migration is rolled back
+    let mut selected = runner.get_rollback_migrations(&applied, args.to.unwrap_or(0));
+    if args.to.is_none() {
+        selected.truncate(1);
+    }
+
+    if selected.is_empty() {
+        match args.to {
+            Some(target) => println!(
+                "{} Nothing to roll back: no applied migrations above version {}",
+                "✓".green(),
+                target
+            ),
+            None => println!(
+                "{} Nothing to roll back: no migrations applied",
+                "✓".green()
+            ),
+        }
+        return Ok(());
+    }
+
+    let to_roll_back = selected
+        .iter()
+        .map(|applied| down_migration(applied, &available, &config, &db))
+        .collect::<CliResult<Vec<_>>>()?;
+
+    println!();
+    println!("Roll back (newest first):");
+    for migration in &to_roll_back {
+        println!("  - {:03}_{}", migration.version, migration.name);
+    }
+    println!();
+
+    if args.print_plan {
+        println!("Rollback plan:");
+        for migration in &to_roll_back {
+            println!("  {:03}_{}", migration.version, migration.name);
+            print_statements(&migration.down_sql);
+            println!();
+        }
+    }
+
+    if args.dry_run || args.print_plan {
+        println!("{}", "Dry run - no changes were made".yellow());
+        return Ok(());
+    }
+
+    if !args.force {
+        let warning = if to_roll_back.iter().any(|m| m.version == 1) {
+            "⚠️  This includes the base migration: every AuthKit table is dropped, including its data."
+        } else {
+            "⚠️  Rolling back drops the migrations' tables and columns, including their data."
+        };
+        println!("{}", warning.red().bold());
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Roll back {} migration(s)?", to_roll_back.len()))
+            .default(false)
+            .interact()
+            .map_err(|_| CliError::Cancelled)?;
+
+        if !confirmed {
+            println!();
+            println!("Operation cancelled");
+            return Ok(());
+        }
+        note!();
+    }
+
+    let lock = MigrationLock::acquire(&db.pool, db.db_type, Duration::ZERO).await?;
+    let result = async {
+        for migration in &to_roll_back {
+            runner.rollback_migration(migration).await?;
+            note!(
+                "  {} {:03}_{}",
+                "Rolled back".yellow(),
+                migration.version,
+                migration.name
+            );
+        }
+        Ok::<_, CliError>(())
+    }
+    .await;
+    lock.release_or_warn().await;
+    result?;
+
+    note!();
+    println!(
+        "{} Rolled back {} migration(s)",
+        "✓".green(),
+        to_roll_back.len()
+    );
+
+    Ok(())
+}
+
+/// Find the down SQL of an applied migration: from the config first (which
+/// includes custom migrations), then from the built-in features
+fn down_migration(
+    applied: &AppliedMigration,
+    available: &[Migration],
+    config: &AuthKitConfig,
+    db: &Database,
+) -> CliResult<Migration> {
+    if let Some(migration) = available
+        .iter()
+        .find(|m| m.version == applied.version && m.name == applied.name)
+    {
+        return Ok(migration.clone());
+    }
+
+    let feature = Feature::all()
+        .iter()
+        .find(|f| f.version() == applied.version && f.migration_name() == applied.name)
+        .ok_or_else(|| {
+            CliError::migration(format!(
+                "Can't roll back {:03}_{}: it is neither in the config nor a built-in feature, so its down SQL is unavailable. \
+                 Remove its row with `authkit repair --mark-unapplied {}` if it was dropped by hand.",
+                applied.version, applied.name, applied.version
+            ))
+        })?;
+    Ok(get_feature_migration_for(config, *feature, db.db_type))
+}
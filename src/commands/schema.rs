@@ -2,15 +2,31 @@ use chrono::Utc;
 use colored::Colorize;
 
 use crate::cli::{DatabaseType, OutputFormat, SchemaArgs};
-use crate::config::AuthKitConfig;
-use crate::database::Database;
-use crate::error::CliResult;
-use crate::migrations::get_migrations_from_config;
+use crate::config::{AuthKitConfig, Feature};
+use crate::database::{redact_url, ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::migrations::{get_migrations_from_config, Migration};
+use crate::schema::{get_feature_migration_for, is_authkit_table, objects};
 
 pub async fn run(args: SchemaArgs) -> CliResult<()> {
     // If db_url is provided, show actual schema from database
     if let Some(db_url) = &args.db_url {
-        return show_actual_schema(db_url, args.format).await;
+        let connect = ConnectOptions::from(&args.connect).read_only();
+        // The config, when there is one, tells which prefixed tables belong to AuthKit
+        let prefix = args
+            .config
+            .as_deref()
+            .and_then(|path| AuthKitConfig::load(path).ok())
+            .and_then(|config| config.database.table_prefix)
+            .unwrap_or_default();
+        return show_actual_schema(
+            db_url,
+            args.format,
+            args.table.as_deref(),
+            &prefix,
+            &connect,
+        )
+        .await;
     }
 
     // Load configuration if available, otherwise use defaults
@@ -20,10 +36,10 @@ pub async fn run(args: SchemaArgs) -> CliResult<()> {
             Err(_) => {
                 // If config doesn't exist, use defaults with specified db type
                 let db_type = args.db.unwrap_or(DatabaseType::Postgres);
-                println!(
+                eprintln!(
                     "{} Config not found, using defaults for {}",
                     "Note:".yellow(),
-                    db_type.to_string()
+                    db_type
                 );
                 AuthKitConfig::default_config(db_type)
             }
@@ -38,22 +54,146 @@ pub async fn run(args: SchemaArgs) -> CliResult<()> {
         .db
         .unwrap_or_else(|| config.database_type().unwrap_or(DatabaseType::Postgres));
 
-    show_template_schema(&config, db_type, args.format)
+    if let Some(against) = &args.against {
+        let previous = AuthKitConfig::load(against)?;
+        return show_template_diff(&previous, &config, db_type, against, args.config.as_deref());
+    }
+
+    let only = args.feature.as_deref().map(Feature::parse).transpose()?;
+    show_template_schema(
+        &config,
+        db_type,
+        args.format,
+        args.group_by_table,
+        args.include_down,
+        only,
+    )
+}
+
+/// Print a unified-style diff of the feature SQL two configs generate
+fn show_template_diff(
+    previous: &AuthKitConfig,
+    current: &AuthKitConfig,
+    db_type: DatabaseType,
+    previous_path: &str,
+    current_path: Option<&str>,
+) -> CliResult<()> {
+    let migrations = |config: &AuthKitConfig| -> Vec<Migration> {
+        config
+            .enabled_features()
+            .into_iter()
+            .map(|feature| get_feature_migration_for(config, feature, db_type))
+            .collect()
+    };
+    let old = migrations(previous);
+    let new = migrations(current);
+
+    let mut versions: Vec<u32> = old.iter().chain(&new).map(|m| m.version).collect();
+    versions.sort();
+    versions.dedup();
+
+    println!("--- {}", previous_path);
+    println!("+++ {}", current_path.unwrap_or("(defaults)"));
+
+    let find = |migrations: &[Migration], version| {
+        migrations
+            .iter()
+            .find(|m| m.version == version)
+            .map(|m| (format!("{:03}_{}", m.version, m.name), m.up_sql.clone()))
+    };
+
+    let mut changed = 0;
+    for version in versions {
+        let (label, old_sql, new_sql) = match (find(&old, version), find(&new, version)) {
+            (Some((label, old_sql)), None) => {
+                (format!("{} (removed)", label), old_sql, String::new())
+            }
+            (None, Some((label, new_sql))) => {
+                (format!("{} (added)", label), String::new(), new_sql)
+            }
+            (Some((label, old_sql)), Some((_, new_sql))) if old_sql != new_sql => {
+                (format!("{} (changed)", label), old_sql, new_sql)
+            }
+            _ => continue,
+        };
+
+        changed += 1;
+        println!("{}", format!("@@ {} @@", label).cyan());
+        for (tag, line) in diff_lines(&old_sql, &new_sql) {
+            match tag {
+                '+' => println!("{}", format!("+{}", line).green()),
+                '-' => println!("{}", format!("-{}", line).red()),
+                _ => println!(" {}", line),
+            }
+        }
+    }
+
+    if changed == 0 {
+        println!("-- No differences in generated feature SQL");
+    }
+
+    Ok(())
+}
+
+/// Line diff based on the longest common subsequence: `' '` for kept lines,
+/// `'-'` for lines only in `old` and `'+'` for lines only in `new`
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = length of the LCS of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+    lines
 }
 
 fn show_template_schema(
     config: &AuthKitConfig,
     db_type: DatabaseType,
     format: OutputFormat,
+    group_by_table: bool,
+    include_down: bool,
+    only: Option<Feature>,
 ) -> CliResult<()> {
-    let migrations = get_migrations_from_config(config);
+    let (migrations, features) = match only {
+        Some(feature) => (
+            vec![get_feature_migration_for(config, feature, db_type)],
+            vec![feature],
+        ),
+        None => (
+            get_migrations_from_config(config)?,
+            config.enabled_features(),
+        ),
+    };
     let db_name = match db_type {
         DatabaseType::Sqlite => "SQLite",
         DatabaseType::Postgres => "PostgreSQL",
     };
 
-    let features = config.enabled_features();
-
     match format {
         OutputFormat::Sql => {
             println!("-- AuthKit Schema for {}", db_name);
@@ -72,7 +212,15 @@ fn show_template_schema(
                     migration.name, migration.version, migration.name
                 );
                 println!("-- ============================================================");
-                println!("{}", migration.up_sql);
+                if group_by_table {
+                    println!("{}", objects::group_by_table(&migration.up_sql));
+                } else {
+                    println!("{}", migration.up_sql);
+                }
+                if include_down {
+                    println!("-- DOWN");
+                    println!("{}", migration.down_sql.trim());
+                }
                 println!();
             }
         }
@@ -115,13 +263,18 @@ fn show_template_schema(
             for migration in &migrations {
                 println!(
                     "{} {:03}_{} ({})",
-                    "Feature".cyan(),
+                    "Migration".cyan(),
                     migration.version,
                     migration.name,
                     format!("checksum: {}...", &migration.checksum[..8]).dimmed()
                 );
                 println!("{}", "─".repeat(60));
                 println!("{}", migration.up_sql.trim());
+                if include_down {
+                    println!();
+                    println!("-- DOWN");
+                    println!("{}", migration.down_sql.trim());
+                }
                 println!();
             }
         }
@@ -130,8 +283,14 @@ fn show_template_schema(
     Ok(())
 }
 
-async fn show_actual_schema(db_url: &str, format: OutputFormat) -> CliResult<()> {
-    let db = Database::connect(db_url).await?;
+async fn show_actual_schema(
+    db_url: &str,
+    format: OutputFormat,
+    table: Option<&str>,
+    prefix: &str,
+    connect: &ConnectOptions,
+) -> CliResult<()> {
+    let db = Database::connect(db_url, connect).await?;
 
     let db_type_name = match db.db_type {
         DatabaseType::Sqlite => "SQLite",
@@ -139,7 +298,16 @@ async fn show_actual_schema(db_url: &str, format: OutputFormat) -> CliResult<()>
     };
 
     // Get table list
-    let tables = get_table_list(&db).await?;
+    let mut tables = db.list_tables().await?;
+    if let Some(name) = table {
+        tables.retain(|t| t.name == name);
+        if tables.is_empty() {
+            return Err(CliError::Other(format!(
+                "table '{}' not found in the database",
+                name
+            )));
+        }
+    }
 
     // Get migration status
     let migrations_applied = get_applied_migration_count(&db).await.unwrap_or(0);
@@ -147,7 +315,7 @@ async fn show_actual_schema(db_url: &str, format: OutputFormat) -> CliResult<()>
     match format {
         OutputFormat::Sql => {
             println!("-- Actual schema from database");
-            println!("-- URL: {}", db_url);
+            println!("-- URL: {}", redact_url(db_url));
             println!("-- Type: {}", db_type_name);
             println!("-- Applied migrations: {}", migrations_applied);
             println!();
@@ -164,13 +332,14 @@ async fn show_actual_schema(db_url: &str, format: OutputFormat) -> CliResult<()>
         }
         OutputFormat::Json => {
             let schema = serde_json::json!({
-                "database_url": db_url,
+                "database_url": redact_url(db_url),
                 "database_type": db_type_name,
                 "applied_migrations": migrations_applied,
                 "tables": tables.iter().map(|t| {
                     serde_json::json!({
                         "name": t.name,
                         "create_sql": t.create_sql,
+                        "columns": t.columns,
                     })
                 }).collect::<Vec<_>>(),
             });
@@ -181,7 +350,7 @@ async fn show_actual_schema(db_url: &str, format: OutputFormat) -> CliResult<()>
             );
         }
         OutputFormat::Table => {
-            println!("Actual schema from: {}", db_url.green());
+            println!("Actual schema from: {}", redact_url(db_url).green());
             println!("Database type: {}", db_type_name.cyan());
             println!("Applied migrations: {}", migrations_applied);
             println!();
@@ -190,15 +359,17 @@ async fn show_actual_schema(db_url: &str, format: OutputFormat) -> CliResult<()>
                 println!("{} No tables found", "!".yellow());
             } else {
                 println!("Tables ({}):", tables.len());
-                for table in &tables {
-                    let is_authkit = table.name.starts_with("_authkit")
-                        || ["users", "accounts", "sessions", "verification"]
-                            .contains(&table.name.as_str());
-
-                    if is_authkit {
-                        println!("  {} {} (AuthKit)", "✓".green(), table.name);
+                for t in &tables {
+                    if is_authkit_table(&t.name, prefix) {
+                        println!("  {} {} (AuthKit)", "✓".green(), t.name);
                     } else {
-                        println!("  {} {}", "○".dimmed(), table.name);
+                        println!("  {} {}", "○".dimmed(), t.name);
+                    }
+                    // A single table gets its columns listed too
+                    if table.is_some() {
+                        for column in &t.columns {
+                            println!("      {}", column);
+                        }
                     }
                 }
             }
@@ -208,37 +379,6 @@ async fn show_actual_schema(db_url: &str, format: OutputFormat) -> CliResult<()>
     Ok(())
 }
 
-struct TableInfo {
-    name: String,
-    create_sql: Option<String>,
-}
-
-async fn get_table_list(db: &Database) -> CliResult<Vec<TableInfo>> {
-    let rows: Vec<(String, Option<String>)> = match db.db_type {
-        DatabaseType::Sqlite => {
-            let query =
-                "SELECT name, sql FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name";
-            sqlx::query_as(query).fetch_all(&db.pool).await?
-        }
-        DatabaseType::Postgres => {
-            let query = r#"
-                SELECT
-                    tablename::text as name,
-                    NULL::text as sql
-                FROM pg_tables
-                WHERE schemaname = 'public'
-                ORDER BY tablename
-            "#;
-            sqlx::query_as(query).fetch_all(&db.pool).await?
-        }
-    };
-
-    Ok(rows
-        .into_iter()
-        .map(|(name, create_sql)| TableInfo { name, create_sql })
-        .collect())
-}
-
 async fn get_applied_migration_count(db: &Database) -> CliResult<i64> {
     // Check if migrations table exists first
     let exists = db.table_exists("_authkit_migrations").await?;
@@ -249,3 +389,27 @@ async fn get_applied_migration_count(db: &Database) -> CliResult<i64> {
     let count = db.count_rows("_authkit_migrations").await?;
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "CREATE TABLE a (\n    id TEXT\n);";
+        let new = "CREATE TABLE a (\n    id TEXT,\n    name TEXT\n);";
+        assert_eq!(
+            diff_lines(old, new),
+            vec![
+                (' ', "CREATE TABLE a ("),
+                ('-', "    id TEXT"),
+                ('+', "    id TEXT,"),
+                ('+', "    name TEXT"),
+                (' ', ");"),
+            ]
+        );
+        assert!(diff_lines("", "SELECT 1;")
+            .iter()
+            .all(|(tag, _)| *tag == '+'));
+    }
+}
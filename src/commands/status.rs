@@ -1,11 +1,13 @@
-use chrono::{TimeZone, Utc};
+use chrono::{Local, SecondsFormat, TimeZone, Utc};
 use colored::Colorize;
 use tabled::{Table, Tabled};
 
-use crate::cli::StatusArgs;
+use crate::cli::{StatusArgs, StatusFormat, TimeFormat};
 use crate::config::AuthKitConfig;
-use crate::database::Database;
-use crate::error::CliResult;
+use crate::csv;
+use crate::database::{redact_url, retry_on_lock, ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::junit::{self, TestCase};
 use crate::migrations::{get_migrations_from_config, runner::MigrationRunner, MigrationState};
 
 #[derive(Tabled)]
@@ -16,42 +18,134 @@ struct MigrationRow {
     name: String,
     #[tabled(rename = "Applied At")]
     applied_at: String,
+    #[tabled(rename = "Duration")]
+    duration: String,
     #[tabled(rename = "Status")]
     status: String,
 }
 
 pub async fn run(args: StatusArgs) -> CliResult<()> {
+    let (pending, missing) = retry_on_lock(args.retry_on_lock, || show_status(&args)).await?;
+
+    if args.check && !args.exit_zero && (pending > 0 || missing > 0) {
+        return Err(CliError::OutOfDate { pending, missing });
+    }
+
+    Ok(())
+}
+
+/// Print the status, returning the number of pending and missing migrations
+async fn show_status(args: &StatusArgs) -> CliResult<(usize, usize)> {
     // Load configuration
     let config = AuthKitConfig::load(&args.config)?;
     let db_type = config.database_type()?;
+    let human = args.format == StatusFormat::Human;
 
-    println!();
-    println!("Configuration: {}", args.config.cyan());
-    println!();
+    if human {
+        println!();
+        println!("Configuration: {}", args.config.cyan());
+        println!();
 
-    // Show enabled features
-    println!("Enabled features:");
-    for feature in config.enabled_features() {
-        println!("  {} {}", "✓".green(), feature.display_name());
+        // Show enabled features
+        println!("Enabled features:");
+        for feature in config.enabled_features() {
+            println!("  {} {}", "✓".green(), feature.display_name());
+        }
+        println!();
     }
-    println!();
 
-    let db = Database::connect(&args.db_url).await?;
+    let connect = ConnectOptions::from(&args.connect).with_config(&config);
+    let db = Database::connect(&args.db_url, &connect).await?;
     let runner = MigrationRunner::new(&db.pool, db.db_type);
 
     // Check if migrations table exists
     runner.ensure_migrations_table().await?;
 
-    let available = get_migrations_from_config(&config);
+    let available = get_migrations_from_config(&config)?;
     let applied = runner.get_applied_migrations().await?;
     let statuses = runner.get_migration_status(&available, &applied);
 
+    let pending_count = statuses
+        .iter()
+        .filter(|(_, _, state, _)| *state == MigrationState::Pending)
+        .count();
+
+    let missing_count = statuses
+        .iter()
+        .filter(|(_, _, state, _)| *state == MigrationState::Missing)
+        .count();
+
+    if args.format == StatusFormat::Json {
+        let applied_count = statuses
+            .iter()
+            .filter(|(_, _, state, _)| *state == MigrationState::Applied)
+            .count();
+        let status = serde_json::json!({
+            "schema_version": applied.last().map(|m| m.version).unwrap_or(0),
+            "migrations": statuses.iter().map(|(version, name, state, applied_at)| {
+                serde_json::json!({
+                    "version": version,
+                    "name": name,
+                    "state": state.as_str().to_lowercase(),
+                    "applied_at": applied_at,
+                })
+            }).collect::<Vec<_>>(),
+            "summary": {
+                "applied": applied_count,
+                "pending": pending_count,
+                "missing": missing_count,
+            },
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&status).unwrap_or_default()
+        );
+        return Ok((pending_count, missing_count));
+    }
+
+    if args.format == StatusFormat::Csv {
+        let rows: Vec<Vec<String>> = statuses
+            .iter()
+            .map(|(version, name, state, applied_at)| {
+                vec![
+                    version.to_string(),
+                    name.clone(),
+                    state.as_str().to_lowercase(),
+                    applied_at
+                        .and_then(|ts| format_applied_at(ts, TimeFormat::Iso))
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect();
+        print!("{}", csv::render(&csv::MIGRATION_HEADER, &rows));
+        return Ok((pending_count, missing_count));
+    }
+
+    if args.format == StatusFormat::Junit {
+        let cases: Vec<TestCase> = statuses
+            .iter()
+            .map(|(version, name, state, _)| {
+                let label = format!("{:03}_{}", version, name);
+                match state {
+                    MigrationState::Applied => TestCase::passed(label),
+                    MigrationState::Pending => TestCase::failed(label, "pending: not yet applied"),
+                    MigrationState::Missing => TestCase::failed(
+                        label,
+                        "missing: applied in the database but not enabled in config",
+                    ),
+                }
+            })
+            .collect();
+        print!("{}", junit::render("authkit.status", &cases));
+        return Ok((pending_count, missing_count));
+    }
+
     let db_type_name = match db.db_type {
         crate::cli::DatabaseType::Sqlite => "SQLite",
         crate::cli::DatabaseType::Postgres => "PostgreSQL",
     };
 
-    println!("Database: {} ({})", args.db_url, db_type_name);
+    println!("Database: {} ({})", redact_url(&args.db_url), db_type_name);
     println!("Config Database Type: {}", db_type.to_string().cyan());
     println!(
         "Schema Version: {}",
@@ -64,19 +158,21 @@ pub async fn run(args: StatusArgs) -> CliResult<()> {
             "{} No migrations defined for enabled features",
             "!".yellow()
         );
-        return Ok(());
+        return Ok((0, 0));
     }
 
     let rows: Vec<MigrationRow> = statuses
         .iter()
         .map(|(version, name, state, applied_at)| {
             let applied_at_str = applied_at
-                .map(|ts| {
-                    Utc.timestamp_opt(ts, 0)
-                        .single()
-                        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                        .unwrap_or_else(|| "-".to_string())
-                })
+                .and_then(|ts| format_applied_at(ts, args.timezone))
+                .unwrap_or_else(|| "-".to_string());
+
+            let duration = applied
+                .iter()
+                .find(|m| m.version == *version)
+                .and_then(|m| m.execution_ms)
+                .map(|ms| format!("{}ms", ms))
                 .unwrap_or_else(|| "-".to_string());
 
             let status_str = match state {
@@ -89,6 +185,7 @@ pub async fn run(args: StatusArgs) -> CliResult<()> {
                 version: format!("{:03}", version),
                 name: name.clone(),
                 applied_at: applied_at_str,
+                duration,
                 status: status_str,
             }
         })
@@ -98,22 +195,12 @@ pub async fn run(args: StatusArgs) -> CliResult<()> {
     println!("{}", table);
     println!();
 
-    let pending_count = statuses
-        .iter()
-        .filter(|(_, _, state, _)| *state == MigrationState::Pending)
-        .count();
-
-    let missing_count = statuses
-        .iter()
-        .filter(|(_, _, state, _)| *state == MigrationState::Missing)
-        .count();
-
     if pending_count == 0 && missing_count == 0 {
         println!("{} Database is up to date", "✓".green());
     } else {
         if pending_count > 0 {
             println!("{} {} pending migration(s)", "!".yellow(), pending_count);
-            println!("  Run {} to apply", "authkit migrate --db-url <URL>".cyan());
+            eprintln!("  Run {} to apply", "authkit migrate --db-url <URL>".cyan());
         }
         if missing_count > 0 {
             println!(
@@ -121,9 +208,59 @@ pub async fn run(args: StatusArgs) -> CliResult<()> {
                 "!".red(),
                 missing_count
             );
-            println!("  This may indicate features were disabled or migrations were modified");
+            eprintln!("  This may indicate features were disabled or migrations were modified");
         }
     }
 
-    Ok(())
+    Ok((pending_count, missing_count))
+}
+
+/// Format a unix timestamp for the Applied At column
+pub fn format_applied_at(timestamp: i64, format: TimeFormat) -> Option<String> {
+    let utc = Utc.timestamp_opt(timestamp, 0).single()?;
+    Some(match format {
+        TimeFormat::Utc => utc.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        TimeFormat::Local => utc
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S %:z")
+            .to_string(),
+        TimeFormat::Iso => utc.to_rfc3339_opts(SecondsFormat::Secs, true),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2025-01-19 10:30:00 UTC
+    const APPLIED_AT: i64 = 1_737_282_600;
+
+    #[test]
+    fn test_format_applied_at_utc() {
+        assert_eq!(
+            format_applied_at(APPLIED_AT, TimeFormat::Utc).unwrap(),
+            "2025-01-19 10:30:00 UTC"
+        );
+    }
+
+    #[test]
+    fn test_format_applied_at_iso() {
+        assert_eq!(
+            format_applied_at(APPLIED_AT, TimeFormat::Iso).unwrap(),
+            "2025-01-19T10:30:00Z"
+        );
+    }
+
+    #[test]
+    fn test_format_applied_at_local() {
+        let expected = Local
+            .timestamp_opt(APPLIED_AT, 0)
+            .unwrap()
+            .format("%Y-%m-%d %H:%M:%S %:z")
+            .to_string();
+        assert_eq!(
+            format_applied_at(APPLIED_AT, TimeFormat::Local).unwrap(),
+            expected
+        );
+    }
 }
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use colored::Colorize;
+use dialoguer::Confirm;
+
+use crate::cli::SyncArgs;
+use crate::config::{AuthKitConfig, Feature};
+use crate::database::{ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::migrations::lock::MigrationLock;
+use crate::migrations::{get_migrations_for_database, runner::MigrationRunner, Migration};
+use crate::output::note;
+use crate::schema::get_feature_migration_for;
+
+pub async fn run(args: SyncArgs) -> CliResult<()> {
+    let config = AuthKitConfig::load(&args.config)?;
+
+    let connect = ConnectOptions::from(&args.connect).with_config(&config);
+    let db = Database::connect(&args.db_url, &connect).await?;
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+
+    runner.ensure_migrations_table().await?;
+
+    // Checksums and down SQL must match the dialect of the connected database
+    let available = get_migrations_for_database(&config, db.db_type)?;
+    let applied = runner.get_applied_migrations().await?;
+    let pending = runner.get_pending_migrations(&available, &applied);
+
+    // Disabled features are no longer in the config, so their down SQL comes from the built-ins
+    let mut to_roll_back: Vec<Migration> = Vec::new();
+    for extra in runner.get_extra_migrations(&available, &applied) {
+        let feature = Feature::all()
+            .iter()
+            .find(|f| f.version() == extra.version && f.migration_name() == extra.name)
+            .ok_or_else(|| {
+                CliError::migration(format!(
+                    "Can't roll back {:03}_{}: it isn't a built-in feature, so its down SQL is unavailable. \
+                     Remove its row with `authkit repair --mark-unapplied {}` if it was dropped by hand.",
+                    extra.version, extra.name, extra.version
+                ))
+            })?;
+        to_roll_back.push(get_feature_migration_for(&config, *feature, db.db_type));
+    }
+
+    if to_roll_back.is_empty() && pending.is_empty() {
+        println!("{} Database already matches the config", "✓".green());
+        return Ok(());
+    }
+
+    println!();
+    if !to_roll_back.is_empty() {
+        println!("Roll back (features disabled in the config):");
+        for migration in &to_roll_back {
+            println!("  - {:03}_{}", migration.version, migration.name);
+        }
+        println!();
+    }
+    if !pending.is_empty() {
+        println!("Apply (features enabled in the config):");
+        for migration in &pending {
+            println!("  + {:03}_{}", migration.version, migration.name);
+        }
+        println!();
+    }
+
+    if args.dry_run {
+        println!("{}", "Dry run - no changes were made".yellow());
+        return Ok(());
+    }
+
+    if !to_roll_back.is_empty() && !args.force {
+        println!(
+            "{}",
+            "⚠️  Rolling back drops the tables and columns of disabled features, including their data."
+                .red()
+                .bold()
+        );
+        let confirmed = Confirm::new()
+            .with_prompt("Sync the database with the config?")
+            .default(false)
+            .interact()
+            .map_err(|_| CliError::Cancelled)?;
+
+        if !confirmed {
+            println!();
+            println!("Operation cancelled");
+            return Ok(());
+        }
+        note!();
+    }
+
+    let lock = MigrationLock::acquire(&db.pool, db.db_type, Duration::ZERO).await?;
+    let result = async {
+        for migration in &to_roll_back {
+            runner.rollback_migration(migration).await?;
+            note!(
+                "  {} {:03}_{}",
+                "Rolled back".yellow(),
+                migration.version,
+                migration.name
+            );
+        }
+        for migration in &pending {
+            runner.apply_migration(migration).await?;
+            note!(
+                "  {} {:03}_{}",
+                "Applied".green(),
+                migration.version,
+                migration.name
+            );
+        }
+        Ok::<_, CliError>(())
+    }
+    .await;
+    lock.release_or_warn().await;
+    result?;
+
+    note!();
+    println!(
+        "{} Rolled back {} and applied {} migration(s)",
+        "✓".green(),
+        to_roll_back.len(),
+        pending.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use colored::Colorize;
+use dialoguer::Confirm;
+
+use crate::cli::{TrackingArgs, TrackingCommand, TrackingPruneArgs};
+use crate::config::Feature;
+use crate::database::{ConnectOptions, Database};
+use crate::error::{CliError, CliResult};
+use crate::migrations::custom::max_builtin_version;
+use crate::migrations::runner::MigrationRunner;
+use crate::migrations::AppliedMigration;
+use crate::schema::MIGRATIONS_TABLE;
+
+pub async fn run(args: TrackingArgs) -> CliResult<()> {
+    match args.command {
+        TrackingCommand::Prune(args) => prune(args).await,
+    }
+}
+
+async fn prune(args: TrackingPruneArgs) -> CliResult<()> {
+    let db = Database::connect(&args.db_url, &ConnectOptions::from(&args.connect)).await?;
+
+    if !db.table_exists(MIGRATIONS_TABLE).await? {
+        println!("{} No {} table found", "✓".green(), MIGRATIONS_TABLE);
+        return Ok(());
+    }
+
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+    let applied = runner.get_applied_migrations().await?;
+    let orphaned: Vec<&AppliedMigration> = applied.iter().filter(|m| is_orphaned(m)).collect();
+
+    println!();
+    if orphaned.is_empty() {
+        println!("{} No orphaned rows in {}", "✓".green(), MIGRATIONS_TABLE);
+        return Ok(());
+    }
+
+    println!(
+        "Rows in {} for versions no built-in feature uses:",
+        MIGRATIONS_TABLE
+    );
+    for migration in &orphaned {
+        println!("  - {:03}_{}", migration.version, migration.name);
+    }
+    println!();
+    println!(
+        "{} Only the tracking rows are removed; any tables these migrations created are left alone.",
+        "Note:".yellow()
+    );
+    println!(
+        "      Versions above {} are assumed to be custom migrations and never pruned.",
+        max_builtin_version()
+    );
+    println!();
+
+    if args.dry_run {
+        println!("{}", "Dry run - no rows were removed".yellow());
+        return Ok(());
+    }
+
+    if !args.force {
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Remove {} orphaned row(s)?", orphaned.len()))
+            .default(false)
+            .interact()
+            .map_err(|_| CliError::Cancelled)?;
+
+        if !confirmed {
+            println!();
+            println!("Operation cancelled");
+            return Ok(());
+        }
+    }
+
+    for migration in &orphaned {
+        runner.remove_migration_record(migration.version).await?;
+    }
+
+    println!(
+        "{} Removed {} orphaned row(s) from {}",
+        "✓".green(),
+        orphaned.len(),
+        MIGRATIONS_TABLE
+    );
+
+    Ok(())
+}
+
+/// A row is orphaned when its version is in the built-in range but no feature uses it
+fn is_orphaned(migration: &AppliedMigration) -> bool {
+    migration.version <= max_builtin_version()
+        && !Feature::all()
+            .iter()
+            .any(|f| f.version() == migration.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(version: u32) -> AppliedMigration {
+        AppliedMigration {
+            version,
+            name: "test".to_string(),
+            applied_at: 0,
+            checksum: String::new(),
+            execution_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_is_orphaned() {
+        assert!(!is_orphaned(&applied(Feature::EmailPassword.version())));
+        assert!(is_orphaned(&applied(0)));
+        // Custom migrations live above the built-in range
+        assert!(!is_orphaned(&applied(max_builtin_version() + 1)));
+    }
+}
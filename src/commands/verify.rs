@@ -0,0 +1,86 @@
+use colored::Colorize;
+
+use crate::cli::{ReportFormat, VerifyArgs};
+use crate::config::AuthKitConfig;
+use crate::database::{ConnectOptions, Database};
+use crate::error::CliResult;
+use crate::junit::{self, TestCase};
+use crate::migrations::{get_migrations_for_database, runner::MigrationRunner};
+
+pub async fn run(args: VerifyArgs) -> CliResult<()> {
+    // Load configuration
+    let config = AuthKitConfig::load(&args.config)?;
+
+    let connect = ConnectOptions::from(&args.connect).with_config(&config);
+    let db = Database::connect(&args.db_url, &connect).await?;
+    let runner = MigrationRunner::new(&db.pool, db.db_type);
+
+    runner.ensure_migrations_table().await?;
+
+    let available = get_migrations_for_database(&config, db.db_type)?;
+    let applied = runner.get_applied_migrations().await?;
+    let mismatches = runner.get_checksum_mismatches(&available, &applied);
+
+    if args.format == ReportFormat::Junit {
+        let cases: Vec<TestCase> = applied
+            .iter()
+            .map(|migration| {
+                let label = format!("{:03}_{}", migration.version, migration.name);
+                if !available.iter().any(|m| m.version == migration.version) {
+                    TestCase::failed(label, "missing: applied in the database but not in config")
+                } else if let Some((_, expected, actual)) =
+                    mismatches.iter().find(|(v, _, _)| *v == migration.version)
+                {
+                    TestCase::failed(
+                        label,
+                        format!(
+                            "checksum mismatch: expected {}, actual {}",
+                            expected, actual
+                        ),
+                    )
+                } else {
+                    TestCase::passed(label)
+                }
+            })
+            .collect();
+        print!("{}", junit::render("authkit.verify", &cases));
+
+        // Keep the exit status meaningful for CI
+        return runner.verify_checksums(&config).await;
+    }
+
+    println!();
+    println!(
+        "Verifying applied migrations against {}",
+        args.config.cyan()
+    );
+    println!();
+
+    if applied.is_empty() {
+        println!("{} No migrations have been applied", "!".yellow());
+        return Ok(());
+    }
+
+    for migration in &applied {
+        let label = format!("{:03}_{}", migration.version, migration.name);
+
+        if !available.iter().any(|m| m.version == migration.version) {
+            println!("  {} {}: not found in config, skipped", "!".yellow(), label);
+        } else if let Some((_, expected, actual)) =
+            mismatches.iter().find(|(v, _, _)| *v == migration.version)
+        {
+            println!("  {} {}: checksum mismatch", "✗".red(), label);
+            println!("      expected: {}", expected);
+            println!("      actual:   {}", actual);
+        } else {
+            println!("  {} {}", "✓".green(), label);
+        }
+    }
+    println!();
+
+    runner.verify_checksums(&config).await?;
+
+    println!("{} All applied migrations match", "✓".green());
+
+    Ok(())
+}
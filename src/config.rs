@@ -1,12 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 
 use crate::cli::DatabaseType;
+use crate::database::{validate_schema_name, validate_table_prefix};
 use crate::error::{CliError, CliResult};
 
 /// AuthKit configuration file structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct AuthKitConfig {
     /// Database configuration
     pub database: DatabaseConfig,
@@ -14,16 +18,95 @@ pub struct AuthKitConfig {
     /// Enabled features
     #[serde(default)]
     pub features: FeaturesConfig,
+
+    /// Column types of the generated tables
+    #[serde(default, skip_serializing_if = "SchemaConfig::is_default")]
+    pub schema: SchemaConfig,
+
+    /// Application-defined migrations managed alongside the features
+    #[serde(default, skip_serializing_if = "MigrationsConfig::is_empty")]
+    pub migrations: MigrationsConfig,
+
+    /// SQL files run around `migrate`
+    #[serde(default, skip_serializing_if = "HooksConfig::is_empty")]
+    pub hooks: HooksConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaConfig {
+    /// Column type of the base tables' ids: "text" or "uuid" (PostgreSQL only)
+    #[serde(default)]
+    pub id_type: IdType,
+}
+
+impl SchemaConfig {
+    fn is_default(&self) -> bool {
+        self.id_type == IdType::Text
+    }
+}
+
+/// Column type of the users, accounts, sessions and verification ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdType {
+    /// Application-generated string ids
+    #[default]
+    Text,
+    /// Native PostgreSQL UUIDs defaulting to gen_random_uuid()
+    Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct MigrationsConfig {
+    /// Directory of `NNN_name.up.sql` / `NNN_name.down.sql` pairs, relative to the working directory
+    #[serde(default)]
+    pub dir: Option<String>,
+}
+
+impl MigrationsConfig {
+    fn is_empty(&self) -> bool {
+        self.dir.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// SQL files run before the pending migrations, relative to the working directory
+    #[serde(default)]
+    pub pre_migrate: Vec<String>,
+
+    /// SQL files run after the pending migrations were applied
+    #[serde(default)]
+    pub post_migrate: Vec<String>,
+}
+
+impl HooksConfig {
+    fn is_empty(&self) -> bool {
+        self.pre_migrate.is_empty() && self.post_migrate.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct DatabaseConfig {
     /// Database type: "sqlite" or "postgres"
     #[serde(rename = "type")]
     pub db_type: String,
+
+    /// PostgreSQL schema holding the AuthKit tables (default: public)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+
+    /// Prefix of every AuthKit table and index name, e.g. "auth_" for auth_users
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_prefix: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
 pub struct FeaturesConfig {
     /// Email/password authentication (always enabled, base feature)
     #[serde(default = "default_true")]
@@ -32,28 +115,243 @@ pub struct FeaturesConfig {
     /// Email verification feature (adds email_verified columns to users)
     #[serde(default)]
     pub email_verification: bool,
-    // Future features can be added here:
-    // pub oauth: bool,
-    // pub magic_link: bool,
-    // pub two_factor: bool,
+
+    /// OAuth provider feature (adds provider token columns to accounts)
+    #[serde(default)]
+    pub oauth: bool,
+
+    /// Two-factor authentication feature (adds TOTP secrets and backup codes)
+    #[serde(default)]
+    pub two_factor: bool,
+
+    /// Case-insensitive email feature (unique index on the normalized email)
+    #[serde(default)]
+    pub email_case_insensitive: bool,
+
+    /// Username feature (adds a unique username column to users)
+    #[serde(default)]
+    pub username: bool,
+
+    /// Audit log feature (adds an append-only auth_audit_log table)
+    #[serde(default)]
+    pub audit_log: bool,
+
+    /// Account lockout feature (adds failed login tracking to accounts)
+    #[serde(default)]
+    pub account_lockout: bool,
+
+    /// API keys feature (adds hashed machine-to-machine keys)
+    #[serde(default)]
+    pub api_keys: bool,
+
+    /// Rate limit feature (adds a rate_limits counter table)
+    #[serde(default)]
+    pub rate_limit: bool,
+
+    /// WebAuthn feature (adds passkey credential storage); also accepted as `passkeys`
+    #[serde(default, alias = "passkeys")]
+    pub webauthn: bool,
+
+    /// Organizations feature (adds multi-tenant organizations and memberships)
+    #[serde(default)]
+    pub organizations: bool,
+
+    /// Login tracking feature (adds last_login_at to users)
+    #[serde(default)]
+    pub login_tracking: bool,
+
+    /// Soft delete feature (adds deleted_at to users, accounts and sessions)
+    #[serde(default)]
+    pub soft_delete: bool,
+
+    /// Phone feature (adds phone numbers to users and SMS verification codes)
+    #[serde(default)]
+    pub phone: bool,
+
+    /// Refresh tokens feature (adds refresh token rotation with token families)
+    #[serde(default)]
+    pub refresh_tokens: bool,
+
+    /// Magic link feature (adds single-use email login links)
+    #[serde(default)]
+    pub magic_link: bool,
+
+    /// Memberships feature (adds organization slugs and one membership per user and organization)
+    #[serde(default)]
+    pub memberships: bool,
+}
+
+impl FeaturesConfig {
+    /// Turn a feature's flag on or off
+    pub fn set(&mut self, feature: Feature, enabled: bool) {
+        let flag = match feature {
+            Feature::EmailPassword => &mut self.email_password,
+            Feature::EmailVerification => &mut self.email_verification,
+            Feature::OAuth => &mut self.oauth,
+            Feature::TwoFactor => &mut self.two_factor,
+            Feature::EmailCaseInsensitive => &mut self.email_case_insensitive,
+            Feature::Username => &mut self.username,
+            Feature::AuditLog => &mut self.audit_log,
+            Feature::AccountLockout => &mut self.account_lockout,
+            Feature::ApiKeys => &mut self.api_keys,
+            Feature::RateLimit => &mut self.rate_limit,
+            Feature::WebAuthn => &mut self.webauthn,
+            Feature::Organizations => &mut self.organizations,
+            Feature::LoginTracking => &mut self.login_tracking,
+            Feature::SoftDelete => &mut self.soft_delete,
+            Feature::Phone => &mut self.phone,
+            Feature::RefreshTokens => &mut self.refresh_tokens,
+            Feature::MagicLink => &mut self.magic_link,
+            Feature::Memberships => &mut self.memberships,
+        };
+        *flag = enabled;
+    }
+
+    /// Whether a feature's flag is on
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::EmailPassword => self.email_password,
+            Feature::EmailVerification => self.email_verification,
+            Feature::OAuth => self.oauth,
+            Feature::TwoFactor => self.two_factor,
+            Feature::EmailCaseInsensitive => self.email_case_insensitive,
+            Feature::Username => self.username,
+            Feature::AuditLog => self.audit_log,
+            Feature::AccountLockout => self.account_lockout,
+            Feature::ApiKeys => self.api_keys,
+            Feature::RateLimit => self.rate_limit,
+            Feature::WebAuthn => self.webauthn,
+            Feature::Organizations => self.organizations,
+            Feature::LoginTracking => self.login_tracking,
+            Feature::SoftDelete => self.soft_delete,
+            Feature::Phone => self.phone,
+            Feature::RefreshTokens => self.refresh_tokens,
+            Feature::MagicLink => self.magic_link,
+            Feature::Memberships => self.memberships,
+        }
+    }
 }
 
 fn default_true() -> bool {
     true
 }
 
+/// Turn a TOML error into a config error, explaining rejected unknown keys
+fn parse_error(error: toml::de::Error) -> CliError {
+    let message = error.message();
+    if !message.starts_with("unknown field") {
+        return CliError::ConfigParse(error.to_string());
+    }
+
+    let key = message.split('`').nth(1).unwrap_or_default();
+    CliError::ConfigParse(format!(
+        "unknown key '{}' in authkit.toml\n{}\nFix or remove the key; `authkit --help-features` lists the valid feature keys.",
+        key, error
+    ))
+}
+
+/// Environment selected with the global `--env` flag
+static SELECTED_ENV: OnceLock<String> = OnceLock::new();
+
+/// Make [`AuthKitConfig::load`] apply the `[env.<name>]` profile
+pub fn select_env(name: String) {
+    let _ = SELECTED_ENV.set(name);
+}
+
+/// Merge the `[env.<name>]` profile over the base config table.
+///
+/// Only `[database]` and `[features]` keys can be overridden; each key in the
+/// profile replaces the base value, the rest of the section is kept.
+fn merge_profile(base: &mut toml::Table, envs: Option<toml::Value>, name: &str) -> CliResult<()> {
+    let mut envs = match envs {
+        Some(toml::Value::Table(envs)) => envs,
+        Some(_) => {
+            return Err(CliError::ConfigParse(
+                "env must be a table of [env.<name>] sections".to_string(),
+            ))
+        }
+        None => toml::Table::new(),
+    };
+
+    let Some(profile) = envs.remove(name) else {
+        let defined: Vec<&str> = envs.keys().map(String::as_str).collect();
+        return Err(CliError::ConfigParse(format!(
+            "environment '{}' is not defined in authkit.toml (defined: {})",
+            name,
+            if defined.is_empty() {
+                "none".to_string()
+            } else {
+                defined.join(", ")
+            }
+        )));
+    };
+    let toml::Value::Table(profile) = profile else {
+        return Err(CliError::ConfigParse(format!(
+            "env.{} must be a table",
+            name
+        )));
+    };
+
+    for (section, overrides) in profile {
+        let toml::Value::Table(overrides) = overrides else {
+            return Err(CliError::ConfigParse(format!(
+                "env.{}.{} must be a table",
+                name, section
+            )));
+        };
+        if section != "database" && section != "features" {
+            return Err(CliError::ConfigParse(format!(
+                "env.{}.{} can't be overridden; profiles may only set [database] and [features] keys",
+                name, section
+            )));
+        }
+
+        let target = base
+            .entry(section)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if let toml::Value::Table(target) = target {
+            target.extend(overrides);
+        }
+    }
+
+    Ok(())
+}
+
 impl AuthKitConfig {
-    /// Load configuration from a TOML file
+    /// Load configuration from a TOML file, applying the environment chosen with `--env`
     pub fn load<P: AsRef<Path>>(path: P) -> CliResult<Self> {
+        Self::load_profile(path, SELECTED_ENV.get().map(String::as_str))
+    }
+
+    /// Load configuration from a TOML file, merging the `[env.<name>]` profile
+    /// over the base sections when `env` is given
+    pub fn load_profile<P: AsRef<Path>>(path: P, env: Option<&str>) -> CliResult<Self> {
         let path = path.as_ref();
 
         if !path.exists() {
             return Err(CliError::ConfigNotFound(path.display().to_string()));
         }
 
+        let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        tracing::debug!("Loading config from {}", resolved.display());
+
         let content = fs::read_to_string(path)?;
-        let config: AuthKitConfig =
-            toml::from_str(&content).map_err(|e| CliError::ConfigParse(e.to_string()))?;
+        Self::parse_profile(&content, env)
+    }
+
+    /// Parse and validate the contents of an authkit.toml file with an optional profile
+    pub fn parse_profile(content: &str, env: Option<&str>) -> CliResult<Self> {
+        let mut table: toml::Table = toml::from_str(content).map_err(parse_error)?;
+        let config: AuthKitConfig = match (table.remove("env"), env) {
+            // Deserialize the text directly so errors keep their line numbers
+            (None, None) => toml::from_str(content).map_err(parse_error)?,
+            (envs, env) => {
+                if let Some(name) = env {
+                    merge_profile(&mut table, envs, name)?;
+                }
+                toml::Value::Table(table).try_into().map_err(parse_error)?
+            }
+        };
 
         // Validate config
         config.validate()?;
@@ -70,19 +368,104 @@ impl AuthKitConfig {
         Ok(())
     }
 
+    /// Prefix of the AuthKit table names, empty when none is configured
+    pub fn table_prefix(&self) -> &str {
+        self.database.table_prefix.as_deref().unwrap_or("")
+    }
+
     /// Create a default configuration
     pub fn default_config(db_type: DatabaseType) -> Self {
         Self {
             database: DatabaseConfig {
                 db_type: db_type.to_string(),
+                schema: None,
+                table_prefix: None,
             },
             features: FeaturesConfig {
                 email_password: true,
                 email_verification: false,
+                oauth: false,
+                two_factor: false,
+                email_case_insensitive: false,
+                username: false,
+                audit_log: false,
+                account_lockout: false,
+                api_keys: false,
+                rate_limit: false,
+                webauthn: false,
+                organizations: false,
+                login_tracking: false,
+                soft_delete: false,
+                phone: false,
+                refresh_tokens: false,
+                magic_link: false,
+                memberships: false,
             },
+            schema: SchemaConfig::default(),
+            migrations: MigrationsConfig::default(),
+            hooks: HooksConfig::default(),
         }
     }
 
+    /// Render a commented example authkit.toml documenting every feature flag
+    /// with its default value, built from the [`Feature`] metadata
+    pub fn example_toml(db_type: DatabaseType) -> String {
+        let defaults = Self::default_config(db_type).enabled_features();
+
+        let mut out = String::new();
+        out.push_str("# AuthKit configuration\n");
+        out.push_str("# Generated by `authkit init --print-example`\n\n");
+        out.push_str("[database]\n");
+        out.push_str("# Database type: \"sqlite\" or \"postgres\"\n");
+        out.push_str(&format!("type = \"{}\"\n", db_type));
+        out.push_str("# PostgreSQL schema holding the AuthKit tables (default: public)\n");
+        out.push_str("# schema = \"public\"\n");
+        out.push_str("# Prefix of every AuthKit table and index name (default: none)\n");
+        out.push_str("# table_prefix = \"auth_\"\n\n");
+
+        out.push_str("[features]\n");
+        for feature in Feature::all() {
+            out.push_str(&format!(
+                "# {}: {}\n",
+                feature.display_name(),
+                feature.description()
+            ));
+            let requires: Vec<&str> = feature
+                .dependencies()
+                .iter()
+                .map(Feature::config_key)
+                .collect();
+            if !requires.is_empty() {
+                out.push_str(&format!("# Requires: {}\n", requires.join(", ")));
+            }
+            out.push_str(&format!(
+                "{} = {}\n\n",
+                feature.config_key(),
+                defaults.contains(feature)
+            ));
+        }
+
+        out.push_str("[schema]\n");
+        out.push_str(
+            "# Column type of the users, accounts, sessions and verification ids: \"text\" or \"uuid\" (PostgreSQL only)\n",
+        );
+        out.push_str("# id_type = \"text\"\n\n");
+
+        out.push_str("[migrations]\n");
+        out.push_str(
+            "# Directory of NNN_name.up.sql / NNN_name.down.sql pairs applied after the features\n",
+        );
+        out.push_str("# dir = \"./custom_migrations\"\n\n");
+
+        out.push_str("[hooks]\n");
+        out.push_str(
+            "# SQL files run before and after the pending migrations, not tracked as migrations\n",
+        );
+        out.push_str("# pre_migrate = [\"./hooks/extensions.sql\"]\n");
+        out.push_str("# post_migrate = [\"./hooks/grants.sql\"]\n");
+        out
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> CliResult<()> {
         // Validate database type
@@ -96,6 +479,37 @@ impl AuthKitConfig {
             }
         }
 
+        if let Some(schema) = &self.database.schema {
+            if self.database.db_type != "postgres" {
+                return Err(CliError::ConfigParse(
+                    "database.schema is only supported for postgres".to_string(),
+                ));
+            }
+            validate_schema_name(schema)?;
+        }
+
+        if let Some(prefix) = &self.database.table_prefix {
+            validate_table_prefix(prefix)?;
+        }
+
+        if self.schema.id_type == IdType::Uuid && self.database.db_type != "postgres" {
+            return Err(CliError::ConfigParse(
+                "schema.id_type = \"uuid\" is only supported for postgres".to_string(),
+            ));
+        }
+
+        for (key, paths) in [
+            ("pre_migrate", &self.hooks.pre_migrate),
+            ("post_migrate", &self.hooks.post_migrate),
+        ] {
+            if let Some(path) = paths.iter().find(|p| !Path::new(p).is_file()) {
+                return Err(CliError::ConfigParse(format!(
+                    "hooks.{}: file '{}' not found",
+                    key, path
+                )));
+            }
+        }
+
         // email_password must always be enabled (it's the base)
         if !self.features.email_password {
             return Err(CliError::ConfigParse(
@@ -103,6 +517,20 @@ impl AuthKitConfig {
             ));
         }
 
+        // Every enabled feature must have its prerequisites enabled too
+        let enabled = self.enabled_features();
+        for feature in &enabled {
+            for dependency in feature.dependencies() {
+                if !enabled.contains(dependency) {
+                    return Err(CliError::ConfigParse(format!(
+                        "feature '{}' requires '{}'",
+                        feature.migration_name(),
+                        dependency.migration_name()
+                    )));
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -118,19 +546,23 @@ impl AuthKitConfig {
         }
     }
 
-    /// Get a list of enabled features in order
+    /// Get the enabled features ordered by migration version
     pub fn enabled_features(&self) -> Vec<Feature> {
-        let mut features = Vec::new();
+        let mut features: Vec<Feature> = Feature::all()
+            .iter()
+            .copied()
+            .filter(|feature| self.features.is_enabled(*feature))
+            .collect();
 
-        // Base feature is always first
-        if self.features.email_password {
-            features.push(Feature::EmailPassword);
-        }
-
-        // Add-on features in order
-        if self.features.email_verification {
-            features.push(Feature::EmailVerification);
-        }
+        // Migrations ALTER shared tables, so they must apply in version order
+        // regardless of how the flags are listed
+        features.sort_by_key(Feature::version);
+        debug_assert!(
+            features
+                .windows(2)
+                .all(|w| w[0].version() != w[1].version()),
+            "two features share a migration version"
+        );
 
         features
     }
@@ -143,14 +575,133 @@ pub enum Feature {
     EmailPassword,
     /// Email verification add-on
     EmailVerification,
+    /// OAuth provider add-on
+    OAuth,
+    /// Two-factor authentication add-on
+    TwoFactor,
+    /// Case-insensitive email uniqueness add-on
+    EmailCaseInsensitive,
+    /// Username sign-in add-on
+    Username,
+    /// Authentication audit log add-on
+    AuditLog,
+    /// Brute-force account lockout add-on
+    AccountLockout,
+    /// Machine-to-machine API keys add-on
+    ApiKeys,
+    /// Rate limit counter storage add-on
+    RateLimit,
+    /// WebAuthn/passkey add-on
+    WebAuthn,
+    /// Organizations (multi-tenant) add-on
+    Organizations,
+    /// Last-login tracking add-on
+    LoginTracking,
+    /// Soft delete add-on
+    SoftDelete,
+    /// Phone / SMS sign-in add-on
+    Phone,
+    /// Refresh token rotation add-on
+    RefreshTokens,
+    /// Magic link sign-in add-on
+    MagicLink,
+    /// Organization memberships add-on
+    Memberships,
 }
 
 impl Feature {
+    /// All built-in features in version order
+    pub fn all() -> &'static [Feature] {
+        &[
+            Feature::EmailPassword,
+            Feature::EmailVerification,
+            Feature::OAuth,
+            Feature::TwoFactor,
+            Feature::EmailCaseInsensitive,
+            Feature::Username,
+            Feature::AuditLog,
+            Feature::AccountLockout,
+            Feature::ApiKeys,
+            Feature::RateLimit,
+            Feature::WebAuthn,
+            Feature::Organizations,
+            Feature::LoginTracking,
+            Feature::SoftDelete,
+            Feature::Phone,
+            Feature::RefreshTokens,
+            Feature::MagicLink,
+            Feature::Memberships,
+        ]
+    }
+
+    /// Look up a feature by its `[features]` key (or an alias of it) or migration name
+    pub fn from_key(key: &str) -> Option<Feature> {
+        if key == "passkeys" {
+            return Some(Feature::WebAuthn);
+        }
+        Feature::all()
+            .iter()
+            .copied()
+            .find(|f| f.config_key() == key || f.migration_name() == key)
+    }
+
+    /// Like [`Feature::from_key`], but with an error listing the valid keys
+    pub fn parse(key: &str) -> CliResult<Feature> {
+        Feature::from_key(key).ok_or_else(|| {
+            let keys: Vec<&str> = Feature::all().iter().map(|f| f.config_key()).collect();
+            CliError::Other(format!(
+                "unknown feature '{}' (expected one of: {})",
+                key,
+                keys.join(", ")
+            ))
+        })
+    }
+
     /// Get the feature name for migration naming
     pub fn migration_name(&self) -> &'static str {
         match self {
             Feature::EmailPassword => "base",
             Feature::EmailVerification => "email_verification",
+            Feature::OAuth => "oauth",
+            Feature::TwoFactor => "two_factor",
+            Feature::EmailCaseInsensitive => "email_case_insensitive",
+            Feature::Username => "username",
+            Feature::AuditLog => "audit_log",
+            Feature::AccountLockout => "account_lockout",
+            Feature::ApiKeys => "api_keys",
+            Feature::RateLimit => "rate_limit",
+            Feature::WebAuthn => "webauthn",
+            Feature::Organizations => "organizations",
+            Feature::LoginTracking => "login_tracking",
+            Feature::SoftDelete => "soft_delete",
+            Feature::Phone => "phone",
+            Feature::RefreshTokens => "refresh_tokens",
+            Feature::MagicLink => "magic_link",
+            Feature::Memberships => "memberships",
+        }
+    }
+
+    /// Get the `[features]` key that enables this feature in authkit.toml
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            Feature::EmailPassword => "email_password",
+            Feature::EmailVerification => "email_verification",
+            Feature::OAuth => "oauth",
+            Feature::TwoFactor => "two_factor",
+            Feature::EmailCaseInsensitive => "email_case_insensitive",
+            Feature::Username => "username",
+            Feature::AuditLog => "audit_log",
+            Feature::AccountLockout => "account_lockout",
+            Feature::ApiKeys => "api_keys",
+            Feature::RateLimit => "rate_limit",
+            Feature::WebAuthn => "webauthn",
+            Feature::Organizations => "organizations",
+            Feature::LoginTracking => "login_tracking",
+            Feature::SoftDelete => "soft_delete",
+            Feature::Phone => "phone",
+            Feature::RefreshTokens => "refresh_tokens",
+            Feature::MagicLink => "magic_link",
+            Feature::Memberships => "memberships",
         }
     }
 
@@ -159,6 +710,48 @@ impl Feature {
         match self {
             Feature::EmailPassword => "Email/Password Authentication",
             Feature::EmailVerification => "Email Verification",
+            Feature::OAuth => "OAuth Providers",
+            Feature::TwoFactor => "Two-Factor Authentication",
+            Feature::EmailCaseInsensitive => "Case-Insensitive Emails",
+            Feature::Username => "Username Sign-In",
+            Feature::AuditLog => "Audit Log",
+            Feature::AccountLockout => "Account Lockout",
+            Feature::ApiKeys => "API Keys",
+            Feature::RateLimit => "Rate Limiting",
+            Feature::WebAuthn => "Passkeys / WebAuthn",
+            Feature::Organizations => "Organizations",
+            Feature::LoginTracking => "Login Tracking",
+            Feature::SoftDelete => "Soft Delete",
+            Feature::Phone => "Phone / SMS Sign-In",
+            Feature::RefreshTokens => "Refresh Token Rotation",
+            Feature::MagicLink => "Magic Link Sign-In",
+            Feature::Memberships => "Organization Memberships",
+        }
+    }
+
+    /// One-line summary of what the feature adds, used in generated config examples
+    pub fn description(&self) -> &'static str {
+        match self {
+            Feature::EmailPassword => {
+                "users, accounts, sessions and verification tables (always enabled)"
+            }
+            Feature::EmailVerification => "adds email_verified columns to users",
+            Feature::OAuth => "adds provider token columns to accounts",
+            Feature::TwoFactor => "adds TOTP secrets and backup codes",
+            Feature::EmailCaseInsensitive => "unique index on the normalized email",
+            Feature::Username => "adds a unique username column to users",
+            Feature::AuditLog => "adds an append-only auth_audit_log table",
+            Feature::AccountLockout => "adds failed login tracking to accounts",
+            Feature::ApiKeys => "adds hashed machine-to-machine keys",
+            Feature::RateLimit => "adds a rate_limits counter table",
+            Feature::WebAuthn => "adds passkey credential storage",
+            Feature::Organizations => "adds multi-tenant organizations and memberships",
+            Feature::LoginTracking => "adds last_login_at to users",
+            Feature::SoftDelete => "adds deleted_at to users, accounts and sessions",
+            Feature::Phone => "adds phone numbers to users and SMS verification codes",
+            Feature::RefreshTokens => "adds refresh tokens grouped into rotation families",
+            Feature::MagicLink => "adds single-use email login links",
+            Feature::Memberships => "adds organization slugs and per-organization member roles",
         }
     }
 
@@ -167,20 +760,154 @@ impl Feature {
         match self {
             Feature::EmailPassword => 1,
             Feature::EmailVerification => 2,
+            Feature::OAuth => 3,
+            Feature::TwoFactor => 4,
+            Feature::EmailCaseInsensitive => 5,
+            Feature::Username => 6,
+            Feature::AuditLog => 7,
+            Feature::AccountLockout => 8,
+            Feature::ApiKeys => 9,
+            Feature::RateLimit => 10,
+            Feature::WebAuthn => 11,
+            Feature::Organizations => 12,
+            Feature::LoginTracking => 13,
+            Feature::SoftDelete => 14,
+            Feature::Phone => 15,
+            Feature::RefreshTokens => 16,
+            Feature::MagicLink => 17,
+            Feature::Memberships => 18,
+        }
+    }
+
+    /// Get the features that must also be enabled for this feature
+    pub fn dependencies(&self) -> &'static [Feature] {
+        match self {
+            Feature::EmailPassword => &[],
+            Feature::EmailVerification => &[Feature::EmailPassword],
+            Feature::OAuth => &[Feature::EmailPassword],
+            Feature::TwoFactor => &[Feature::EmailPassword, Feature::EmailVerification],
+            Feature::EmailCaseInsensitive => &[Feature::EmailPassword],
+            Feature::Username => &[Feature::EmailPassword],
+            Feature::AuditLog => &[Feature::EmailPassword],
+            Feature::AccountLockout => &[Feature::EmailPassword],
+            Feature::ApiKeys => &[Feature::EmailPassword],
+            Feature::RateLimit => &[Feature::EmailPassword],
+            Feature::WebAuthn => &[Feature::EmailPassword],
+            Feature::Organizations => &[Feature::EmailPassword],
+            Feature::LoginTracking => &[Feature::EmailPassword],
+            Feature::SoftDelete => &[Feature::EmailPassword],
+            Feature::Phone => &[Feature::EmailPassword],
+            Feature::RefreshTokens => &[Feature::EmailPassword],
+            Feature::MagicLink => &[Feature::EmailPassword],
+            Feature::Memberships => &[Feature::EmailPassword, Feature::Organizations],
         }
     }
+
+    /// This feature preceded by everything it transitively depends on
+    pub fn with_dependencies(&self) -> Vec<Feature> {
+        let mut features = Vec::new();
+        for dependency in self.dependencies() {
+            for feature in dependency.with_dependencies() {
+                if !features.contains(&feature) {
+                    features.push(feature);
+                }
+            }
+        }
+        features.push(*self);
+        features
+    }
+
+    /// Ensure every built-in feature has its own migration version
+    pub fn check_versions() -> CliResult<()> {
+        check_unique_versions(
+            Feature::all()
+                .iter()
+                .map(|f| (f.version(), f.migration_name())),
+        )
+    }
+}
+
+/// Ensure no two migrations share a version.
+///
+/// `_authkit_migrations` is keyed on the version, so a collision would record
+/// one migration as applied and silently skip the other.
+pub fn check_unique_versions<'a>(
+    migrations: impl IntoIterator<Item = (u32, &'a str)>,
+) -> CliResult<()> {
+    let mut seen: HashMap<u32, &str> = HashMap::new();
+    for (version, name) in migrations {
+        if let Some(first) = seen.insert(version, name) {
+            return Err(CliError::DuplicateVersion {
+                version,
+                first: first.to_string(),
+                second: name.to_string(),
+            });
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_features_set_enables_every_feature() {
+        for feature in Feature::all() {
+            let mut config = AuthKitConfig::default_config(DatabaseType::Sqlite);
+            config.features.set(*feature, true);
+            assert!(config.enabled_features().contains(feature));
+        }
+    }
+
+    #[test]
+    fn test_example_toml_parses_and_documents_every_feature() {
+        for db_type in [DatabaseType::Postgres, DatabaseType::Sqlite] {
+            let example = AuthKitConfig::example_toml(db_type);
+            let config = AuthKitConfig::parse_profile(&example, None).unwrap();
+            assert_eq!(config.database.db_type, db_type.to_string());
+            assert_eq!(config.enabled_features(), vec![Feature::EmailPassword]);
+
+            for feature in Feature::all() {
+                assert!(example.contains(&format!("# {}: ", feature.display_name())));
+                assert!(example.contains(&format!("\n{} = ", feature.config_key())));
+            }
+        }
+    }
+
     #[test]
     fn test_default_config() {
         let config = AuthKitConfig::default_config(DatabaseType::Postgres);
         assert_eq!(config.database.db_type, "postgres");
         assert!(config.features.email_password);
         assert!(!config.features.email_verification);
+        assert!(!config.features.oauth);
+        assert!(!config.features.two_factor);
+        assert!(!config.features.email_case_insensitive);
+        assert!(!config.features.username);
+        assert!(!config.features.audit_log);
+        assert!(!config.features.account_lockout);
+        assert!(!config.features.api_keys);
+        assert!(!config.features.rate_limit);
+        assert!(!config.features.webauthn);
+        assert!(!config.features.organizations);
+        assert!(!config.features.login_tracking);
+        assert!(!config.features.soft_delete);
+        assert!(!config.features.phone);
+        assert!(!config.features.refresh_tokens);
+        assert!(!config.features.magic_link);
+        assert!(!config.features.memberships);
+    }
+
+    #[test]
+    fn test_feature_from_key() {
+        assert_eq!(
+            Feature::from_key("email_password"),
+            Some(Feature::EmailPassword)
+        );
+        assert_eq!(Feature::from_key("base"), Some(Feature::EmailPassword));
+        assert_eq!(Feature::from_key("two_factor"), Some(Feature::TwoFactor));
+        assert_eq!(Feature::from_key("users"), None);
     }
 
     #[test]
@@ -193,4 +920,202 @@ mod tests {
         assert_eq!(features[0], Feature::EmailPassword);
         assert_eq!(features[1], Feature::EmailVerification);
     }
+
+    #[test]
+    fn test_feature_versions_are_unique_and_contiguous() {
+        let versions: Vec<u32> = Feature::all().iter().map(Feature::version).collect();
+        let expected: Vec<u32> = (1..=Feature::all().len() as u32).collect();
+        assert_eq!(versions, expected);
+    }
+
+    #[test]
+    fn test_check_versions_accepts_builtin_features() {
+        Feature::check_versions().unwrap();
+    }
+
+    #[test]
+    fn test_check_unique_versions_reports_collision() {
+        let features = [(1, "base"), (2, "email_verification"), (2, "sso")];
+        match check_unique_versions(features) {
+            Err(CliError::DuplicateVersion {
+                version,
+                first,
+                second,
+            }) => {
+                assert_eq!(version, 2);
+                assert_eq!(first, "email_verification");
+                assert_eq!(second, "sso");
+            }
+            other => panic!("expected DuplicateVersion, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_enabled_features_sorted_by_version_regardless_of_config_order() {
+        let config = AuthKitConfig::parse_profile(
+            r#"
+[database]
+type = "sqlite"
+
+[features]
+soft_delete = true
+organizations = true
+two_factor = true
+email_verification = true
+email_password = true
+"#,
+            None,
+        )
+        .unwrap();
+
+        let enabled = config.enabled_features();
+        assert_eq!(
+            enabled,
+            vec![
+                Feature::EmailPassword,
+                Feature::EmailVerification,
+                Feature::TwoFactor,
+                Feature::Organizations,
+                Feature::SoftDelete,
+            ]
+        );
+
+        // Enabling the flags one by one in reverse produces the same order
+        let mut reversed = AuthKitConfig::default_config(DatabaseType::Sqlite);
+        for feature in enabled.iter().rev() {
+            reversed.features.set(*feature, true);
+        }
+        assert_eq!(reversed.enabled_features(), enabled);
+    }
+
+    #[test]
+    fn test_oauth_ordered_after_email_verification() {
+        let mut config = AuthKitConfig::default_config(DatabaseType::Sqlite);
+        config.features.oauth = true;
+        config.features.email_verification = true;
+
+        let features = config.enabled_features();
+        assert_eq!(
+            features,
+            vec![
+                Feature::EmailPassword,
+                Feature::EmailVerification,
+                Feature::OAuth
+            ]
+        );
+        assert_eq!(Feature::OAuth.migration_name(), "oauth");
+        assert_eq!(Feature::OAuth.version(), 3);
+    }
+
+    #[test]
+    fn test_validate_dependency_chain() {
+        let mut config = AuthKitConfig::default_config(DatabaseType::Postgres);
+        config.features.email_verification = true;
+        config.features.two_factor = true;
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_passkeys_alias_enables_webauthn() {
+        let content = "[database]\ntype = \"sqlite\"\n\n[features]\npasskeys = true\n";
+        let config = AuthKitConfig::parse_profile(content, None).unwrap();
+        assert!(config.enabled_features().contains(&Feature::WebAuthn));
+        assert_eq!(Feature::from_key("passkeys"), Some(Feature::WebAuthn));
+        assert_eq!(Feature::WebAuthn.display_name(), "Passkeys / WebAuthn");
+    }
+
+    const PROFILES: &str = "[database]\ntype = \"sqlite\"\n\n[features]\nemail_password = true\n\n[env.prod]\ndatabase.type = \"postgres\"\n\n[env.prod.features]\noauth = true\n";
+
+    #[test]
+    fn test_profile_overrides_base() {
+        let config = AuthKitConfig::parse_profile(PROFILES, Some("prod")).unwrap();
+        assert_eq!(config.database.db_type, "postgres");
+        assert!(config.features.oauth);
+        assert!(config.features.email_password);
+    }
+
+    #[test]
+    fn test_no_env_uses_base_config() {
+        let config = AuthKitConfig::parse_profile(PROFILES, None).unwrap();
+        assert_eq!(config.database.db_type, "sqlite");
+        assert!(!config.features.oauth);
+    }
+
+    #[test]
+    fn test_unknown_env_is_rejected() {
+        match AuthKitConfig::parse_profile(PROFILES, Some("staging")) {
+            Err(CliError::ConfigParse(msg)) => {
+                assert!(msg.contains("environment 'staging' is not defined"));
+                assert!(msg.contains("defined: prod"));
+            }
+            other => panic!("expected ConfigParse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_misspelled_feature_key_is_rejected() {
+        let content = "[database]\ntype = \"sqlite\"\n\n[features]\nemail_verficiation = true\n";
+
+        match AuthKitConfig::parse_profile(content, None) {
+            Err(CliError::ConfigParse(msg)) => {
+                assert!(msg.starts_with("unknown key 'email_verficiation' in authkit.toml"));
+                assert!(msg.contains("email_verification"));
+            }
+            other => panic!("expected ConfigParse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_missing_dependency() {
+        let mut config = AuthKitConfig::default_config(DatabaseType::Postgres);
+        config.features.two_factor = true;
+
+        match config.validate() {
+            Err(CliError::ConfigParse(msg)) => {
+                assert_eq!(msg, "feature 'two_factor' requires 'email_verification'")
+            }
+            other => panic!("expected ConfigParse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hook_files_must_exist() {
+        let temp = tempfile::tempdir().unwrap();
+        let hook = temp.path().join("grants.sql");
+        std::fs::write(&hook, "SELECT 1;").unwrap();
+
+        let content = format!(
+            "[database]\ntype = \"sqlite\"\n\n[features]\nemail_password = true\n\n[hooks]\npre_migrate = [{:?}]\n",
+            hook.display().to_string()
+        );
+        let config = AuthKitConfig::parse_profile(&content, None).unwrap();
+        assert_eq!(config.hooks.pre_migrate.len(), 1);
+
+        std::fs::remove_file(&hook).unwrap();
+        match AuthKitConfig::parse_profile(&content, None) {
+            Err(CliError::ConfigParse(msg)) => {
+                assert!(msg.starts_with("hooks.pre_migrate: file"), "{}", msg)
+            }
+            other => panic!("expected ConfigParse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_uuid_id_type_requires_postgres() {
+        let content = "[database]\ntype = \"postgres\"\n\n[features]\nemail_password = true\n\n[schema]\nid_type = \"uuid\"\n";
+        let config = AuthKitConfig::parse_profile(content, None).unwrap();
+        assert_eq!(config.schema.id_type, IdType::Uuid);
+
+        let content = content.replace("postgres", "sqlite");
+        match AuthKitConfig::parse_profile(&content, None) {
+            Err(CliError::ConfigParse(msg)) => {
+                assert_eq!(
+                    msg,
+                    "schema.id_type = \"uuid\" is only supported for postgres"
+                )
+            }
+            other => panic!("expected ConfigParse error, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,46 @@
+//! CSV output for spreadsheet imports
+
+/// Columns of the migration CSV written by `status` and `history`
+pub const MIGRATION_HEADER: [&str; 4] = ["version", "name", "status", "applied_at"];
+
+/// Render a header row and data rows as RFC 4180 CSV with CRLF line endings
+pub fn render(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut csv = String::new();
+    push_row(&mut csv, header.iter().copied());
+    for row in rows {
+        push_row(&mut csv, row.iter().map(String::as_str));
+    }
+    csv
+}
+
+fn push_row<'a>(csv: &mut String, fields: impl Iterator<Item = &'a str>) {
+    let fields: Vec<String> = fields.map(quote).collect();
+    csv.push_str(&fields.join(","));
+    csv.push_str("\r\n");
+}
+
+/// Quote a field containing a comma, quote or line break, doubling inner quotes
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_quotes_special_fields() {
+        let rows = vec![
+            vec!["1".to_string(), "base".to_string()],
+            vec!["100".to_string(), "seed, \"demo\"".to_string()],
+        ];
+        assert_eq!(
+            render(&["version", "name"], &rows),
+            "version,name\r\n1,base\r\n100,\"seed, \"\"demo\"\"\"\r\n"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! `.env` file loading
+//!
+//! Variables already set in the environment are never overridden, and
+//! explicit command-line arguments still take precedence over both.
+
+use std::path::Path;
+
+use crate::error::{CliError, CliResult};
+
+/// Load `--env-file <PATH>` if given, otherwise `./.env` when it exists
+pub fn load(args: impl IntoIterator<Item = String>) -> CliResult<()> {
+    match env_file_arg(args) {
+        Some(path) => {
+            dotenvy::from_path(&path)
+                .map_err(|e| CliError::Other(format!("Failed to load env file {}: {}", path, e)))?;
+        }
+        None => {
+            if Path::new(".env").exists() {
+                dotenvy::from_path(".env")
+                    .map_err(|e| CliError::Other(format!("Failed to load .env: {}", e)))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the `--env-file` value ahead of clap, which needs the environment loaded first
+fn env_file_arg(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--env-file" {
+            return args.next();
+        }
+        if let Some(path) = arg.strip_prefix("--env-file=") {
+            return Some(path.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_env_file_arg() {
+        assert_eq!(
+            env_file_arg(args(&["authkit", "status", "--env-file", "prod.env"])),
+            Some("prod.env".to_string())
+        );
+        assert_eq!(
+            env_file_arg(args(&["authkit", "--env-file=ci.env", "migrate"])),
+            Some("ci.env".to_string())
+        );
+        assert_eq!(env_file_arg(args(&["authkit", "migrate"])), None);
+    }
+}
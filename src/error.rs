@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::cli::DatabaseType;
+
 pub type CliResult<T> = Result<T, CliError>;
 
 #[derive(Error, Debug)]
@@ -14,8 +16,22 @@ pub enum CliError {
     #[error("Unknown database type in URL: {0}")]
     UnknownDatabase(String),
 
-    #[error("Migration error: {0}")]
-    Migration(String),
+    #[error("Database URL is {actual} but config specifies {config}. Use --allow-type-mismatch to migrate anyway.")]
+    DatabaseTypeMismatch {
+        config: DatabaseType,
+        actual: DatabaseType,
+    },
+
+    #[error("Migration error: {message}")]
+    Migration {
+        /// Version of the failing migration, when one is involved
+        version: Option<u32>,
+        /// Name of the failing migration (empty for run-level failures)
+        name: String,
+        /// Zero-based index of the failing statement within the migration
+        statement_index: Option<usize>,
+        message: String,
+    },
 
     #[error("Checksum mismatch for migration {version}: expected {expected}, got {actual}")]
     ChecksumMismatch {
@@ -24,9 +40,47 @@ pub enum CliError {
         actual: String,
     },
 
+    #[error("Duplicate index name '{name}' in features '{first}' and '{second}'")]
+    DuplicateIndex {
+        name: String,
+        first: String,
+        second: String,
+    },
+
+    #[error("Duplicate migration version {version:03} in features '{first}' and '{second}'")]
+    DuplicateVersion {
+        version: u32,
+        first: String,
+        second: String,
+    },
+
+    #[error("Database contains non-AuthKit tables: {0}")]
+    DatabaseNotEmpty(String),
+
+    #[error("Database has base tables but no migration records: {0}")]
+    UntrackedTables(String),
+
+    #[error("Found {0} user(s) whose emails differ only by case or whitespace")]
+    DuplicateEmails(usize),
+
+    #[error("Another migration is in progress: {0}")]
+    MigrationInProgress(String),
+
+    #[error("Database drifted since the plan was made: {0}")]
+    PlanMismatch(String),
+
+    #[error("Database is not up to date: {pending} pending and {missing} missing migration(s)")]
+    OutOfDate { pending: usize, missing: usize },
+
+    #[error("Database is not clean: {0} problem(s) found")]
+    NotClean(usize),
+
     #[error("File already exists: {0}. Use --force to overwrite.")]
     FileExists(String),
 
+    #[error("Output directory has SQL files this run doesn't produce: {0}. Use --clean to remove stale AuthKit files or --force to write anyway.")]
+    OutputNotEmpty(String),
+
     #[error("Operation cancelled by user")]
     Cancelled,
 
@@ -39,6 +93,129 @@ pub enum CliError {
     #[error("Feature not enabled: {0}")]
     FeatureNotEnabled(String),
 
+    #[error("Invalid SQL identifier '{0}': use letters, digits and underscores, not starting with a digit")]
+    InvalidIdentifier(String),
+
     #[error("{0}")]
     Other(String),
 }
+
+impl CliError {
+    /// A migration failure that is not tied to a specific migration
+    pub fn migration(message: impl Into<String>) -> Self {
+        CliError::Migration {
+            version: None,
+            name: String::new(),
+            statement_index: None,
+            message: message.into(),
+        }
+    }
+
+    /// Stable name of the error variant, for machine-readable output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CliError::Database(_) => "Database",
+            CliError::Io(_) => "Io",
+            CliError::UnknownDatabase(_) => "UnknownDatabase",
+            CliError::DatabaseTypeMismatch { .. } => "DatabaseTypeMismatch",
+            CliError::Migration { .. } => "Migration",
+            CliError::ChecksumMismatch { .. } => "ChecksumMismatch",
+            CliError::DuplicateIndex { .. } => "DuplicateIndex",
+            CliError::DuplicateVersion { .. } => "DuplicateVersion",
+            CliError::DatabaseNotEmpty(_) => "DatabaseNotEmpty",
+            CliError::UntrackedTables(_) => "UntrackedTables",
+            CliError::DuplicateEmails(_) => "DuplicateEmails",
+            CliError::MigrationInProgress(_) => "MigrationInProgress",
+            CliError::PlanMismatch(_) => "PlanMismatch",
+            CliError::OutOfDate { .. } => "OutOfDate",
+            CliError::NotClean(_) => "NotClean",
+            CliError::FileExists(_) => "FileExists",
+            CliError::OutputNotEmpty(_) => "OutputNotEmpty",
+            CliError::Cancelled => "Cancelled",
+            CliError::ConfigNotFound(_) => "ConfigNotFound",
+            CliError::ConfigParse(_) => "ConfigParse",
+            CliError::FeatureNotEnabled(_) => "FeatureNotEnabled",
+            CliError::InvalidIdentifier(_) => "InvalidIdentifier",
+            CliError::Other(_) => "Other",
+        }
+    }
+
+    /// Migration version the error refers to, if any
+    pub fn version(&self) -> Option<u32> {
+        match self {
+            CliError::ChecksumMismatch { version, .. } => Some(*version),
+            CliError::DuplicateVersion { version, .. } => Some(*version),
+            CliError::Migration { version, .. } => *version,
+            _ => None,
+        }
+    }
+
+    /// Index of the failing statement within a migration, if known
+    pub fn statement_index(&self) -> Option<usize> {
+        match self {
+            CliError::Migration {
+                statement_index, ..
+            } => *statement_index,
+            _ => None,
+        }
+    }
+
+    /// Render the error as a JSON object
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "error": {
+                "kind": self.kind(),
+                "message": self.to_string(),
+                "version": self.version(),
+                "statement_index": self.statement_index(),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_error_includes_kind_and_version() {
+        let err = CliError::ChecksumMismatch {
+            version: 2,
+            expected: "a".to_string(),
+            actual: "b".to_string(),
+        };
+        let json = err.to_json();
+        assert_eq!(json["error"]["kind"], "ChecksumMismatch");
+        assert_eq!(json["error"]["version"], 2);
+        assert_eq!(
+            json["error"]["message"],
+            "Checksum mismatch for migration 2: expected a, got b"
+        );
+    }
+
+    #[test]
+    fn test_json_error_for_failed_statement() {
+        let err = CliError::Migration {
+            version: Some(3),
+            name: "oauth".to_string(),
+            statement_index: Some(1),
+            message: "Failed to execute migration oauth: duplicate column".to_string(),
+        };
+        let json = err.to_json();
+        assert_eq!(json["error"]["kind"], "Migration");
+        assert_eq!(json["error"]["version"], 3);
+        assert_eq!(json["error"]["statement_index"], 1);
+        assert_eq!(
+            json["error"]["message"],
+            "Migration error: Failed to execute migration oauth: duplicate column"
+        );
+    }
+
+    #[test]
+    fn test_json_error_without_version() {
+        let json = CliError::UnknownDatabase("mysql://x".to_string()).to_json();
+        assert_eq!(json["error"]["kind"], "UnknownDatabase");
+        assert!(json["error"]["version"].is_null());
+        assert!(json["error"]["statement_index"].is_null());
+    }
+}
@@ -0,0 +1,83 @@
+//! JUnit XML reports for CI test dashboards
+
+/// A single test case; `failure` holds the reason when it failed
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub failure: Option<String>,
+}
+
+impl TestCase {
+    pub fn passed(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            failure: None,
+        }
+    }
+
+    pub fn failed(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            failure: Some(reason.into()),
+        }
+    }
+}
+
+/// Render a single `<testsuite>` containing the given test cases
+pub fn render(suite: &str, cases: &[TestCase]) -> String {
+    let failures = cases.iter().filter(|c| c.failure.is_some()).count();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites>\n  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        escape(suite),
+        cases.len(),
+        failures
+    ));
+    for case in cases {
+        let name = escape(&case.name);
+        match &case.failure {
+            None => xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\"/>\n",
+                name,
+                escape(suite)
+            )),
+            Some(reason) => xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                name,
+                escape(suite),
+                escape(reason),
+                escape(reason)
+            )),
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_failures_and_escapes() {
+        let xml = render(
+            "authkit.status",
+            &[
+                TestCase::passed("001_base"),
+                TestCase::failed("002_email_verification", "pending: <not applied>"),
+            ],
+        );
+        assert!(xml.contains("<testsuite name=\"authkit.status\" tests=\"2\" failures=\"1\">"));
+        assert!(xml.contains("<testcase name=\"001_base\" classname=\"authkit.status\"/>"));
+        assert!(xml.contains("<failure message=\"pending: &lt;not applied&gt;\">"));
+    }
+}
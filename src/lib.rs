@@ -0,0 +1,43 @@
+//! AuthKit schema management as a library
+//!
+//! The `authkit` binary is a thin wrapper over this crate. Servers that want to
+//! bring their schema up to date at startup can call [`migrate`] on their own
+//! pool instead of shelling out:
+//!
+//! ```no_run
+//! # async fn run() -> authkit_cli::CliResult<()> {
+//! use authkit_cli::{AuthKitConfig, Feature};
+//!
+//! sqlx::any::install_default_drivers();
+//! let pool = sqlx::AnyPool::connect("postgres://localhost/app").await?;
+//!
+//! let mut config = AuthKitConfig::load("authkit.toml")?;
+//! config.features.set(Feature::EmailVerification, true);
+//! for name in authkit_cli::migrate(&pool, &config).await? {
+//!     println!("applied {}", name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod cli;
+pub mod commands;
+pub mod config;
+pub mod csv;
+pub mod database;
+pub mod env_file;
+pub mod error;
+pub mod junit;
+pub mod logging;
+pub mod migrations;
+pub mod output;
+pub mod schema;
+pub mod tokens;
+
+pub use cli::DatabaseType;
+pub use config::{AuthKitConfig, Feature};
+pub use database::Database;
+pub use error::{CliError, CliResult};
+pub use migrations::migrate;
+pub use migrations::runner::MigrationRunner;
+pub use tokens::default_token_ttl_seconds;
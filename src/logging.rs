@@ -0,0 +1,28 @@
+//! Diagnostic logging for `--verbose`
+//!
+//! Logs go to stderr so they never mix with command output on stdout.
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Install the global subscriber: DEBUG (including sqlx query logs) when
+/// verbose, WARN otherwise
+pub fn init(verbose: bool) {
+    let level = if verbose {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::WARN
+    };
+
+    let format = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_ansi(colored::control::SHOULD_COLORIZE.should_colorize())
+        .without_time();
+
+    let _ = tracing_subscriber::registry()
+        .with(format)
+        .with(Targets::new().with_default(level))
+        .try_init();
+}
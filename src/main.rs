@@ -1,26 +1,73 @@
-use clap::Parser;
-
-mod cli;
-mod commands;
-mod config;
-mod database;
-mod error;
-mod migrations;
-mod schema;
-
-use cli::{Cli, Commands};
-use error::CliResult;
+use authkit_cli::cli::{Cli, Commands, MessageFormat};
+use authkit_cli::config::Feature;
+use authkit_cli::error::CliResult;
+use authkit_cli::{commands, config, env_file, logging, output};
+use clap::{CommandFactory, Parser};
 
 #[tokio::main]
 async fn main() -> CliResult<()> {
+    // Must run before parsing so env-backed args (AUTHKIT_DATABASE_URL, ...) see the file
+    env_file::load(std::env::args())?;
+
     let cli = Cli::parse();
 
-    match cli.command {
+    // A version shared by two features would collide in _authkit_migrations
+    Feature::check_versions()?;
+
+    if cli.no_color || std::env::var_os("NO_COLOR").is_some() {
+        colored::control::set_override(false);
+    }
+
+    logging::init(cli.verbose);
+    output::set_quiet(cli.quiet);
+
+    let error_format = cli.error_format;
+
+    if let Some(env) = cli.env {
+        config::select_env(env);
+    }
+
+    if cli.help_features {
+        return commands::help_features::run();
+    }
+
+    let Some(command) = cli.command else {
+        Cli::command().print_help()?;
+        return Ok(());
+    };
+
+    let result = match command {
         Commands::Init(args) => commands::init::run(args).await,
         Commands::Generate(args) => commands::generate::run(args).await,
+        Commands::Export(args) => commands::export::run(args).await,
         Commands::Migrate(args) => commands::migrate::run(args).await,
         Commands::Status(args) => commands::status::run(args).await,
+        Commands::History(args) => commands::history::run(args).await,
+        Commands::Verify(args) => commands::verify::run(args).await,
+        Commands::Baseline(args) => commands::baseline::run(args).await,
+        Commands::Redo(args) => commands::redo::run(args).await,
+        Commands::Sync(args) => commands::sync::run(args).await,
+        Commands::Rollback(args) => commands::rollback::run(args).await,
+        Commands::Repair(args) => commands::repair::run(args).await,
+        Commands::DumpChecksums(args) => commands::dump_checksums::run(args).await,
+        Commands::FeatureSetHash(args) => commands::feature_set_hash::run(args).await,
+        Commands::ListFeatures(args) => commands::list_features::run(args).await,
+        Commands::Dump(args) => commands::dump::run(args).await,
+        Commands::CleanExpired(args) => commands::clean_expired::run(args).await,
+        Commands::Doctor(args) => commands::doctor::run(args).await,
         Commands::Destroy(args) => commands::destroy::run(args).await,
         Commands::Schema(args) => commands::schema::run(args).await,
+        Commands::Diff(args) => commands::diff::run(args).await,
+        Commands::Tracking(args) => commands::tracking::run(args).await,
+    };
+
+    // JSON errors are printed here; human mode keeps the default error output
+    if let Err(e) = &result {
+        if error_format == MessageFormat::Json {
+            eprintln!("{}", e.to_json());
+            std::process::exit(1);
+        }
     }
+
+    result
 }
@@ -0,0 +1,36 @@
+//! Pre-migration data checks
+//!
+//! Some features add constraints that existing rows may violate. These checks find
+//! the offending rows before any SQL runs, so the failure can be explained clearly.
+
+use sqlx::AnyPool;
+
+use crate::cli::DatabaseType;
+use crate::database::quote_ident;
+use crate::error::CliResult;
+
+/// Find users whose emails collide once lower-cased and trimmed.
+///
+/// `users_table` is the (possibly prefixed) name of the users table.
+/// Returns `(id, email)` pairs grouped by normalized email.
+pub async fn find_duplicate_emails(
+    pool: &AnyPool,
+    db_type: DatabaseType,
+    users_table: &str,
+) -> CliResult<Vec<(String, String)>> {
+    let users = quote_ident(db_type, users_table)?;
+    let sql = format!(
+        r#"
+        SELECT id, email FROM {users}
+        WHERE LOWER(TRIM(email)) IN (
+            SELECT LOWER(TRIM(email)) FROM {users}
+            GROUP BY LOWER(TRIM(email))
+            HAVING COUNT(*) > 1
+        )
+        ORDER BY LOWER(TRIM(email)), email
+        "#
+    );
+    let rows = sqlx::query_as(&sql).fetch_all(pool).await?;
+
+    Ok(rows)
+}
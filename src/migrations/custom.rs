@@ -0,0 +1,146 @@
+//! Application-defined migrations from the `[migrations] dir` directory
+//!
+//! Files are named like generated ones (`100_add_widgets.up.sql` plus
+//! `100_add_widgets.down.sql`) and are recorded in `_authkit_migrations`
+//! after the built-in features, so their versions must be higher.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Feature;
+use crate::error::{CliError, CliResult};
+use crate::migrations::{compute_checksum, Migration};
+
+/// Highest version used by a built-in feature
+pub fn max_builtin_version() -> u32 {
+    Feature::all()
+        .iter()
+        .map(|f| f.version())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Read every migration pair in `dir`, ordered by version
+pub fn load(dir: &str) -> CliResult<Vec<Migration>> {
+    let path = Path::new(dir);
+    if !path.is_dir() {
+        return Err(CliError::ConfigParse(format!(
+            "migrations directory not found: {}",
+            dir
+        )));
+    }
+
+    // (version, name) -> (up, down)
+    let mut files: BTreeMap<(u32, String), (Option<String>, Option<String>)> = BTreeMap::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some((version, name, is_up)) = parse_file_name(&file_name) else {
+            continue;
+        };
+
+        let sql = fs::read_to_string(entry.path())?;
+        let slot = files.entry((version, name)).or_default();
+        if is_up {
+            slot.0 = Some(sql);
+        } else {
+            slot.1 = Some(sql);
+        }
+    }
+
+    let max_builtin = max_builtin_version();
+    let mut migrations: Vec<Migration> = Vec::new();
+    for ((version, name), (up, down)) in files {
+        let label = format!("{:03}_{}", version, name);
+        let error = |message: String| CliError::Migration {
+            version: Some(version),
+            name: name.clone(),
+            statement_index: None,
+            message,
+        };
+
+        if version <= max_builtin {
+            return Err(error(format!(
+                "Custom migration {} must use a version above {} (the highest built-in feature version)",
+                label, max_builtin
+            )));
+        }
+        if let Some(other) = migrations.iter().find(|m| m.version == version) {
+            return Err(error(format!(
+                "Custom migrations {:03}_{} and {} share version {}",
+                other.version, other.name, label, version
+            )));
+        }
+        let (Some(up_sql), Some(down_sql)) = (up, down) else {
+            return Err(error(format!(
+                "Custom migration {} needs both {}.up.sql and {}.down.sql in {}",
+                label, label, label, dir
+            )));
+        };
+
+        migrations.push(Migration {
+            version,
+            checksum: compute_checksum(&up_sql),
+            name,
+            up_sql,
+            down_sql,
+        });
+    }
+
+    Ok(migrations)
+}
+
+/// Split `NNN_name.up.sql` / `NNN_name.down.sql` into version, name and direction
+fn parse_file_name(file_name: &str) -> Option<(u32, String, bool)> {
+    let (stem, is_up) = if let Some(stem) = file_name.strip_suffix(".up.sql") {
+        (stem, true)
+    } else {
+        (file_name.strip_suffix(".down.sql")?, false)
+    };
+
+    let (version, name) = stem.split_once('_')?;
+    let version = version.parse().ok()?;
+    (!name.is_empty()).then(|| (version, name.to_string(), is_up))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_name() {
+        assert_eq!(
+            parse_file_name("100_add_widgets.up.sql"),
+            Some((100, "add_widgets".to_string(), true))
+        );
+        assert_eq!(
+            parse_file_name("100_add_widgets.down.sql"),
+            Some((100, "add_widgets".to_string(), false))
+        );
+        assert_eq!(parse_file_name("README.md"), None);
+        assert_eq!(parse_file_name("widgets.up.sql"), None);
+    }
+
+    #[test]
+    fn test_load_rejects_builtin_versions_and_missing_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        fs::write(dir.path().join("005_widgets.up.sql"), "SELECT 1;").unwrap();
+        fs::write(dir.path().join("005_widgets.down.sql"), "SELECT 1;").unwrap();
+        let err = load(path).err().unwrap();
+        assert!(err.to_string().contains("must use a version above"));
+
+        fs::remove_file(dir.path().join("005_widgets.up.sql")).unwrap();
+        fs::remove_file(dir.path().join("005_widgets.down.sql")).unwrap();
+        fs::write(dir.path().join("100_widgets.up.sql"), "SELECT 1;").unwrap();
+        let err = load(path).err().unwrap();
+        assert!(err.to_string().contains("needs both"));
+
+        fs::write(dir.path().join("100_widgets.down.sql"), "SELECT 2;").unwrap();
+        let migrations = load(path).unwrap();
+        assert_eq!(migrations.len(), 1);
+        assert_eq!(migrations[0].checksum, compute_checksum("SELECT 1;"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Cross-process lock that keeps concurrent `migrate` runs from colliding
+//!
+//! PostgreSQL uses a session-level advisory lock held on a dedicated
+//! connection, detached from the pool so it doesn't use up
+//! `--max-connections`. SQLite has no advisory locks, so a single-row table
+//! is used instead; a row older than an hour is left over from a crashed run
+//! and is taken over.
+
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use sqlx::{AnyConnection, AnyPool, Row};
+
+use crate::cli::DatabaseType;
+use crate::error::{CliError, CliResult};
+use crate::schema::MIGRATIONS_LOCK_TABLE;
+
+/// Advisory lock key shared by every AuthKit CLI process ("authkit" in ASCII)
+const ADVISORY_LOCK_KEY: i64 = 0x61_75_74_68_6b_69_74;
+
+/// How often to retry while another run holds the lock
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Age after which a SQLite lock row is treated as left behind by a crashed run
+const STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// A held migration lock; call [`MigrationLock::release`] when done
+pub struct MigrationLock {
+    conn: AnyConnection,
+    db_type: DatabaseType,
+}
+
+impl MigrationLock {
+    /// Acquire the lock, waiting up to `timeout` for another run to finish
+    pub async fn acquire(
+        pool: &AnyPool,
+        db_type: DatabaseType,
+        timeout: Duration,
+    ) -> CliResult<Self> {
+        let mut conn = pool.acquire().await?.detach();
+
+        if db_type == DatabaseType::Sqlite {
+            sqlx::query(&format!(
+                "CREATE TABLE IF NOT EXISTS {} (id INTEGER PRIMARY KEY, holder TEXT NOT NULL, locked_at BIGINT NOT NULL)",
+                MIGRATIONS_LOCK_TABLE
+            ))
+            .execute(&mut conn)
+            .await?;
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut waiting = false;
+        loop {
+            if Self::try_lock(&mut conn, db_type).await? {
+                return Ok(Self { conn, db_type });
+            }
+            if Instant::now() >= deadline {
+                return Err(CliError::MigrationInProgress(
+                    Self::describe_holder(&mut conn, db_type).await?,
+                ));
+            }
+            if !waiting {
+                eprintln!("Waiting for another migration run to finish...");
+                waiting = true;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    async fn try_lock(conn: &mut AnyConnection, db_type: DatabaseType) -> CliResult<bool> {
+        match db_type {
+            DatabaseType::Postgres => {
+                let row = sqlx::query("SELECT pg_try_advisory_lock($1) AS locked")
+                    .bind(ADVISORY_LOCK_KEY)
+                    .fetch_one(&mut *conn)
+                    .await?;
+                Ok(row.get::<bool, _>("locked"))
+            }
+            DatabaseType::Sqlite => {
+                Self::remove_stale(conn).await?;
+
+                let result = sqlx::query(&format!(
+                    "INSERT OR IGNORE INTO {} (id, holder, locked_at) VALUES (1, $1, $2)",
+                    MIGRATIONS_LOCK_TABLE
+                ))
+                .bind(format!("pid {}", std::process::id()))
+                .bind(chrono::Utc::now().timestamp())
+                .execute(&mut *conn)
+                .await?;
+                Ok(result.rows_affected() == 1)
+            }
+        }
+    }
+
+    /// Delete the SQLite lock row if it is older than [`STALE_AFTER`]
+    async fn remove_stale(conn: &mut AnyConnection) -> CliResult<()> {
+        let cutoff = chrono::Utc::now().timestamp() - STALE_AFTER.as_secs() as i64;
+        let row = sqlx::query(&format!(
+            "SELECT holder, locked_at FROM {} WHERE id = 1 AND locked_at < $1",
+            MIGRATIONS_LOCK_TABLE
+        ))
+        .bind(cutoff)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(());
+        };
+        let locked_at: i64 = row.get("locked_at");
+
+        // Match on locked_at so a run that took over the lock meanwhile keeps it
+        let result = sqlx::query(&format!(
+            "DELETE FROM {} WHERE id = 1 AND locked_at = $1",
+            MIGRATIONS_LOCK_TABLE
+        ))
+        .bind(locked_at)
+        .execute(&mut *conn)
+        .await?;
+
+        if result.rows_affected() == 1 {
+            eprintln!(
+                "{} Taking over a stale migration lock held by {} since {}",
+                "Warning:".yellow(),
+                row.get::<String, _>("holder"),
+                format_timestamp(locked_at)
+            );
+        }
+        Ok(())
+    }
+
+    async fn describe_holder(conn: &mut AnyConnection, db_type: DatabaseType) -> CliResult<String> {
+        match db_type {
+            DatabaseType::Postgres => Ok(format!(
+                "advisory lock {} is held by another session",
+                ADVISORY_LOCK_KEY
+            )),
+            DatabaseType::Sqlite => {
+                let row = sqlx::query(&format!(
+                    "SELECT holder, locked_at FROM {} WHERE id = 1",
+                    MIGRATIONS_LOCK_TABLE
+                ))
+                .fetch_optional(&mut *conn)
+                .await?;
+
+                Ok(match row {
+                    Some(row) => format!(
+                        "locked by {} since {}. If no migration is running, delete the row from {} (it is taken over once it is an hour old)",
+                        row.get::<String, _>("holder"),
+                        format_timestamp(row.get::<i64, _>("locked_at")),
+                        MIGRATIONS_LOCK_TABLE
+                    ),
+                    None => "lock was released while waiting; try again".to_string(),
+                })
+            }
+        }
+    }
+
+    /// Release the lock, only warning if that fails, so the caller goes on to
+    /// report the outcome of the work done under it
+    pub async fn release_or_warn(self) {
+        if let Err(e) = self.release().await {
+            eprintln!(
+                "{} Failed to release the migration lock: {}",
+                "Warning:".yellow(),
+                e
+            );
+        }
+    }
+
+    /// Release the lock
+    pub async fn release(mut self) -> CliResult<()> {
+        match self.db_type {
+            DatabaseType::Postgres => {
+                sqlx::query("SELECT pg_advisory_unlock($1)")
+                    .bind(ADVISORY_LOCK_KEY)
+                    .execute(&mut self.conn)
+                    .await?;
+            }
+            DatabaseType::Sqlite => {
+                // `destroy` drops the lock table while holding the lock, which releases it
+                let exists =
+                    sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = $1")
+                        .bind(MIGRATIONS_LOCK_TABLE)
+                        .fetch_optional(&mut self.conn)
+                        .await?
+                        .is_some();
+                if !exists {
+                    return Ok(());
+                }
+
+                sqlx::query(&format!(
+                    "DELETE FROM {} WHERE id = 1",
+                    MIGRATIONS_LOCK_TABLE
+                ))
+                .execute(&mut self.conn)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_timestamp(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_default()
+}
@@ -1,6 +1,17 @@
+pub mod checks;
+pub mod custom;
+pub mod lock;
+pub mod plan;
 pub mod runner;
 
-use crate::config::AuthKitConfig;
+use std::time::Duration;
+
+use sqlx::AnyPool;
+
+use crate::cli::DatabaseType;
+use crate::config::{check_unique_versions, AuthKitConfig};
+use crate::database::Database;
+use crate::error::CliResult;
 use crate::schema;
 
 /// A single migration
@@ -8,11 +19,43 @@ use crate::schema;
 pub struct Migration {
     pub version: u32,
     pub name: String,
-    pub up_sql: &'static str,
-    pub down_sql: &'static str,
+    pub up_sql: String,
+    pub down_sql: String,
     pub checksum: String,
 }
 
+/// Checksums the first release recorded for built-in migrations whose SQL has
+/// since gained comments, as `(version, name, checksum)`. That release hashed
+/// the raw SQL, comments included, so the raw hash of today's SQL differs.
+const RELEASED_CHECKSUMS: &[(u32, &str, &str)] = &[
+    // 001_base before the token lifetime comments: PostgreSQL, then SQLite
+    (
+        1,
+        "base",
+        "63766b5281c4e66606bdf06010108d083c63db8dc601fb0c94c82c084afe21c3",
+    ),
+    (
+        1,
+        "base",
+        "45cfeedf2905166bd97e27123300f8189bc70dd8bd59e2a7c93552d8c1aa4d16",
+    ),
+];
+
+impl Migration {
+    /// Whether a stored checksum belongs to this migration's SQL.
+    ///
+    /// Checksums recorded before whitespace normalization hashed the raw SQL,
+    /// so those are accepted too, as are the [`RELEASED_CHECKSUMS`] of SQL
+    /// that has only gained comments since.
+    pub fn matches_checksum(&self, checksum: &str) -> bool {
+        self.checksum == checksum
+            || sha256_hex(&self.up_sql) == checksum
+            || RELEASED_CHECKSUMS.iter().any(|&(version, name, released)| {
+                version == self.version && name == self.name && released == checksum
+            })
+    }
+}
+
 /// A migration that has been applied to the database
 #[derive(Clone)]
 #[allow(dead_code)]
@@ -21,6 +64,8 @@ pub struct AppliedMigration {
     pub name: String,
     pub applied_at: i64,
     pub checksum: String,
+    /// How long the migration's SQL took to run (unset for baselined migrations)
+    pub execution_ms: Option<i64>,
 }
 
 /// Migration state
@@ -44,24 +89,137 @@ impl MigrationState {
     }
 }
 
-/// Get migrations for enabled features from config
-pub fn get_migrations_from_config(config: &AuthKitConfig) -> Vec<Migration> {
-    let db_type = config.database_type().expect("Invalid database type");
-    let features = config.enabled_features();
-    schema::get_migrations_for_features(&features, db_type)
+/// Get migrations for enabled features from config, followed by any custom migrations
+pub fn get_migrations_from_config(config: &AuthKitConfig) -> CliResult<Vec<Migration>> {
+    get_migrations_for_database(config, config.database_type()?)
+}
+
+/// Get migrations for enabled features in the dialect of a specific database,
+/// followed by any custom migrations from `[migrations] dir`.
+///
+/// Checksums are computed from dialect-specific SQL, so anything comparing against
+/// `_authkit_migrations` must use the type of the connected database, not the config's.
+pub fn get_migrations_for_database(
+    config: &AuthKitConfig,
+    db_type: DatabaseType,
+) -> CliResult<Vec<Migration>> {
+    let mut migrations: Vec<Migration> = config
+        .enabled_features()
+        .into_iter()
+        .map(|feature| schema::get_feature_migration_for(config, feature, db_type))
+        .collect();
+    check_unique_versions(migrations.iter().map(|m| (m.version, m.name.as_str())))?;
+    if let Some(dir) = &config.migrations.dir {
+        migrations.extend(custom::load(dir)?);
+    }
+    Ok(migrations)
+}
+
+/// Apply every pending migration of the config's enabled features (and custom
+/// migrations) to an existing pool, returning the applied ones as `NNN_name`.
+/// The `[hooks]` files run around them when anything is pending.
+///
+/// The database type is detected from the pool's URL. `[database] schema` is put on
+/// the search_path as `authkit migrate` does (see [`Database::from_pool`]). On
+/// PostgreSQL the run holds the same advisory lock as `authkit migrate`, so replicas
+/// starting together take turns. SQLite runs without the lock: its lock table needs a
+/// second connection, which an in-memory database doesn't share.
+pub async fn migrate(pool: &AnyPool, config: &AuthKitConfig) -> CliResult<Vec<String>> {
+    let db = Database::from_pool(pool, config).await?;
+    let db_type = db.db_type;
+    let runner = runner::MigrationRunner::new(&db.pool, db_type);
+    runner.ensure_migrations_table().await?;
+
+    let lock = match db_type {
+        DatabaseType::Postgres => {
+            Some(lock::MigrationLock::acquire(&db.pool, db_type, Duration::from_secs(60)).await?)
+        }
+        DatabaseType::Sqlite => None,
+    };
+    let result = async {
+        let available = get_migrations_for_database(config, db_type)?;
+        let applied = runner.get_applied_migrations().await?;
+
+        let pending = runner.get_pending_migrations(&available, &applied);
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        for hook in &config.hooks.pre_migrate {
+            runner.run_hook(hook).await?;
+        }
+        let mut names = Vec::new();
+        for migration in pending {
+            runner.apply_migration(migration).await?;
+            names.push(format!("{:03}_{}", migration.version, migration.name));
+        }
+        for hook in &config.hooks.post_migrate {
+            runner.run_hook(hook).await?;
+        }
+        Ok(names)
+    }
+    .await;
+    // A failed release must not hide the migration's own result; the advisory
+    // lock also ends with the session
+    if let Some(lock) = lock {
+        if let Err(e) = lock.release().await {
+            tracing::warn!("Failed to release the migration lock: {}", e);
+        }
+    }
+    result
 }
 
-/// Compute SHA-256 checksum for migration content
+/// Compute SHA-256 checksum for migration content, ignoring formatting (see [`normalize_sql`])
 pub fn compute_checksum(content: &str) -> String {
+    sha256_hex(&normalize_sql(content))
+}
+
+/// Canonical form of a SQL script for hashing: comment lines are dropped, and each
+/// statement is trimmed with its whitespace runs collapsed to single spaces.
+/// Only the checksum uses this; migrations still run the original SQL.
+pub fn normalize_sql(sql: &str) -> String {
+    let code = sql
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    code.split(';')
+        .map(|statement| statement.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|statement| !statement.is_empty())
+        .collect::<Vec<_>>()
+        .join(";\n")
+}
+
+fn sha256_hex(content: &str) -> String {
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
     hasher.update(content.as_bytes());
     hex::encode(hasher.finalize())
 }
 
+/// Hash identifying a set of migrations: one line per migration
+/// (`version name checksum`, sorted by version), hashed with SHA-256
+pub fn feature_set_hash(migrations: &[Migration]) -> String {
+    let mut lines: Vec<(u32, String)> = migrations
+        .iter()
+        .map(|m| {
+            (
+                m.version,
+                format!("{} {} {}\n", m.version, m.name, m.checksum),
+            )
+        })
+        .collect();
+    lines.sort();
+
+    let content: String = lines.into_iter().map(|(_, line)| line).collect();
+    sha256_hex(&content)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::IdType;
 
     #[test]
     fn test_compute_checksum() {
@@ -74,6 +232,83 @@ mod tests {
         assert_eq!(checksum1.len(), 64); // SHA-256 produces 64 hex chars
     }
 
+    #[test]
+    fn test_checksum_ignores_formatting() {
+        let compact =
+            "CREATE TABLE users (id TEXT, email TEXT);\nCREATE INDEX idx_users_id ON users(id);";
+        let formatted = "\n-- Users table\nCREATE TABLE users (id TEXT,\n    email TEXT);\n\n  CREATE INDEX idx_users_id\n      ON users(id);  \n\n";
+        assert_eq!(compute_checksum(compact), compute_checksum(formatted));
+        assert_ne!(
+            compute_checksum(compact),
+            compute_checksum("CREATE TABLE users (id INTEGER);")
+        );
+    }
+
+    #[test]
+    fn test_normalize_sql() {
+        assert_eq!(
+            normalize_sql("-- comment\nSELECT  1 ;\n\n  SELECT\n\t2;\n"),
+            "SELECT 1;\nSELECT 2"
+        );
+    }
+
+    #[test]
+    fn test_matches_legacy_raw_checksum() {
+        let migration = schema::get_feature_migration(
+            crate::config::Feature::EmailPassword,
+            DatabaseType::Sqlite,
+        );
+        assert!(migration.matches_checksum(&migration.checksum));
+        assert!(migration.matches_checksum(&sha256_hex(&migration.up_sql)));
+        assert!(!migration.matches_checksum(&sha256_hex("SELECT 1;")));
+    }
+
+    #[test]
+    fn test_matches_released_base_checksums() {
+        let postgres = schema::get_feature_migration(
+            crate::config::Feature::EmailPassword,
+            DatabaseType::Postgres,
+        );
+        let sqlite = schema::get_feature_migration(
+            crate::config::Feature::EmailPassword,
+            DatabaseType::Sqlite,
+        );
+        assert!(postgres
+            .matches_checksum("63766b5281c4e66606bdf06010108d083c63db8dc601fb0c94c82c084afe21c3"));
+        assert!(sqlite
+            .matches_checksum("45cfeedf2905166bd97e27123300f8189bc70dd8bd59e2a7c93552d8c1aa4d16"));
+
+        // The released checksums belong to 001_base only
+        let verification = schema::get_feature_migration(
+            crate::config::Feature::EmailVerification,
+            DatabaseType::Sqlite,
+        );
+        assert!(!verification
+            .matches_checksum("45cfeedf2905166bd97e27123300f8189bc70dd8bd59e2a7c93552d8c1aa4d16"));
+    }
+
+    #[test]
+    fn test_feature_set_hash_ignores_order() {
+        use crate::config::Feature;
+
+        let features = [Feature::EmailPassword, Feature::OAuth];
+        let forward =
+            schema::get_migrations_for_features(&features, DatabaseType::Postgres, IdType::Text)
+                .unwrap();
+        let mut reversed = forward.clone();
+        reversed.reverse();
+
+        assert_eq!(feature_set_hash(&forward), feature_set_hash(&reversed));
+        assert_ne!(feature_set_hash(&forward), feature_set_hash(&forward[..1]));
+        assert_ne!(
+            feature_set_hash(&forward),
+            feature_set_hash(
+                &schema::get_migrations_for_features(&features, DatabaseType::Sqlite, IdType::Text)
+                    .unwrap()
+            )
+        );
+    }
+
     #[test]
     fn test_migration_state_str() {
         assert_eq!(MigrationState::Applied.as_str(), "Applied");
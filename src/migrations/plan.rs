@@ -0,0 +1,158 @@
+//! Reviewed migration plans
+//!
+//! `migrate --print-plan --plan-out plan.json` records the pending migrations;
+//! `migrate --plan-file plan.json` later applies them only if the pending set
+//! is still exactly the same.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::cli::DatabaseType;
+use crate::error::{CliError, CliResult};
+use crate::migrations::Migration;
+
+/// Pending migrations at the time the plan was made
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub database_type: String,
+    pub migrations: Vec<PlannedMigration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedMigration {
+    pub version: u32,
+    pub name: String,
+    pub checksum: String,
+}
+
+impl Plan {
+    pub fn new(db_type: DatabaseType, pending: &[&Migration]) -> Self {
+        Self {
+            database_type: db_type.to_string(),
+            migrations: pending
+                .iter()
+                .map(|m| PlannedMigration {
+                    version: m.version,
+                    name: m.name.clone(),
+                    checksum: m.checksum.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &str) -> CliResult<Self> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| CliError::Other(format!("Invalid plan file {}: {}", path, e)))
+    }
+
+    pub fn write(&self, path: &str) -> CliResult<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| CliError::Other(format!("Failed to serialize plan: {}", e)))?;
+        fs::write(path, format!("{}\n", json))?;
+        Ok(())
+    }
+
+    /// Check that the current pending set is exactly what the plan recorded
+    pub fn check(&self, db_type: DatabaseType, pending: &[&Migration]) -> CliResult<()> {
+        if self.database_type != db_type.to_string() {
+            return Err(CliError::PlanMismatch(format!(
+                "plan was made for {} but the database is {}",
+                self.database_type, db_type
+            )));
+        }
+
+        let current = Plan::new(db_type, pending);
+        for planned in &self.migrations {
+            match current
+                .migrations
+                .iter()
+                .find(|m| m.version == planned.version)
+            {
+                None => {
+                    return Err(CliError::PlanMismatch(format!(
+                        "{:03}_{} is no longer pending",
+                        planned.version, planned.name
+                    )))
+                }
+                Some(m) if m.checksum != planned.checksum => {
+                    return Err(CliError::PlanMismatch(format!(
+                        "checksum of {:03}_{} changed (planned {}, now {})",
+                        planned.version, planned.name, planned.checksum, m.checksum
+                    )))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(extra) = current
+            .migrations
+            .iter()
+            .find(|m| !self.migrations.iter().any(|p| p.version == m.version))
+        {
+            return Err(CliError::PlanMismatch(format!(
+                "{:03}_{} is pending but not in the plan",
+                extra.version, extra.name
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Feature;
+    use crate::schema::get_feature_migration;
+
+    fn pending(features: &[Feature]) -> Vec<Migration> {
+        features
+            .iter()
+            .map(|f| get_feature_migration(*f, DatabaseType::Sqlite))
+            .collect()
+    }
+
+    fn refs(migrations: &[Migration]) -> Vec<&Migration> {
+        migrations.iter().collect()
+    }
+
+    #[test]
+    fn test_plan_matches_same_pending_set() {
+        let migrations = pending(&[Feature::EmailPassword, Feature::OAuth]);
+        let pending = refs(&migrations);
+        let plan = Plan::new(DatabaseType::Sqlite, &pending);
+        assert!(plan.check(DatabaseType::Sqlite, &pending).is_ok());
+        assert!(matches!(
+            plan.check(DatabaseType::Postgres, &pending),
+            Err(CliError::PlanMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_plan_detects_drift() {
+        let planned = pending(&[Feature::EmailPassword, Feature::OAuth]);
+        let plan = Plan::new(DatabaseType::Sqlite, &refs(&planned));
+
+        // Base was applied by someone else in the meantime
+        let err = plan
+            .check(DatabaseType::Sqlite, &refs(&pending(&[Feature::OAuth])))
+            .unwrap_err();
+        assert!(err.to_string().contains("001_base is no longer pending"));
+
+        // A feature was enabled after the plan was made
+        let err = plan
+            .check(
+                DatabaseType::Sqlite,
+                &refs(&pending(&[
+                    Feature::EmailPassword,
+                    Feature::OAuth,
+                    Feature::WebAuthn,
+                ])),
+            )
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("011_webauthn is pending but not in the plan"));
+    }
+}
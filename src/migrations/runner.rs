@@ -1,10 +1,60 @@
-use sqlx::{AnyPool, Row};
+use sqlx::{AnyConnection, AnyPool, Row};
 use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use crate::cli::DatabaseType;
 use crate::config::AuthKitConfig;
 use crate::error::{CliError, CliResult};
-use crate::migrations::{get_migrations_from_config, AppliedMigration, Migration, MigrationState};
+use crate::migrations::lock::MigrationLock;
+use crate::migrations::{get_migrations_for_database, AppliedMigration, Migration, MigrationState};
+use crate::schema::objects;
+
+/// A column of the `_authkit_migrations` tracking table
+struct TrackingColumn {
+    name: &'static str,
+    sqlite: &'static str,
+    postgres: &'static str,
+}
+
+impl TrackingColumn {
+    fn definition(&self, db_type: DatabaseType) -> &'static str {
+        match db_type {
+            DatabaseType::Sqlite => self.sqlite,
+            DatabaseType::Postgres => self.postgres,
+        }
+    }
+}
+
+/// Every column of the tracking table. Columns appended here are added to
+/// existing tables by `ensure_migrations_table`, so they must be nullable or
+/// have a default.
+const TRACKING_COLUMNS: &[TrackingColumn] = &[
+    TrackingColumn {
+        name: "version",
+        sqlite: "INTEGER PRIMARY KEY",
+        postgres: "INTEGER PRIMARY KEY",
+    },
+    TrackingColumn {
+        name: "name",
+        sqlite: "TEXT NOT NULL",
+        postgres: "TEXT NOT NULL",
+    },
+    TrackingColumn {
+        name: "applied_at",
+        sqlite: "INTEGER NOT NULL",
+        postgres: "BIGINT NOT NULL",
+    },
+    TrackingColumn {
+        name: "checksum",
+        sqlite: "TEXT NOT NULL",
+        postgres: "TEXT NOT NULL",
+    },
+    TrackingColumn {
+        name: "execution_ms",
+        sqlite: "INTEGER",
+        postgres: "BIGINT",
+    },
+];
 
 /// Migration runner
 pub struct MigrationRunner<'a> {
@@ -17,42 +67,96 @@ impl<'a> MigrationRunner<'a> {
         Self { pool, db_type }
     }
 
-    /// Ensure the migrations tracking table exists
+    /// Ensure the migrations tracking table exists, with every column in
+    /// [`TRACKING_COLUMNS`]
     pub async fn ensure_migrations_table(&self) -> CliResult<()> {
+        let columns = TRACKING_COLUMNS
+            .iter()
+            .map(|c| format!("{} {}", c.name, c.definition(self.db_type)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS _authkit_migrations ({})",
+            columns
+        );
+
+        sqlx::query(&sql).execute(self.pool).await?;
+        self.add_missing_columns().await
+    }
+
+    /// Add columns introduced after a tracking table was created by an older version
+    async fn add_missing_columns(&self) -> CliResult<()> {
+        // SQLite has no ADD COLUMN IF NOT EXISTS, so compare against the existing columns
+        let existing = self.tracking_columns().await?;
+        for column in TRACKING_COLUMNS {
+            if existing.contains(column.name) {
+                continue;
+            }
+            let sql = format!(
+                "ALTER TABLE _authkit_migrations ADD COLUMN {} {}",
+                column.name,
+                column.definition(self.db_type)
+            );
+            // Another process may have added the column since it was checked
+            if let Err(e) = sqlx::query(&sql).execute(self.pool).await {
+                if !self.tracking_columns().await?.contains(column.name) {
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Names of the tracking table's existing columns
+    async fn tracking_columns(&self) -> CliResult<HashSet<String>> {
+        let sql = match self.db_type {
+            DatabaseType::Sqlite => "SELECT name FROM pragma_table_info('_authkit_migrations')",
+            DatabaseType::Postgres => {
+                "SELECT column_name::text FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = '_authkit_migrations'"
+            }
+        };
+
+        let rows = sqlx::query(sql).fetch_all(self.pool).await?;
+        rows.iter()
+            .map(|row| Ok(row.try_get::<String, _>(0)?))
+            .collect()
+    }
+
+    /// Whether the migrations tracking table exists yet
+    async fn migrations_table_exists(&self) -> CliResult<bool> {
         let sql = match self.db_type {
             DatabaseType::Sqlite => {
-                r#"
-                CREATE TABLE IF NOT EXISTS _authkit_migrations (
-                    version INTEGER PRIMARY KEY,
-                    name TEXT NOT NULL,
-                    applied_at INTEGER NOT NULL,
-                    checksum TEXT NOT NULL
-                )
-                "#
+                "SELECT name FROM sqlite_master WHERE type='table' AND name = '_authkit_migrations'"
             }
             DatabaseType::Postgres => {
-                r#"
-                CREATE TABLE IF NOT EXISTS _authkit_migrations (
-                    version INTEGER PRIMARY KEY,
-                    name TEXT NOT NULL,
-                    applied_at BIGINT NOT NULL,
-                    checksum TEXT NOT NULL
-                )
-                "#
+                "SELECT tablename::text FROM pg_tables WHERE tablename = '_authkit_migrations' AND schemaname = current_schema()"
             }
         };
 
-        sqlx::query(sql).execute(self.pool).await?;
-        Ok(())
+        Ok(sqlx::query(sql).fetch_optional(self.pool).await?.is_some())
     }
 
     /// Get all applied migrations from the database
+    ///
+    /// Returns an empty list when the tracking table hasn't been created yet.
     pub async fn get_applied_migrations(&self) -> CliResult<Vec<AppliedMigration>> {
-        let rows = sqlx::query(
-            "SELECT version, name, applied_at, checksum FROM _authkit_migrations ORDER BY version",
-        )
-        .fetch_all(self.pool)
-        .await?;
+        if !self.migrations_table_exists().await? {
+            return Ok(Vec::new());
+        }
+
+        // Tables not yet upgraded by ensure_migrations_table lack the newer columns
+        let existing = self.tracking_columns().await?;
+        let columns = TRACKING_COLUMNS
+            .iter()
+            .filter(|c| existing.contains(c.name))
+            .map(|c| c.name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "SELECT {} FROM _authkit_migrations ORDER BY version",
+            columns
+        );
+        let rows = sqlx::query(&sql).fetch_all(self.pool).await?;
 
         let mut migrations = Vec::new();
         for row in rows {
@@ -60,12 +164,14 @@ impl<'a> MigrationRunner<'a> {
             let name: String = row.get("name");
             let applied_at: i64 = row.get("applied_at");
             let checksum: String = row.get("checksum");
+            let execution_ms: Option<i64> = row.try_get("execution_ms").ok().flatten();
 
             migrations.push(AppliedMigration {
                 version: version as u32,
                 name,
                 applied_at,
                 checksum,
+                execution_ms,
             });
         }
 
@@ -86,6 +192,34 @@ impl<'a> MigrationRunner<'a> {
             .collect()
     }
 
+    /// Get applied migrations that are no longer available, newest first (the order to roll them back)
+    pub fn get_extra_migrations<'m>(
+        &self,
+        available: &[Migration],
+        applied: &'m [AppliedMigration],
+    ) -> Vec<&'m AppliedMigration> {
+        let available_versions: HashSet<u32> = available.iter().map(|m| m.version).collect();
+
+        let mut extra: Vec<&AppliedMigration> = applied
+            .iter()
+            .filter(|m| !available_versions.contains(&m.version))
+            .collect();
+        extra.sort_by_key(|m| std::cmp::Reverse(m.version));
+        extra
+    }
+
+    /// Get applied migrations above the target version, newest first (the order to roll them back)
+    pub fn get_rollback_migrations<'m>(
+        &self,
+        applied: &'m [AppliedMigration],
+        target: u32,
+    ) -> Vec<&'m AppliedMigration> {
+        let mut rollback: Vec<&AppliedMigration> =
+            applied.iter().filter(|m| m.version > target).collect();
+        rollback.sort_by_key(|m| std::cmp::Reverse(m.version));
+        rollback
+    }
+
     /// Get migration status
     pub fn get_migration_status(
         &self,
@@ -134,52 +268,240 @@ impl<'a> MigrationRunner<'a> {
         statuses
     }
 
-    /// Apply a single migration
+    /// Apply a single migration in its own transaction
     pub async fn apply_migration(&self, migration: &Migration) -> CliResult<()> {
+        let mut tx = self.pool.begin().await?;
+        self.apply_migration_in(&mut tx, migration).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Apply a single migration on an existing connection or transaction
+    pub async fn apply_migration_in(
+        &self,
+        conn: &mut AnyConnection,
+        migration: &Migration,
+    ) -> CliResult<()> {
+        let start = Instant::now();
+
         // Execute each statement individually (important for PostgreSQL)
-        for statement in migration.up_sql.split(';') {
-            let trimmed = statement.trim();
-            if trimmed.is_empty() {
+        for (index, sql) in Self::split_statements(&migration.up_sql).iter().enumerate() {
+            tracing::debug!(
+                "{:03}_{} statement {}: {}",
+                migration.version,
+                migration.name,
+                index,
+                sql
+            );
+            sqlx::query(sql)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| CliError::Migration {
+                    version: Some(migration.version),
+                    name: migration.name.clone(),
+                    statement_index: Some(index),
+                    message: format!("Failed to execute migration {}: {}", migration.name, e),
+                })?;
+        }
+
+        // Record the migration, rounding up so fast migrations don't show as 0ms
+        let elapsed_ms = start.elapsed().as_micros().div_ceil(1000) as i64;
+        Self::record_migration(conn, migration, Some(elapsed_ms)).await?;
+
+        Ok(())
+    }
+
+    /// Apply a migration with its `CREATE INDEX` statements built `CONCURRENTLY`
+    /// (PostgreSQL), so index builds don't block writes to large tables.
+    ///
+    /// `CONCURRENTLY` can't run inside a transaction: the other statements run in
+    /// the migration's transaction first, then each index is built on its own and
+    /// the migration is recorded last. Invalid indexes of the same name, left by a
+    /// failed or killed build, are dropped before each build and after a failure,
+    /// so re-running `migrate` builds them again; valid indexes are never dropped.
+    pub async fn apply_migration_concurrent_indexes(&self, migration: &Migration) -> CliResult<()> {
+        let start = Instant::now();
+        let statements = Self::split_statements(&migration.up_sql);
+        let failed = |index: usize, e: sqlx::Error, note: &str| CliError::Migration {
+            version: Some(migration.version),
+            name: migration.name.clone(),
+            statement_index: Some(index),
+            message: format!(
+                "Failed to execute migration {}: {}{}",
+                migration.name, e, note
+            ),
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let mut indexes = Vec::new();
+        for (index, sql) in statements.iter().enumerate() {
+            if let Some(concurrent) = concurrent_index(sql) {
+                indexes.push((index, sql, concurrent));
                 continue;
             }
+            tracing::debug!(
+                "{:03}_{} statement {}: {}",
+                migration.version,
+                migration.name,
+                index,
+                sql
+            );
+            sqlx::query(sql)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| failed(index, e, ""))?;
+        }
+        tx.commit().await?;
 
-            // Strip leading comment lines to get the actual SQL statement
-            let sql = Self::strip_leading_comments(trimmed);
-            if sql.is_empty() {
-                continue;
+        for (index, sql, concurrent) in indexes {
+            tracing::debug!(
+                "{:03}_{} statement {} (concurrent): {}",
+                migration.version,
+                migration.name,
+                index,
+                concurrent
+            );
+            // `IF NOT EXISTS` would skip an invalid leftover instead of rebuilding it
+            let names = objects::parse(sql).indexes;
+            self.drop_invalid_indexes(&names).await?;
+            if let Err(e) = sqlx::query(&concurrent).execute(self.pool).await {
+                if let Err(drop_error) = self.drop_invalid_indexes(&names).await {
+                    tracing::warn!("Failed to drop invalid indexes: {}", drop_error);
+                }
+                return Err(failed(
+                    index,
+                    e,
+                    " (its other statements were committed; re-run migrate to retry the index)",
+                ));
             }
+        }
 
-            sqlx::query(&sql).execute(self.pool).await.map_err(|e| {
-                CliError::Migration(format!(
-                    "Failed to execute migration {}: {}",
-                    migration.name, e
-                ))
-            })?;
+        let elapsed_ms = start.elapsed().as_micros().div_ceil(1000) as i64;
+        let mut conn = self.pool.acquire().await?;
+        Self::record_migration(&mut conn, migration, Some(elapsed_ms)).await?;
+
+        Ok(())
+    }
+
+    /// Drop the PostgreSQL indexes among `names` that are marked invalid
+    /// (`pg_index.indisvalid`), as a failed `CREATE INDEX CONCURRENTLY` leaves them.
+    /// Unqualified names are looked up on the search_path.
+    async fn drop_invalid_indexes(&self, names: &[String]) -> CliResult<()> {
+        for name in names {
+            let (schema, relname) = name.rsplit_once('.').unwrap_or(("", name));
+            let invalid: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM pg_index i \
+                 JOIN pg_class c ON c.oid = i.indexrelid \
+                 JOIN pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE c.relname = $1 AND NOT i.indisvalid \
+                 AND (($2::text = '' AND pg_table_is_visible(c.oid)) OR n.nspname = $2::text)",
+            )
+            .bind(relname)
+            .bind(schema)
+            .fetch_one(self.pool)
+            .await?;
+            if invalid > 0 {
+                tracing::debug!("Dropping invalid index {}", name);
+                sqlx::query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", name))
+                    .execute(self.pool)
+                    .await?;
+            }
         }
+        Ok(())
+    }
 
-        // Record the migration
-        self.record_migration(migration).await?;
+    /// Run a `[hooks]` SQL file in one transaction. Its statements are split like
+    /// a migration's, but nothing is recorded in the tracking table.
+    pub async fn run_hook(&self, path: &str) -> CliResult<()> {
+        let sql = std::fs::read_to_string(path)?;
+        let mut tx = self.pool.begin().await?;
+        for (index, statement) in Self::split_statements(&sql).iter().enumerate() {
+            tracing::debug!("hook {} statement {}: {}", path, index, statement);
+            sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| CliError::Migration {
+                    version: None,
+                    name: path.to_string(),
+                    statement_index: Some(index),
+                    message: format!("Failed to execute hook {}: {}", path, e),
+                })?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Run some of an applied migration's up statements again, in one transaction,
+    /// without touching its tracking row. Statements are `(index, sql)` pairs
+    /// from [`Self::split_statements`], so errors report the original index.
+    pub async fn reapply_statements(
+        &self,
+        migration: &Migration,
+        statements: &[(usize, String)],
+    ) -> CliResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for (index, sql) in statements {
+            sqlx::query(sql)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| CliError::Migration {
+                    version: Some(migration.version),
+                    name: migration.name.clone(),
+                    statement_index: Some(*index),
+                    message: format!("Failed to repair migration {}: {}", migration.name, e),
+                })?;
+        }
+        tx.commit().await?;
 
         Ok(())
     }
 
-    /// Record a migration in the tracking table
-    async fn record_migration(&self, migration: &Migration) -> CliResult<()> {
+    /// Record a migration in the tracking table, with how long its SQL took to run.
+    ///
+    /// An existing row for the version is overwritten, so a retry after a crash
+    /// between running the SQL and recording it does not fail on the primary key.
+    async fn record_migration(
+        conn: &mut AnyConnection,
+        migration: &Migration,
+        execution_ms: Option<i64>,
+    ) -> CliResult<()> {
         let now = chrono::Utc::now().timestamp();
 
         sqlx::query(
-            "INSERT INTO _authkit_migrations (version, name, applied_at, checksum) VALUES ($1, $2, $3, $4)",
+            "INSERT INTO _authkit_migrations (version, name, applied_at, checksum, execution_ms) VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (version) DO UPDATE SET name = excluded.name, applied_at = excluded.applied_at, \
+             checksum = excluded.checksum, execution_ms = excluded.execution_ms",
         )
         .bind(migration.version as i32)
         .bind(&migration.name)
         .bind(now)
         .bind(&migration.checksum)
-        .execute(self.pool)
+        .bind(execution_ms)
+        .execute(conn)
         .await?;
 
         Ok(())
     }
 
+    /// Record a migration as applied without executing its SQL, replacing any existing record
+    pub async fn baseline_migration(&self, migration: &Migration) -> CliResult<()> {
+        self.remove_migration_record(migration.version).await?;
+
+        let mut conn = self.pool.acquire().await?;
+        Self::record_migration(&mut conn, migration, None).await
+    }
+
+    /// Split a migration script into the statements executed one by one,
+    /// with leading comment lines removed and comment-only chunks dropped
+    pub fn split_statements(sql: &str) -> Vec<String> {
+        sql.split(';')
+            .map(|statement| Self::strip_leading_comments(statement.trim()))
+            .filter(|statement| !statement.is_empty())
+            .collect()
+    }
+
     /// Strip leading comment lines from a SQL statement
     /// Comments start with "--" and continue to end of line
     fn strip_leading_comments(sql: &str) -> String {
@@ -201,82 +523,173 @@ impl<'a> MigrationRunner<'a> {
         lines.join("\n").trim().to_string()
     }
 
-    /// Run all pending migrations based on config
-    #[allow(dead_code)]
+    /// Run all pending migrations based on config, returning their names
+    ///
+    /// On PostgreSQL this holds the same advisory lock as `authkit migrate`
+    /// (waiting up to a minute for it), so concurrent callers take turns.
+    /// SQLite runs without it, as in [`crate::migrations::migrate`].
     pub async fn run_pending(&self, config: &AuthKitConfig) -> CliResult<Vec<String>> {
         self.ensure_migrations_table().await?;
 
-        let available = get_migrations_from_config(config);
-        let applied = self.get_applied_migrations().await?;
-        let pending = self.get_pending_migrations(&available, &applied);
-
-        let mut applied_names = Vec::new();
+        let lock = match self.db_type {
+            DatabaseType::Postgres => Some(
+                MigrationLock::acquire(self.pool, self.db_type, Duration::from_secs(60)).await?,
+            ),
+            DatabaseType::Sqlite => None,
+        };
+        let result = async {
+            let available = get_migrations_for_database(config, self.db_type)?;
+            let applied = self.get_applied_migrations().await?;
+            let pending = self.get_pending_migrations(&available, &applied);
 
-        for migration in pending {
-            self.apply_migration(migration).await?;
-            applied_names.push(migration.name.clone());
+            let mut applied_names = Vec::new();
+            for migration in pending {
+                self.apply_migration(migration).await?;
+                applied_names.push(migration.name.clone());
+            }
+            Ok(applied_names)
+        }
+        .await;
+        if let Some(lock) = lock {
+            if let Err(e) = lock.release().await {
+                tracing::warn!("Failed to release the migration lock: {}", e);
+            }
         }
+        result
+    }
+
+    /// Get applied migrations whose stored checksum differs from the available migration
+    ///
+    /// Returns `(version, expected, actual)` where `expected` is the stored checksum.
+    pub fn get_checksum_mismatches(
+        &self,
+        available: &[Migration],
+        applied: &[AppliedMigration],
+    ) -> Vec<(u32, String, String)> {
+        let available_map: HashMap<u32, &Migration> =
+            available.iter().map(|m| (m.version, m)).collect();
 
-        Ok(applied_names)
+        applied
+            .iter()
+            .filter_map(|applied_migration| {
+                let migration = available_map.get(&applied_migration.version)?;
+                if migration.matches_checksum(&applied_migration.checksum) {
+                    return None;
+                }
+                Some((
+                    applied_migration.version,
+                    applied_migration.checksum.clone(),
+                    migration.checksum.clone(),
+                ))
+            })
+            .collect()
     }
 
     /// Verify checksums of applied migrations
-    #[allow(dead_code)]
     pub async fn verify_checksums(&self, config: &AuthKitConfig) -> CliResult<()> {
-        let available = get_migrations_from_config(config);
+        // Compare against the dialect that was actually applied, not the config's type
+        let available = get_migrations_for_database(config, self.db_type)?;
         let applied = self.get_applied_migrations().await?;
 
-        let available_map: HashMap<u32, &Migration> =
-            available.iter().map(|m| (m.version, m)).collect();
-
-        for applied_migration in &applied {
-            if let Some(migration) = available_map.get(&applied_migration.version) {
-                if migration.checksum != applied_migration.checksum {
-                    return Err(CliError::ChecksumMismatch {
-                        version: applied_migration.version,
-                        expected: applied_migration.checksum.clone(),
-                        actual: migration.checksum.clone(),
-                    });
-                }
-            }
+        if let Some((version, expected, actual)) = self
+            .get_checksum_mismatches(&available, &applied)
+            .into_iter()
+            .next()
+        {
+            return Err(CliError::ChecksumMismatch {
+                version,
+                expected,
+                actual,
+            });
         }
 
         Ok(())
     }
 
-    /// Rollback a single migration
-    #[allow(dead_code)]
+    /// Rollback a single migration in its own transaction
     pub async fn rollback_migration(&self, migration: &Migration) -> CliResult<()> {
-        // Execute each statement individually
-        for statement in migration.down_sql.split(';') {
-            let trimmed = statement.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
+        let mut tx = self.pool.begin().await?;
+        self.rollback_migration_in(&mut tx, migration).await?;
+        tx.commit().await?;
 
-            // Strip leading comment lines to get the actual SQL statement
-            let sql = Self::strip_leading_comments(trimmed);
-            if sql.is_empty() {
-                continue;
-            }
+        Ok(())
+    }
 
-            sqlx::query(&sql).execute(self.pool).await.map_err(|e| {
-                CliError::Migration(format!(
-                    "Failed to rollback migration {}: {}",
-                    migration.name, e
-                ))
-            })?;
+    /// Rollback a single migration on an existing connection or transaction
+    pub async fn rollback_migration_in(
+        &self,
+        conn: &mut AnyConnection,
+        migration: &Migration,
+    ) -> CliResult<()> {
+        let statements = Self::split_statements(&migration.down_sql);
+        if self.db_type == DatabaseType::Sqlite {
+            Self::check_sqlite_drop_column(conn, migration, &statements).await?;
+        }
+
+        // Execute each statement individually
+        for (index, sql) in statements.iter().enumerate() {
+            tracing::debug!(
+                "{:03}_{} statement {}: {}",
+                migration.version,
+                migration.name,
+                index,
+                sql
+            );
+            sqlx::query(sql)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| CliError::Migration {
+                    version: Some(migration.version),
+                    name: migration.name.clone(),
+                    statement_index: Some(index),
+                    message: format!("Failed to rollback migration {}: {}", migration.name, e),
+                })?;
         }
 
         // Remove the migration record
-        self.remove_migration_record(migration.version).await?;
+        sqlx::query("DELETE FROM _authkit_migrations WHERE version = $1")
+            .bind(migration.version as i32)
+            .execute(conn)
+            .await?;
 
         Ok(())
     }
 
+    /// Refuse a rollback that drops columns on SQLite versions without DROP COLUMN,
+    /// instead of letting the engine report a bare syntax error
+    async fn check_sqlite_drop_column(
+        conn: &mut AnyConnection,
+        migration: &Migration,
+        statements: &[String],
+    ) -> CliResult<()> {
+        let Some(index) = statements
+            .iter()
+            .position(|sql| sql.to_ascii_uppercase().contains("DROP COLUMN"))
+        else {
+            return Ok(());
+        };
+
+        let version: String = sqlx::query_scalar("SELECT sqlite_version()")
+            .fetch_one(&mut *conn)
+            .await?;
+        if sqlite_supports_drop_column(&version) {
+            return Ok(());
+        }
+
+        Err(CliError::Migration {
+            version: Some(migration.version),
+            name: migration.name.clone(),
+            statement_index: Some(index),
+            message: format!(
+                "Cannot roll back {}: it drops columns, which needs SQLite 3.35.0 or newer (this database runs {}). \
+                 Recreate the table without the columns by hand, then remove the record with `authkit repair --mark-unapplied {}`",
+                migration.name, version, migration.version
+            ),
+        })
+    }
+
     /// Remove a migration record from the tracking table
-    #[allow(dead_code)]
-    async fn remove_migration_record(&self, version: u32) -> CliResult<()> {
+    pub async fn remove_migration_record(&self, version: u32) -> CliResult<()> {
         sqlx::query("DELETE FROM _authkit_migrations WHERE version = $1")
             .bind(version as i32)
             .execute(self.pool)
@@ -286,10 +699,340 @@ impl<'a> MigrationRunner<'a> {
     }
 }
 
+/// Whether a `sqlite_version()` string supports `ALTER TABLE ... DROP COLUMN` (3.35.0+)
+fn sqlite_supports_drop_column(version: &str) -> bool {
+    let mut parts = version
+        .trim()
+        .split('.')
+        .map(|part| part.parse::<u32>().unwrap_or(0));
+    let major = parts.next().unwrap_or(0);
+    let minor = parts.next().unwrap_or(0);
+    (major, minor) >= (3, 35)
+}
+
+/// `CREATE [UNIQUE] INDEX` rewritten as `CREATE [UNIQUE] INDEX CONCURRENTLY`
+/// (unchanged if it already is); `None` for any other statement
+fn concurrent_index(sql: &str) -> Option<String> {
+    let upper = sql.to_ascii_uppercase();
+    let prefix = ["CREATE INDEX ", "CREATE UNIQUE INDEX "]
+        .into_iter()
+        .find(|prefix| upper.starts_with(prefix))?;
+    if upper[prefix.len()..]
+        .trim_start()
+        .starts_with("CONCURRENTLY ")
+    {
+        return Some(sql.to_string());
+    }
+    Some(format!(
+        "{}CONCURRENTLY {}",
+        &sql[..prefix.len()],
+        &sql[prefix.len()..]
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_applied_migrations_empty_without_tracking_table() {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+
+        assert!(runner.get_applied_migrations().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_statement_reports_structured_error() {
+        sqlx::any::install_default_drivers();
+        // A single connection, so every query sees the same in-memory database
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+        runner.ensure_migrations_table().await.unwrap();
+
+        let up_sql = "-- Setup\nCREATE TABLE t (id TEXT);\nCREATE TABLE t (id TEXT);";
+        let migration = Migration {
+            version: 7,
+            name: "broken".to_string(),
+            up_sql: up_sql.to_string(),
+            down_sql: String::new(),
+            checksum: crate::migrations::compute_checksum(up_sql),
+        };
+
+        match runner.apply_migration(&migration).await {
+            Err(CliError::Migration {
+                version,
+                name,
+                statement_index,
+                message,
+            }) => {
+                assert_eq!(version, Some(7));
+                assert_eq!(name, "broken");
+                assert_eq!(statement_index, Some(1));
+                assert!(message.starts_with("Failed to execute migration broken:"));
+            }
+            other => panic!("expected a migration error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ensure_adds_execution_ms_to_existing_table() {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // Tracking table as created by earlier releases
+        sqlx::query(
+            "CREATE TABLE _authkit_migrations (version INTEGER PRIMARY KEY, name TEXT NOT NULL, applied_at INTEGER NOT NULL, checksum TEXT NOT NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO _authkit_migrations VALUES (1, 'base', 1, 'abc')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+        assert_eq!(runner.get_applied_migrations().await.unwrap().len(), 1);
+
+        // Running it twice must not try to add the column again
+        runner.ensure_migrations_table().await.unwrap();
+        runner.ensure_migrations_table().await.unwrap();
+
+        let applied = runner.get_applied_migrations().await.unwrap();
+        assert_eq!(applied[0].execution_ms, None);
+    }
+
+    #[tokio::test]
+    async fn test_ensure_upgrades_old_tracking_table_without_data_loss() {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // Only the columns of the first release
+        sqlx::query(
+            "CREATE TABLE _authkit_migrations (version INTEGER PRIMARY KEY, name TEXT NOT NULL, applied_at INTEGER NOT NULL, checksum TEXT NOT NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO _authkit_migrations VALUES (1, 'base', 10, 'abc'), (2, 'email_verification', 20, 'def')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+        runner.ensure_migrations_table().await.unwrap();
+
+        let columns = runner.tracking_columns().await.unwrap();
+        for column in TRACKING_COLUMNS {
+            assert!(columns.contains(column.name), "missing {}", column.name);
+        }
+
+        let applied = runner.get_applied_migrations().await.unwrap();
+        let rows: Vec<_> = applied
+            .iter()
+            .map(|m| {
+                (
+                    m.version,
+                    m.name.as_str(),
+                    m.applied_at,
+                    m.checksum.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [(1, "base", 10, "abc"), (2, "email_verification", 20, "def")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_and_remove_migration_sqlite() {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+        runner.ensure_migrations_table().await.unwrap();
+
+        for (version, name) in [(1, "first"), (2, "second")] {
+            let up_sql = "SELECT 1;";
+            let migration = Migration {
+                version,
+                name: name.to_string(),
+                up_sql: up_sql.to_string(),
+                down_sql: String::new(),
+                checksum: crate::migrations::compute_checksum(up_sql),
+            };
+            runner.apply_migration(&migration).await.unwrap();
+        }
+
+        // Every bound value lands in its own column
+        let applied = runner.get_applied_migrations().await.unwrap();
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied[1].version, 2);
+        assert_eq!(applied[1].name, "second");
+        assert_eq!(
+            applied[1].checksum,
+            crate::migrations::compute_checksum("SELECT 1;")
+        );
+        assert!(applied[1].applied_at > 0);
+        assert!(applied[1].execution_ms.unwrap() > 0);
+
+        runner.remove_migration_record(1).await.unwrap();
+        let remaining: Vec<u32> = runner
+            .get_applied_migrations()
+            .await
+            .unwrap()
+            .iter()
+            .map(|m| m.version)
+            .collect();
+        assert_eq!(remaining, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_record_migration_twice_keeps_one_row() {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+        runner.ensure_migrations_table().await.unwrap();
+
+        let mut migration = Migration {
+            version: 2,
+            name: "email_verification".to_string(),
+            up_sql: String::new(),
+            down_sql: String::new(),
+            checksum: "first".to_string(),
+        };
+        let mut conn = pool.acquire().await.unwrap();
+        MigrationRunner::record_migration(&mut conn, &migration, Some(5))
+            .await
+            .unwrap();
+        migration.checksum = "second".to_string();
+        MigrationRunner::record_migration(&mut conn, &migration, None)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let applied = runner.get_applied_migrations().await.unwrap();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].checksum, "second");
+        assert_eq!(applied[0].execution_ms, None);
+    }
+
+    #[tokio::test]
+    async fn test_extra_migrations_newest_first() {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .connect_lazy("sqlite::memory:")
+            .unwrap();
+        let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+
+        let available = vec![Migration {
+            version: 1,
+            name: "base".to_string(),
+            up_sql: String::new(),
+            down_sql: String::new(),
+            checksum: String::new(),
+        }];
+        let applied: Vec<AppliedMigration> =
+            [(1, "base"), (2, "email_verification"), (4, "two_factor")]
+                .iter()
+                .map(|(version, name)| AppliedMigration {
+                    version: *version,
+                    name: name.to_string(),
+                    applied_at: 0,
+                    checksum: String::new(),
+                    execution_ms: None,
+                })
+                .collect();
+
+        let extra: Vec<u32> = runner
+            .get_extra_migrations(&available, &applied)
+            .iter()
+            .map(|m| m.version)
+            .collect();
+        assert_eq!(extra, vec![4, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_migrations_above_target_newest_first() {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .connect_lazy("sqlite::memory:")
+            .unwrap();
+        let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+
+        let applied: Vec<AppliedMigration> = [1, 4, 2]
+            .iter()
+            .map(|version| AppliedMigration {
+                version: *version,
+                name: String::new(),
+                applied_at: 0,
+                checksum: String::new(),
+                execution_ms: None,
+            })
+            .collect();
+        let versions = |target| -> Vec<u32> {
+            runner
+                .get_rollback_migrations(&applied, target)
+                .iter()
+                .map(|m| m.version)
+                .collect()
+        };
+
+        assert_eq!(versions(1), vec![4, 2]);
+        assert_eq!(versions(2), vec![4]);
+        assert_eq!(versions(0), vec![4, 2, 1]);
+        assert!(versions(4).is_empty());
+    }
+
+    #[test]
+    fn test_sqlite_supports_drop_column() {
+        assert!(!sqlite_supports_drop_column("3.34.1"));
+        assert!(!sqlite_supports_drop_column("3.8.11"));
+        assert!(sqlite_supports_drop_column("3.35.0"));
+        assert!(sqlite_supports_drop_column("3.35.5"));
+        assert!(sqlite_supports_drop_column("3.45.1"));
+        assert!(sqlite_supports_drop_column("4.0.0"));
+        assert!(!sqlite_supports_drop_column("2.99.9"));
+    }
+
+    #[test]
+    fn test_split_statements_drops_comment_only_chunks() {
+        let sql = "-- Users\nCREATE TABLE users (id TEXT);\n\n-- Trailing note;\n-- Index\nCREATE INDEX idx ON users(id);\n";
+        assert_eq!(
+            MigrationRunner::split_statements(sql),
+            vec![
+                "CREATE TABLE users (id TEXT)",
+                "CREATE INDEX idx ON users(id)"
+            ]
+        );
+    }
+
     #[test]
     fn test_strip_leading_comments_simple() {
         let sql = "-- This is a comment\nCREATE TABLE users (id TEXT)";
@@ -349,4 +1092,22 @@ CREATE TABLE IF NOT EXISTS accounts (
         assert!(result.starts_with("CREATE TABLE IF NOT EXISTS accounts"));
         assert!(result.contains("id TEXT PRIMARY KEY"));
     }
+
+    #[test]
+    fn test_concurrent_index_rewrites_only_index_statements() {
+        assert_eq!(
+            concurrent_index("CREATE INDEX IF NOT EXISTS idx_a ON t(a)").unwrap(),
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_a ON t(a)"
+        );
+        assert_eq!(
+            concurrent_index("create unique index idx_b on t(b)").unwrap(),
+            "create unique index CONCURRENTLY idx_b on t(b)"
+        );
+        assert_eq!(
+            concurrent_index("CREATE INDEX CONCURRENTLY idx_c ON t(c)").unwrap(),
+            "CREATE INDEX CONCURRENTLY idx_c ON t(c)"
+        );
+        assert_eq!(concurrent_index("CREATE TABLE t (a TEXT)"), None);
+        assert_eq!(concurrent_index("DROP INDEX IF EXISTS idx_a"), None);
+    }
 }
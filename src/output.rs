@@ -0,0 +1,39 @@
+//! Console output for `--quiet`
+//!
+//! Quiet mode keeps the result line of each command and its errors and warnings,
+//! dropping headers, progress bars and hints. Decorative lines go through
+//! [`note!`] and [`enote!`] instead of `println!`/`eprintln!`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Suppress non-essential output for the rest of the process
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether `--quiet` was given
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `println!` for headers, progress lines and hints; silent with `--quiet`
+macro_rules! note {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// `eprintln!` for progress notes on stderr; silent with `--quiet`
+macro_rules! enote {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use {enote, note};
@@ -0,0 +1,149 @@
+//! Comparison of the schema the enabled features should produce against a live database
+
+use crate::database::TableInfo;
+use crate::migrations::Migration;
+use crate::schema::{is_authkit_table, objects, MIGRATIONS_LOCK_TABLE, MIGRATIONS_TABLE};
+
+/// A table and the columns it should have once every migration ran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedTable {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+/// Differences between the expected and the actual schema
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    pub missing_tables: Vec<String>,
+    /// AuthKit tables present in the database that no enabled feature creates
+    pub extra_tables: Vec<String>,
+    /// `(table, column)` pairs missing from tables that do exist
+    pub missing_columns: Vec<(String, String)>,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.problem_count() == 0
+    }
+
+    pub fn problem_count(&self) -> usize {
+        self.missing_tables.len() + self.extra_tables.len() + self.missing_columns.len()
+    }
+}
+
+/// Tables and columns created by the given migrations, in creation order
+pub fn expected_tables(migrations: &[Migration]) -> Vec<ExpectedTable> {
+    let mut tables: Vec<ExpectedTable> = Vec::new();
+
+    for migration in migrations {
+        let objects = objects::parse(&migration.up_sql);
+        for table in objects.tables {
+            tables.push(ExpectedTable {
+                name: table.name,
+                columns: table.columns,
+            });
+        }
+        for column in objects.columns {
+            if let Some(table) = tables.iter_mut().find(|t| t.name == column.table) {
+                table.columns.push(column.name);
+            }
+        }
+    }
+
+    tables
+}
+
+/// Compare the expected tables against those found in the database.
+///
+/// Tables that don't belong to AuthKit (named with `prefix`) are the application's
+/// and never reported.
+pub fn diff(expected: &[ExpectedTable], actual: &[TableInfo], prefix: &str) -> SchemaDiff {
+    let mut result = SchemaDiff::default();
+
+    for table in expected {
+        match actual.iter().find(|t| t.name == table.name) {
+            None => result.missing_tables.push(table.name.clone()),
+            Some(found) => {
+                for column in &table.columns {
+                    if !found.columns.contains(column) {
+                        result
+                            .missing_columns
+                            .push((table.name.clone(), column.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    for table in actual {
+        let bookkeeping = table.name == MIGRATIONS_TABLE || table.name == MIGRATIONS_LOCK_TABLE;
+        if !bookkeeping
+            && is_authkit_table(&table.name, prefix)
+            && !expected.iter().any(|t| t.name == table.name)
+        {
+            result.extra_tables.push(table.name.clone());
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::DatabaseType;
+    use crate::config::Feature;
+    use crate::schema::get_feature_migration;
+
+    fn actual(name: &str, columns: &[&str]) -> TableInfo {
+        TableInfo {
+            name: name.to_string(),
+            create_sql: None,
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_expected_tables_include_added_columns() {
+        let migrations = [
+            get_feature_migration(Feature::EmailPassword, DatabaseType::Sqlite),
+            get_feature_migration(Feature::EmailVerification, DatabaseType::Sqlite),
+        ];
+        let tables = expected_tables(&migrations);
+        let users = tables.iter().find(|t| t.name == "users").unwrap();
+        assert!(users.columns.contains(&"email".to_string()));
+        assert!(users.columns.contains(&"email_verified_at".to_string()));
+    }
+
+    #[test]
+    fn test_diff_reports_missing_and_extra() {
+        let expected = vec![
+            ExpectedTable {
+                name: "users".to_string(),
+                columns: vec!["id".to_string(), "email_verified".to_string()],
+            },
+            ExpectedTable {
+                name: "sessions".to_string(),
+                columns: vec!["id".to_string()],
+            },
+        ];
+        let result = diff(
+            &expected,
+            &[
+                actual("users", &["id"]),
+                actual("webauthn_credentials", &["id"]),
+                actual("_authkit_migrations", &["version"]),
+                actual("app_orders", &["id"]),
+            ],
+            "",
+        );
+
+        assert_eq!(result.missing_tables, vec!["sessions"]);
+        assert_eq!(result.extra_tables, vec!["webauthn_credentials"]);
+        assert_eq!(
+            result.missing_columns,
+            vec![("users".to_string(), "email_verified".to_string())]
+        );
+        assert_eq!(result.problem_count(), 3);
+    }
+}
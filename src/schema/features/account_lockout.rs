@@ -0,0 +1,84 @@
+//! Account lockout feature schema
+//!
+//! This feature supports brute-force protection by:
+//! - Adding failed_login_attempts, locked_until and last_failed_login_at columns to accounts table
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Account Lockout Feature
+-- Adds failed login tracking to accounts table
+
+-- Add lockout columns to accounts table (locked_until is NULL while unlocked)
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS failed_login_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS locked_until BIGINT;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS last_failed_login_at BIGINT;
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Remove account lockout feature
+
+-- Remove lockout columns from accounts table
+ALTER TABLE accounts DROP COLUMN IF EXISTS last_failed_login_at;
+ALTER TABLE accounts DROP COLUMN IF EXISTS locked_until;
+ALTER TABLE accounts DROP COLUMN IF EXISTS failed_login_attempts;
+"#;
+
+/// SQLite schema - UP migration
+/// Note: SQLite has limited ALTER TABLE support, so we use a different approach
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Account Lockout Feature
+-- Adds failed login tracking to accounts table
+
+-- SQLite: Add lockout columns
+-- Note: SQLite 3.35.0+ supports ADD COLUMN, older versions need table recreation
+ALTER TABLE accounts ADD COLUMN failed_login_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE accounts ADD COLUMN locked_until INTEGER;
+ALTER TABLE accounts ADD COLUMN last_failed_login_at INTEGER;
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Remove account lockout feature
+-- Note: SQLite doesn't support DROP COLUMN in older versions
+-- This requires table recreation for full compatibility
+
+-- For SQLite 3.35.0+, we can drop columns directly
+-- For older versions, a table recreation would be needed
+ALTER TABLE accounts DROP COLUMN last_failed_login_at;
+ALTER TABLE accounts DROP COLUMN locked_until;
+ALTER TABLE accounts DROP COLUMN failed_login_attempts;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_adds_columns() {
+        assert!(POSTGRES_UP
+            .contains("ADD COLUMN IF NOT EXISTS failed_login_attempts INTEGER NOT NULL DEFAULT 0"));
+        assert!(POSTGRES_UP.contains("ADD COLUMN IF NOT EXISTS locked_until BIGINT"));
+        assert!(POSTGRES_UP.contains("ADD COLUMN IF NOT EXISTS last_failed_login_at BIGINT"));
+    }
+
+    #[test]
+    fn test_sqlite_up_adds_columns() {
+        assert!(SQLITE_UP.contains("ADD COLUMN failed_login_attempts INTEGER NOT NULL DEFAULT 0"));
+        assert!(SQLITE_UP.contains("ADD COLUMN locked_until INTEGER"));
+        assert!(SQLITE_UP.contains("ADD COLUMN last_failed_login_at INTEGER"));
+    }
+
+    #[test]
+    fn test_down_removes_columns() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            for column in [
+                "failed_login_attempts",
+                "locked_until",
+                "last_failed_login_at",
+            ] {
+                assert!(down.contains(column));
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! API keys feature schema
+//!
+//! This feature adds machine-to-machine authentication by creating:
+//! - api_keys: Hashed API keys owned by a user, with optional scopes and expiry
+//!
+//! Only a hash of each key is stored; `prefix` keeps the first characters so
+//! users can tell their keys apart.
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit API Keys Feature
+-- Adds API keys for machine-to-machine authentication
+
+-- API keys table: Hashed keys owned by users
+CREATE TABLE IF NOT EXISTS api_keys (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    scopes JSONB,
+    last_used_at BIGINT,
+    expires_at BIGINT,
+    created_at BIGINT NOT NULL,
+    revoked_at BIGINT
+);
+
+-- Indexes for key lookups and per-user listings
+CREATE UNIQUE INDEX IF NOT EXISTS idx_api_keys_key_hash ON api_keys(key_hash);
+CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys(user_id);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_api_keys_user_id;
+DROP INDEX IF EXISTS idx_api_keys_key_hash;
+
+-- Drop table
+DROP TABLE IF EXISTS api_keys;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit API Keys Feature
+-- Adds API keys for machine-to-machine authentication
+
+-- API keys table: Hashed keys owned by users (scopes holds a JSON array)
+CREATE TABLE IF NOT EXISTS api_keys (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    scopes TEXT,
+    last_used_at INTEGER,
+    expires_at INTEGER,
+    created_at INTEGER NOT NULL,
+    revoked_at INTEGER
+);
+
+-- Indexes for key lookups and per-user listings
+CREATE UNIQUE INDEX IF NOT EXISTS idx_api_keys_key_hash ON api_keys(key_hash);
+CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys(user_id);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_api_keys_user_id;
+DROP INDEX IF EXISTS idx_api_keys_key_hash;
+
+-- Drop table
+DROP TABLE IF EXISTS api_keys;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_creates_api_keys_table() {
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS api_keys"));
+        assert!(POSTGRES_UP.contains("scopes JSONB"));
+        assert!(POSTGRES_UP.contains("REFERENCES users(id) ON DELETE CASCADE"));
+    }
+
+    #[test]
+    fn test_sqlite_up_creates_api_keys_table() {
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS api_keys"));
+        assert!(SQLITE_UP.contains("scopes TEXT"));
+        assert!(SQLITE_UP.contains("revoked_at INTEGER"));
+    }
+
+    #[test]
+    fn test_indexes_and_down_migrations() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("CREATE UNIQUE INDEX IF NOT EXISTS idx_api_keys_key_hash"));
+            assert!(up.contains("CREATE INDEX IF NOT EXISTS idx_api_keys_user_id"));
+        }
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let index = down
+                .find("DROP INDEX IF EXISTS idx_api_keys_key_hash")
+                .unwrap();
+            let table = down.find("DROP TABLE IF EXISTS api_keys").unwrap();
+            assert!(index < table);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Audit log feature schema
+//!
+//! This feature adds an append-only audit trail by creating:
+//! - auth_audit_log: One row per authentication event (sign-in, password change, ...)
+//!
+//! Rows outlive the user they refer to: deleting a user only clears `user_id`.
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Audit Log Feature
+-- Adds an append-only log of authentication events
+
+-- Audit log table: Authentication events
+CREATE TABLE IF NOT EXISTS auth_audit_log (
+    id TEXT PRIMARY KEY,
+    user_id TEXT REFERENCES users(id) ON DELETE SET NULL,
+    event_type TEXT NOT NULL,
+    ip_address TEXT,
+    user_agent TEXT,
+    metadata JSONB,
+    created_at BIGINT NOT NULL
+);
+
+-- Index for per-user event history
+CREATE INDEX IF NOT EXISTS idx_auth_audit_log_user_id_created_at ON auth_audit_log(user_id, created_at);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Drop index first
+DROP INDEX IF EXISTS idx_auth_audit_log_user_id_created_at;
+
+-- Drop table
+DROP TABLE IF EXISTS auth_audit_log;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Audit Log Feature
+-- Adds an append-only log of authentication events
+
+-- Audit log table: Authentication events (metadata holds a JSON document)
+CREATE TABLE IF NOT EXISTS auth_audit_log (
+    id TEXT PRIMARY KEY,
+    user_id TEXT REFERENCES users(id) ON DELETE SET NULL,
+    event_type TEXT NOT NULL,
+    ip_address TEXT,
+    user_agent TEXT,
+    metadata TEXT,
+    created_at INTEGER NOT NULL
+);
+
+-- Index for per-user event history
+CREATE INDEX IF NOT EXISTS idx_auth_audit_log_user_id_created_at ON auth_audit_log(user_id, created_at);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Drop index first
+DROP INDEX IF EXISTS idx_auth_audit_log_user_id_created_at;
+
+-- Drop table
+DROP TABLE IF EXISTS auth_audit_log;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_creates_audit_log_table() {
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS auth_audit_log"));
+        assert!(POSTGRES_UP.contains("metadata JSONB"));
+        assert!(POSTGRES_UP.contains("REFERENCES users(id) ON DELETE SET NULL"));
+    }
+
+    #[test]
+    fn test_sqlite_up_creates_audit_log_table() {
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS auth_audit_log"));
+        assert!(SQLITE_UP.contains("metadata TEXT"));
+    }
+
+    #[test]
+    fn test_index_on_user_and_time() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("ON auth_audit_log(user_id, created_at)"));
+        }
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            assert!(down.contains("DROP INDEX IF EXISTS idx_auth_audit_log_user_id_created_at"));
+            assert!(down.contains("DROP TABLE IF EXISTS auth_audit_log"));
+        }
+    }
+}
@@ -6,14 +6,16 @@
 //! - sessions: Active user sessions
 //! - verification: Tokens for password reset, magic links, etc.
 
-/// PostgreSQL schema - UP migration
-pub const POSTGRES_UP: &str = r#"
+use crate::config::IdType;
+
+/// PostgreSQL schema - UP migration template; see [`postgres_up`]
+const POSTGRES_UP_TEMPLATE: &str = r#"
 -- AuthKit Base Schema
 -- Feature: email_password
 
 -- Users table: Core user data
 CREATE TABLE IF NOT EXISTS users (
-    id TEXT PRIMARY KEY,
+    id {id} PRIMARY KEY{id_default},
     email TEXT NOT NULL UNIQUE,
     name TEXT,
     created_at BIGINT NOT NULL,
@@ -24,8 +26,8 @@ CREATE TABLE IF NOT EXISTS users (
 -- For email/password, provider = 'credential' and password_hash is set
 -- For OAuth (future), provider = 'google'/'github'/etc
 CREATE TABLE IF NOT EXISTS accounts (
-    id TEXT PRIMARY KEY,
-    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    id {id} PRIMARY KEY{id_default},
+    user_id {id} NOT NULL REFERENCES users(id) ON DELETE CASCADE,
     provider TEXT NOT NULL,
     provider_account_id TEXT NOT NULL,
     password_hash TEXT,
@@ -36,8 +38,8 @@ CREATE TABLE IF NOT EXISTS accounts (
 
 -- Sessions table: Active user sessions
 CREATE TABLE IF NOT EXISTS sessions (
-    id TEXT PRIMARY KEY,
-    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    id {id} PRIMARY KEY{id_default},
+    user_id {id} NOT NULL REFERENCES users(id) ON DELETE CASCADE,
     token TEXT NOT NULL UNIQUE,
     expires_at BIGINT NOT NULL,
     created_at BIGINT NOT NULL,
@@ -46,9 +48,14 @@ CREATE TABLE IF NOT EXISTS sessions (
 );
 
 -- Verification table: Tokens for password reset, magic links, etc.
+-- expires_at is the issue time plus the token type's lifetime. Suggested defaults
+-- (authkit_cli::tokens::default_token_ttl_seconds):
+--   password_reset: 3600 seconds (1 hour)
+--   email_verify: 86400 seconds (24 hours)
+--   magic_link: 900 seconds (15 minutes)
 CREATE TABLE IF NOT EXISTS verification (
-    id TEXT PRIMARY KEY,
-    user_id TEXT REFERENCES users(id) ON DELETE CASCADE,
+    id {id} PRIMARY KEY{id_default},
+    user_id {id} REFERENCES users(id) ON DELETE CASCADE,
     identifier TEXT NOT NULL,
     token_hash TEXT NOT NULL UNIQUE,
     token_type TEXT NOT NULL,
@@ -69,6 +76,18 @@ CREATE INDEX IF NOT EXISTS idx_verification_identifier ON verification(identifie
 CREATE INDEX IF NOT EXISTS idx_verification_expires_at ON verification(expires_at);
 "#;
 
+/// PostgreSQL schema - UP migration, with the id columns of the base tables
+/// (and the user_id columns referencing them) typed by `id_type`
+pub fn postgres_up(id_type: IdType) -> String {
+    let (id, id_default) = match id_type {
+        IdType::Text => ("TEXT", ""),
+        IdType::Uuid => ("UUID", " DEFAULT gen_random_uuid()"),
+    };
+    POSTGRES_UP_TEMPLATE
+        .replace("{id_default}", id_default)
+        .replace("{id}", id)
+}
+
 /// PostgreSQL schema - DOWN migration
 pub const POSTGRES_DOWN: &str = r#"
 -- Drop indexes first
@@ -127,6 +146,11 @@ CREATE TABLE IF NOT EXISTS sessions (
 );
 
 -- Verification table: Tokens for password reset, magic links, etc.
+-- expires_at is the issue time plus the token type's lifetime. Suggested defaults
+-- (authkit_cli::tokens::default_token_ttl_seconds):
+--   password_reset: 3600 seconds (1 hour)
+--   email_verify: 86400 seconds (24 hours)
+--   magic_link: 900 seconds (15 minutes)
 CREATE TABLE IF NOT EXISTS verification (
     id TEXT PRIMARY KEY,
     user_id TEXT REFERENCES users(id) ON DELETE CASCADE,
@@ -176,10 +200,34 @@ mod tests {
 
     #[test]
     fn test_postgres_up_contains_all_tables() {
-        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS users"));
-        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS accounts"));
-        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS sessions"));
-        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS verification"));
+        let up = postgres_up(IdType::Text);
+        assert!(up.contains("CREATE TABLE IF NOT EXISTS users"));
+        assert!(up.contains("CREATE TABLE IF NOT EXISTS accounts"));
+        assert!(up.contains("CREATE TABLE IF NOT EXISTS sessions"));
+        assert!(up.contains("CREATE TABLE IF NOT EXISTS verification"));
+    }
+
+    #[test]
+    fn test_postgres_up_text_ids() {
+        let up = postgres_up(IdType::Text);
+        assert!(up.contains("    id TEXT PRIMARY KEY,"));
+        assert!(up.contains("user_id TEXT NOT NULL REFERENCES users(id)"));
+        assert!(!up.contains("UUID"));
+        assert!(!up.contains('{'));
+    }
+
+    #[test]
+    fn test_postgres_up_uuid_ids() {
+        let up = postgres_up(IdType::Uuid);
+        assert_eq!(
+            up.matches("id UUID PRIMARY KEY DEFAULT gen_random_uuid(),")
+                .count(),
+            4
+        );
+        assert!(up.contains("user_id UUID NOT NULL REFERENCES users(id)"));
+        assert!(up.contains("user_id UUID REFERENCES users(id)"));
+        assert!(!up.contains("id TEXT PRIMARY KEY"));
+        assert!(!up.contains("user_id TEXT"));
     }
 
     #[test]
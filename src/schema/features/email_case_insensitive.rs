@@ -0,0 +1,53 @@
+//! Case-insensitive email feature schema
+//!
+//! This feature enforces email uniqueness regardless of case and surrounding whitespace by:
+//! - Adding a unique index on the normalized (lower-cased, trimmed) email
+//!
+//! Existing rows that differ only by case or whitespace make the index creation fail,
+//! so `migrate` checks for them before applying this feature.
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Case-Insensitive Email Feature
+-- Enforces unique emails regardless of case and surrounding whitespace
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_normalized ON users (LOWER(TRIM(email)));
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+DROP INDEX IF EXISTS idx_users_email_normalized;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Case-Insensitive Email Feature
+-- Enforces unique emails regardless of case and surrounding whitespace
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_normalized ON users (LOWER(TRIM(email)));
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+DROP INDEX IF EXISTS idx_users_email_normalized;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_up_creates_normalized_unique_index() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_normalized"));
+            assert!(up.contains("LOWER(TRIM(email))"));
+        }
+    }
+
+    #[test]
+    fn test_down_drops_index() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            assert!(down.contains("DROP INDEX IF EXISTS idx_users_email_normalized"));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Login tracking feature schema
+//!
+//! This feature records when each user last signed in by:
+//! - Adding a nullable last_login_at column to users table
+//! - Indexing it for "inactive since" queries
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Login Tracking Feature
+-- Adds last login timestamps to users table
+
+-- Add last login column to users table (NULL until the next sign-in)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_login_at BIGINT;
+
+-- Create index for inactivity queries
+CREATE INDEX IF NOT EXISTS idx_users_last_login_at ON users(last_login_at);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Remove login tracking feature
+
+-- Drop index first
+DROP INDEX IF EXISTS idx_users_last_login_at;
+
+-- Remove last login column from users table
+ALTER TABLE users DROP COLUMN IF EXISTS last_login_at;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Login Tracking Feature
+-- Adds last login timestamps to users table
+
+-- Add last login column to users table (NULL until the next sign-in)
+ALTER TABLE users ADD COLUMN last_login_at INTEGER;
+
+-- Create index for inactivity queries
+CREATE INDEX IF NOT EXISTS idx_users_last_login_at ON users(last_login_at);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Remove login tracking feature
+-- Note: SQLite doesn't support DROP COLUMN in older versions
+-- This requires table recreation for full compatibility
+
+-- Drop the index (an indexed column cannot be dropped)
+DROP INDEX IF EXISTS idx_users_last_login_at;
+
+-- For SQLite 3.35.0+, we can drop columns directly
+ALTER TABLE users DROP COLUMN last_login_at;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_up_adds_column_and_index() {
+        assert!(POSTGRES_UP.contains("ADD COLUMN IF NOT EXISTS last_login_at BIGINT"));
+        assert!(SQLITE_UP.contains("ADD COLUMN last_login_at INTEGER"));
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("CREATE INDEX IF NOT EXISTS idx_users_last_login_at"));
+        }
+    }
+
+    #[test]
+    fn test_down_removes_column_and_index() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let index = down
+                .find("DROP INDEX IF EXISTS idx_users_last_login_at")
+                .unwrap();
+            let column = down.find("ALTER TABLE users DROP COLUMN").unwrap();
+            assert!(index < column);
+            assert!(down.contains("last_login_at;"));
+        }
+    }
+}
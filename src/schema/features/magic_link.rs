@@ -0,0 +1,117 @@
+//! Magic link feature schema
+//!
+//! This feature adds passwordless email sign-in by:
+//! - Creating magic_links: Hashed single-use login tokens sent by email,
+//!   marked with consumed_at once used
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Magic Link Feature
+-- Adds single-use email login links
+
+-- Magic links table: one row per link sent, consumed at most once
+CREATE TABLE IF NOT EXISTS magic_links (
+    id TEXT PRIMARY KEY,
+    email TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    consumed_at BIGINT,
+    created_at BIGINT NOT NULL,
+    ip_address TEXT
+);
+
+-- Each token can only be issued once
+CREATE UNIQUE INDEX IF NOT EXISTS idx_magic_links_token_hash ON magic_links(token_hash);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_magic_links_email ON magic_links(email);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Remove magic link feature
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_magic_links_email;
+DROP INDEX IF EXISTS idx_magic_links_token_hash;
+
+-- Drop the links table
+DROP TABLE IF EXISTS magic_links;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Magic Link Feature
+-- Adds single-use email login links
+
+-- Magic links table: one row per link sent, consumed at most once
+CREATE TABLE IF NOT EXISTS magic_links (
+    id TEXT PRIMARY KEY,
+    email TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    consumed_at INTEGER,
+    created_at INTEGER NOT NULL,
+    ip_address TEXT
+);
+
+-- Each token can only be issued once
+CREATE UNIQUE INDEX IF NOT EXISTS idx_magic_links_token_hash ON magic_links(token_hash);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_magic_links_email ON magic_links(email);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Remove magic link feature
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_magic_links_email;
+DROP INDEX IF EXISTS idx_magic_links_token_hash;
+
+-- Drop the links table
+DROP TABLE IF EXISTS magic_links;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_creates_table() {
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS magic_links"));
+        assert!(POSTGRES_UP.contains("expires_at BIGINT NOT NULL"));
+        assert!(POSTGRES_UP.contains("consumed_at BIGINT,"));
+    }
+
+    #[test]
+    fn test_sqlite_up_creates_table() {
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS magic_links"));
+        assert!(SQLITE_UP.contains("expires_at INTEGER NOT NULL"));
+        assert!(SQLITE_UP.contains("consumed_at INTEGER,"));
+    }
+
+    #[test]
+    fn test_up_indexes() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_magic_links_token_hash ON magic_links(token_hash)"
+            ));
+            assert!(up.contains("idx_magic_links_email ON magic_links(email)"));
+            assert!(up.contains("ip_address TEXT"));
+        }
+    }
+
+    #[test]
+    fn test_down_drops_indexes_and_table() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let index = down
+                .find("DROP INDEX IF EXISTS idx_magic_links_token_hash")
+                .unwrap();
+            let table = down.find("DROP TABLE IF EXISTS magic_links").unwrap();
+            assert!(index < table);
+            assert!(down.contains("DROP INDEX IF EXISTS idx_magic_links_email"));
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Memberships feature schema
+//!
+//! This feature builds on organizations by:
+//! - Adding a nullable slug to organizations, unique once set
+//! - Creating memberships: one row per user and organization, with a role
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Memberships Feature
+-- Adds organization slugs and a memberships table
+
+-- Add slug column to organizations (nullable so existing organizations stay valid)
+ALTER TABLE organizations ADD COLUMN IF NOT EXISTS slug TEXT;
+
+-- Enforce unique slugs (NULLs are not considered equal)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_organizations_slug ON organizations(slug);
+
+-- Memberships table: Links users to organizations, at most once per pair
+CREATE TABLE IF NOT EXISTS memberships (
+    id TEXT PRIMARY KEY,
+    org_id TEXT NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member',
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL,
+    UNIQUE(org_id, user_id)
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_memberships_org_id ON memberships(org_id);
+CREATE INDEX IF NOT EXISTS idx_memberships_user_id ON memberships(user_id);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_memberships_user_id;
+DROP INDEX IF EXISTS idx_memberships_org_id;
+
+-- Drop memberships table
+DROP TABLE IF EXISTS memberships;
+
+-- Remove slug from organizations
+DROP INDEX IF EXISTS idx_organizations_slug;
+ALTER TABLE organizations DROP COLUMN IF EXISTS slug;
+"#;
+
+/// SQLite schema - UP migration
+/// Note: SQLite cannot add a UNIQUE column with ALTER TABLE, so uniqueness comes from the index
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Memberships Feature
+-- Adds organization slugs and a memberships table
+
+-- SQLite: Add slug column (ADD COLUMN does not allow UNIQUE constraints)
+ALTER TABLE organizations ADD COLUMN slug TEXT;
+
+-- Enforce unique slugs (NULLs are not considered equal)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_organizations_slug ON organizations(slug);
+
+-- Memberships table: Links users to organizations, at most once per pair
+CREATE TABLE IF NOT EXISTS memberships (
+    id TEXT PRIMARY KEY,
+    org_id TEXT NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    UNIQUE(org_id, user_id)
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_memberships_org_id ON memberships(org_id);
+CREATE INDEX IF NOT EXISTS idx_memberships_user_id ON memberships(user_id);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_memberships_user_id;
+DROP INDEX IF EXISTS idx_memberships_org_id;
+
+-- Drop memberships table
+DROP TABLE IF EXISTS memberships;
+
+-- Remove slug from organizations (an indexed column cannot be dropped)
+-- For SQLite 3.35.0+, we can drop columns directly
+DROP INDEX IF EXISTS idx_organizations_slug;
+ALTER TABLE organizations DROP COLUMN slug;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_up_migrations_create_memberships() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("CREATE TABLE IF NOT EXISTS memberships"));
+            assert!(up.contains("org_id TEXT NOT NULL REFERENCES organizations(id)"));
+            assert!(up.contains("user_id TEXT NOT NULL REFERENCES users(id)"));
+            assert!(up.contains("UNIQUE(org_id, user_id)"));
+            assert!(up.contains("ON memberships(org_id)"));
+            assert!(up.contains("ON memberships(user_id)"));
+            assert!(up.contains(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_organizations_slug ON organizations(slug)"
+            ));
+        }
+        assert!(POSTGRES_UP.contains("ALTER TABLE organizations ADD COLUMN IF NOT EXISTS slug"));
+        assert!(SQLITE_UP.contains("ALTER TABLE organizations ADD COLUMN slug"));
+    }
+
+    #[test]
+    fn test_down_migrations_drop_memberships_and_slug() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let table = down.find("DROP TABLE IF EXISTS memberships").unwrap();
+            let slug = down
+                .find("DROP INDEX IF EXISTS idx_organizations_slug")
+                .unwrap();
+            let column = down.find("ALTER TABLE organizations DROP COLUMN").unwrap();
+            assert!(table < slug && slug < column);
+        }
+    }
+}
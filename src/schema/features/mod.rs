@@ -2,5 +2,21 @@
 //!
 //! Each feature module contains the up/down SQL for both PostgreSQL and SQLite.
 
+pub mod account_lockout;
+pub mod api_keys;
+pub mod audit_log;
 pub mod base;
+pub mod email_case_insensitive;
 pub mod email_verification;
+pub mod login_tracking;
+pub mod magic_link;
+pub mod memberships;
+pub mod oauth;
+pub mod organizations;
+pub mod phone;
+pub mod rate_limit;
+pub mod refresh_tokens;
+pub mod soft_delete;
+pub mod two_factor;
+pub mod username;
+pub mod webauthn;
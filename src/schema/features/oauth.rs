@@ -0,0 +1,100 @@
+//! OAuth feature schema
+//!
+//! This feature adds OAuth provider support by:
+//! - Adding token columns (access_token, refresh_token, expires_at, scope, id_token) to accounts
+//! - Indexing provider account lookups used during OAuth sign-in
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit OAuth Feature
+-- Adds OAuth provider tokens to accounts table
+
+-- Add OAuth token columns to accounts table
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS access_token TEXT;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS refresh_token TEXT;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS expires_at BIGINT;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS scope TEXT;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS id_token TEXT;
+
+-- Create index for provider account lookups during OAuth sign-in
+CREATE INDEX IF NOT EXISTS idx_accounts_oauth_provider ON accounts(provider, provider_account_id);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Remove OAuth feature
+
+-- Drop index first
+DROP INDEX IF EXISTS idx_accounts_oauth_provider;
+
+-- Remove OAuth token columns from accounts table
+ALTER TABLE accounts DROP COLUMN IF EXISTS id_token;
+ALTER TABLE accounts DROP COLUMN IF EXISTS scope;
+ALTER TABLE accounts DROP COLUMN IF EXISTS expires_at;
+ALTER TABLE accounts DROP COLUMN IF EXISTS refresh_token;
+ALTER TABLE accounts DROP COLUMN IF EXISTS access_token;
+"#;
+
+/// SQLite schema - UP migration
+/// Note: SQLite has limited ALTER TABLE support, so we use a different approach
+pub const SQLITE_UP: &str = r#"
+-- AuthKit OAuth Feature
+-- Adds OAuth provider tokens to accounts table
+
+-- SQLite: Add OAuth token columns
+-- Note: SQLite 3.35.0+ supports ADD COLUMN, older versions need table recreation
+ALTER TABLE accounts ADD COLUMN access_token TEXT;
+ALTER TABLE accounts ADD COLUMN refresh_token TEXT;
+ALTER TABLE accounts ADD COLUMN expires_at INTEGER;
+ALTER TABLE accounts ADD COLUMN scope TEXT;
+ALTER TABLE accounts ADD COLUMN id_token TEXT;
+
+-- Create index for provider account lookups during OAuth sign-in
+CREATE INDEX IF NOT EXISTS idx_accounts_oauth_provider ON accounts(provider, provider_account_id);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Remove OAuth feature
+-- Note: SQLite doesn't support DROP COLUMN in older versions
+-- This requires table recreation for full compatibility
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_accounts_oauth_provider;
+
+-- For SQLite 3.35.0+, we can drop columns directly
+-- For older versions, a table recreation would be needed
+ALTER TABLE accounts DROP COLUMN id_token;
+ALTER TABLE accounts DROP COLUMN scope;
+ALTER TABLE accounts DROP COLUMN expires_at;
+ALTER TABLE accounts DROP COLUMN refresh_token;
+ALTER TABLE accounts DROP COLUMN access_token;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_adds_columns() {
+        assert!(POSTGRES_UP.contains("ALTER TABLE accounts ADD COLUMN"));
+        assert!(POSTGRES_UP.contains("access_token"));
+        assert!(POSTGRES_UP.contains("refresh_token"));
+        assert!(POSTGRES_UP.contains("id_token"));
+        assert!(POSTGRES_UP.contains("ON accounts(provider, provider_account_id)"));
+    }
+
+    #[test]
+    fn test_postgres_down_removes_columns() {
+        assert!(POSTGRES_DOWN.contains("ALTER TABLE accounts DROP COLUMN"));
+        assert!(POSTGRES_DOWN.contains("access_token"));
+        assert!(POSTGRES_DOWN.contains("DROP INDEX IF EXISTS idx_accounts_oauth_provider"));
+    }
+
+    #[test]
+    fn test_sqlite_up_adds_columns() {
+        assert!(SQLITE_UP.contains("ALTER TABLE accounts ADD COLUMN"));
+        assert!(SQLITE_UP.contains("scope"));
+        assert!(SQLITE_UP.contains("expires_at INTEGER"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Organizations feature schema
+//!
+//! This feature adds multi-tenant support by creating:
+//! - organizations: Tenants that users can belong to
+//! - organization_members: Membership join table with a per-member role
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Organizations Feature
+-- Adds organizations and membership tables
+
+-- Organizations table: Tenants
+CREATE TABLE IF NOT EXISTS organizations (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL
+);
+
+-- Organization members table: Links users to organizations
+CREATE TABLE IF NOT EXISTS organization_members (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member',
+    created_at BIGINT NOT NULL
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_organization_members_organization_id ON organization_members(organization_id);
+CREATE INDEX IF NOT EXISTS idx_organization_members_user_id ON organization_members(user_id);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_organization_members_user_id;
+DROP INDEX IF EXISTS idx_organization_members_organization_id;
+
+-- Drop tables in reverse order (respecting foreign keys)
+DROP TABLE IF EXISTS organization_members;
+DROP TABLE IF EXISTS organizations;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Organizations Feature
+-- Adds organizations and membership tables
+
+-- Organizations table: Tenants
+CREATE TABLE IF NOT EXISTS organizations (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+-- Organization members table: Links users to organizations
+CREATE TABLE IF NOT EXISTS organization_members (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member',
+    created_at INTEGER NOT NULL
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_organization_members_organization_id ON organization_members(organization_id);
+CREATE INDEX IF NOT EXISTS idx_organization_members_user_id ON organization_members(user_id);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_organization_members_user_id;
+DROP INDEX IF EXISTS idx_organization_members_organization_id;
+
+-- Drop tables in reverse order (respecting foreign keys)
+DROP TABLE IF EXISTS organization_members;
+DROP TABLE IF EXISTS organizations;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_contains_all_tables() {
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS organizations"));
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS organization_members"));
+        assert!(POSTGRES_UP.contains("REFERENCES organizations(id)"));
+        assert!(POSTGRES_UP.contains("REFERENCES users(id)"));
+    }
+
+    #[test]
+    fn test_sqlite_up_contains_all_tables() {
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS organizations"));
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS organization_members"));
+        assert!(SQLITE_UP.contains("REFERENCES organizations(id)"));
+    }
+
+    #[test]
+    fn test_down_migrations_drop_members_first() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let members = down
+                .find("DROP TABLE IF EXISTS organization_members")
+                .unwrap();
+            let organizations = down.find("DROP TABLE IF EXISTS organizations").unwrap();
+            assert!(members < organizations);
+        }
+    }
+}
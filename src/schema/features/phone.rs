@@ -0,0 +1,152 @@
+//! Phone feature schema
+//!
+//! This feature adds SMS one-time-code sign-in by:
+//! - Adding a nullable, unique phone_number and a phone_verified flag to users
+//! - Creating phone_verification: Hashed SMS codes with an attempt counter
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Phone Feature
+-- Adds phone numbers to users and SMS verification codes
+
+-- Add phone columns to users table (nullable so existing users stay valid)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS phone_number TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS phone_verified BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Enforce unique phone numbers (NULLs are not considered equal)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_phone_number ON users(phone_number);
+
+-- Phone verification table: SMS codes, like verification but for phone numbers
+CREATE TABLE IF NOT EXISTS phone_verification (
+    id TEXT PRIMARY KEY,
+    user_id TEXT REFERENCES users(id) ON DELETE CASCADE,
+    phone_number TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    expires_at BIGINT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at BIGINT NOT NULL
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_phone_verification_user_id ON phone_verification(user_id);
+CREATE INDEX IF NOT EXISTS idx_phone_verification_phone_number ON phone_verification(phone_number);
+CREATE INDEX IF NOT EXISTS idx_phone_verification_expires_at ON phone_verification(expires_at);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Remove phone feature
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_phone_verification_expires_at;
+DROP INDEX IF EXISTS idx_phone_verification_phone_number;
+DROP INDEX IF EXISTS idx_phone_verification_user_id;
+DROP INDEX IF EXISTS idx_users_phone_number;
+
+-- Drop the codes table
+DROP TABLE IF EXISTS phone_verification;
+
+-- Remove phone columns from users table
+ALTER TABLE users DROP COLUMN IF EXISTS phone_verified;
+ALTER TABLE users DROP COLUMN IF EXISTS phone_number;
+"#;
+
+/// SQLite schema - UP migration
+/// Note: SQLite cannot add a UNIQUE column with ALTER TABLE, so uniqueness comes from the index
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Phone Feature
+-- Adds phone numbers to users and SMS verification codes
+
+-- SQLite: Add phone columns (ADD COLUMN does not allow UNIQUE constraints)
+ALTER TABLE users ADD COLUMN phone_number TEXT;
+ALTER TABLE users ADD COLUMN phone_verified INTEGER NOT NULL DEFAULT 0;
+
+-- Enforce unique phone numbers (NULLs are not considered equal)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_phone_number ON users(phone_number);
+
+-- Phone verification table: SMS codes, like verification but for phone numbers
+CREATE TABLE IF NOT EXISTS phone_verification (
+    id TEXT PRIMARY KEY,
+    user_id TEXT REFERENCES users(id) ON DELETE CASCADE,
+    phone_number TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_phone_verification_user_id ON phone_verification(user_id);
+CREATE INDEX IF NOT EXISTS idx_phone_verification_phone_number ON phone_verification(phone_number);
+CREATE INDEX IF NOT EXISTS idx_phone_verification_expires_at ON phone_verification(expires_at);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Remove phone feature
+-- Note: SQLite doesn't support DROP COLUMN in older versions
+-- This requires table recreation for full compatibility
+
+-- Drop indexes first (an indexed column cannot be dropped)
+DROP INDEX IF EXISTS idx_phone_verification_expires_at;
+DROP INDEX IF EXISTS idx_phone_verification_phone_number;
+DROP INDEX IF EXISTS idx_phone_verification_user_id;
+DROP INDEX IF EXISTS idx_users_phone_number;
+
+-- Drop the codes table
+DROP TABLE IF EXISTS phone_verification;
+
+-- For SQLite 3.35.0+, we can drop columns directly
+ALTER TABLE users DROP COLUMN phone_verified;
+ALTER TABLE users DROP COLUMN phone_number;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_adds_columns_and_table() {
+        assert!(POSTGRES_UP.contains("ADD COLUMN IF NOT EXISTS phone_number TEXT;"));
+        assert!(POSTGRES_UP.contains("phone_verified BOOLEAN NOT NULL DEFAULT FALSE"));
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS phone_verification"));
+        assert!(POSTGRES_UP.contains("expires_at BIGINT NOT NULL"));
+    }
+
+    #[test]
+    fn test_sqlite_up_adds_columns_and_table() {
+        assert!(SQLITE_UP.contains("ALTER TABLE users ADD COLUMN phone_number TEXT;"));
+        assert!(SQLITE_UP.contains("phone_verified INTEGER NOT NULL DEFAULT 0"));
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS phone_verification"));
+        assert!(SQLITE_UP.contains("expires_at INTEGER NOT NULL"));
+    }
+
+    #[test]
+    fn test_up_indexes() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("CREATE UNIQUE INDEX IF NOT EXISTS idx_users_phone_number"));
+            assert!(up.contains("idx_phone_verification_user_id"));
+            assert!(up.contains("idx_phone_verification_phone_number"));
+            assert!(up.contains("idx_phone_verification_expires_at"));
+            assert!(up.contains("attempts INTEGER NOT NULL DEFAULT 0"));
+        }
+    }
+
+    #[test]
+    fn test_down_drops_indexes_before_columns() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let index = down
+                .find("DROP INDEX IF EXISTS idx_users_phone_number")
+                .unwrap();
+            let table = down
+                .find("DROP TABLE IF EXISTS phone_verification")
+                .unwrap();
+            let column = down.find("ALTER TABLE users DROP COLUMN").unwrap();
+            assert!(index < table && table < column);
+            assert!(
+                down.contains("DROP COLUMN IF EXISTS phone_number")
+                    || down.contains("DROP COLUMN phone_number")
+            );
+        }
+    }
+}
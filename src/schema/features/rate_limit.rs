@@ -0,0 +1,97 @@
+//! Rate limit feature schema
+//!
+//! This feature adds storage for server-side rate limiting by creating:
+//! - rate_limits: One counter per key (e.g. `ip:route` or `user:action`) and time window
+//!
+//! Rows past `expires_at` are removed by `authkit clean-expired`.
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Rate Limit Feature
+-- Adds counters for server-side rate limiting
+
+-- Rate limits table: Request counts per key and window
+CREATE TABLE IF NOT EXISTS rate_limits (
+    id TEXT PRIMARY KEY,
+    key TEXT NOT NULL,
+    count INTEGER NOT NULL DEFAULT 0,
+    window_start BIGINT NOT NULL,
+    expires_at BIGINT NOT NULL
+);
+
+-- Indexes for key lookups and expired row cleanup
+CREATE UNIQUE INDEX IF NOT EXISTS idx_rate_limits_key ON rate_limits(key);
+CREATE INDEX IF NOT EXISTS idx_rate_limits_expires_at ON rate_limits(expires_at);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_rate_limits_expires_at;
+DROP INDEX IF EXISTS idx_rate_limits_key;
+
+-- Drop table
+DROP TABLE IF EXISTS rate_limits;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Rate Limit Feature
+-- Adds counters for server-side rate limiting
+
+-- Rate limits table: Request counts per key and window
+CREATE TABLE IF NOT EXISTS rate_limits (
+    id TEXT PRIMARY KEY,
+    key TEXT NOT NULL,
+    count INTEGER NOT NULL DEFAULT 0,
+    window_start INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
+-- Indexes for key lookups and expired row cleanup
+CREATE UNIQUE INDEX IF NOT EXISTS idx_rate_limits_key ON rate_limits(key);
+CREATE INDEX IF NOT EXISTS idx_rate_limits_expires_at ON rate_limits(expires_at);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_rate_limits_expires_at;
+DROP INDEX IF EXISTS idx_rate_limits_key;
+
+-- Drop table
+DROP TABLE IF EXISTS rate_limits;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_creates_rate_limits_table() {
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS rate_limits"));
+        assert!(POSTGRES_UP.contains("window_start BIGINT NOT NULL"));
+        assert!(POSTGRES_UP.contains("expires_at BIGINT NOT NULL"));
+    }
+
+    #[test]
+    fn test_sqlite_up_creates_rate_limits_table() {
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS rate_limits"));
+        assert!(SQLITE_UP.contains("expires_at INTEGER NOT NULL"));
+    }
+
+    #[test]
+    fn test_indexes_and_down_migrations() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("CREATE UNIQUE INDEX IF NOT EXISTS idx_rate_limits_key"));
+            assert!(up.contains("ON rate_limits(expires_at)"));
+        }
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let index = down
+                .find("DROP INDEX IF EXISTS idx_rate_limits_expires_at")
+                .unwrap();
+            let table = down.find("DROP TABLE IF EXISTS rate_limits").unwrap();
+            assert!(index < table);
+        }
+    }
+}
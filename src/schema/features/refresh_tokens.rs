@@ -0,0 +1,125 @@
+//! Refresh tokens feature schema
+//!
+//! This feature adds refresh token rotation by:
+//! - Creating refresh_tokens: Hashed refresh tokens grouped into families,
+//!   each rotated token pointing at the token it replaced (parent_id)
+//!
+//! Presenting a token whose family already has a newer token signals reuse,
+//! and the whole family can be revoked.
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Refresh Tokens Feature
+-- Adds refresh token rotation with token families
+
+-- Refresh tokens table: one row per issued token, rotated tokens keep their parent
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    parent_id TEXT REFERENCES refresh_tokens(id) ON DELETE SET NULL,
+    revoked_at BIGINT,
+    expires_at BIGINT NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family_id ON refresh_tokens(family_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_token_hash ON refresh_tokens(token_hash);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Remove refresh tokens feature
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_refresh_tokens_token_hash;
+DROP INDEX IF EXISTS idx_refresh_tokens_family_id;
+DROP INDEX IF EXISTS idx_refresh_tokens_user_id;
+
+-- Drop the tokens table
+DROP TABLE IF EXISTS refresh_tokens;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Refresh Tokens Feature
+-- Adds refresh token rotation with token families
+
+-- Refresh tokens table: one row per issued token, rotated tokens keep their parent
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    parent_id TEXT REFERENCES refresh_tokens(id) ON DELETE SET NULL,
+    revoked_at INTEGER,
+    expires_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family_id ON refresh_tokens(family_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_token_hash ON refresh_tokens(token_hash);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Remove refresh tokens feature
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_refresh_tokens_token_hash;
+DROP INDEX IF EXISTS idx_refresh_tokens_family_id;
+DROP INDEX IF EXISTS idx_refresh_tokens_user_id;
+
+-- Drop the tokens table
+DROP TABLE IF EXISTS refresh_tokens;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_creates_table() {
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS refresh_tokens"));
+        assert!(POSTGRES_UP.contains("expires_at BIGINT NOT NULL"));
+        assert!(POSTGRES_UP.contains("revoked_at BIGINT,"));
+    }
+
+    #[test]
+    fn test_sqlite_up_creates_table() {
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS refresh_tokens"));
+        assert!(SQLITE_UP.contains("expires_at INTEGER NOT NULL"));
+        assert!(SQLITE_UP.contains("revoked_at INTEGER,"));
+    }
+
+    #[test]
+    fn test_up_columns_and_indexes() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE"));
+            assert!(up.contains("family_id TEXT NOT NULL"));
+            assert!(up.contains("token_hash TEXT NOT NULL UNIQUE"));
+            assert!(up.contains("parent_id TEXT REFERENCES refresh_tokens(id)"));
+            assert!(up.contains("idx_refresh_tokens_user_id ON refresh_tokens(user_id)"));
+            assert!(up.contains("idx_refresh_tokens_family_id ON refresh_tokens(family_id)"));
+            assert!(up.contains("idx_refresh_tokens_token_hash ON refresh_tokens(token_hash)"));
+        }
+    }
+
+    #[test]
+    fn test_down_drops_indexes_before_table() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let index = down
+                .find("DROP INDEX IF EXISTS idx_refresh_tokens_user_id")
+                .unwrap();
+            let table = down.find("DROP TABLE IF EXISTS refresh_tokens").unwrap();
+            assert!(index < table);
+            assert!(down.contains("DROP INDEX IF EXISTS idx_refresh_tokens_family_id"));
+            assert!(down.contains("DROP INDEX IF EXISTS idx_refresh_tokens_token_hash"));
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Soft delete feature schema
+//!
+//! This feature lets rows be marked deleted instead of removed by:
+//! - Adding a nullable deleted_at column to users, accounts and sessions
+//! - Indexing the rows that are still active
+//!
+//! PostgreSQL gets partial indexes (`WHERE deleted_at IS NULL`) on the columns
+//! used for lookups; SQLite gets plain indexes on deleted_at instead, since
+//! partial index support depends on the SQLite version.
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Soft Delete Feature
+-- Adds deleted_at markers to users, accounts and sessions
+
+-- Add soft delete columns (NULL while the row is active)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
+
+-- Partial indexes for active-row lookups
+CREATE INDEX IF NOT EXISTS idx_users_active_email ON users(email) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_accounts_active_user_id ON accounts(user_id) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_sessions_active_token ON sessions(token) WHERE deleted_at IS NULL;
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Remove soft delete feature
+
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_sessions_active_token;
+DROP INDEX IF EXISTS idx_accounts_active_user_id;
+DROP INDEX IF EXISTS idx_users_active_email;
+
+-- Remove soft delete columns in reverse order
+ALTER TABLE sessions DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE accounts DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE users DROP COLUMN IF EXISTS deleted_at;
+"#;
+
+/// SQLite schema - UP migration
+/// Note: SQLite has limited ALTER TABLE support, so we use a different approach
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Soft Delete Feature
+-- Adds deleted_at markers to users, accounts and sessions
+
+-- SQLite: Add soft delete columns (NULL while the row is active)
+-- Note: SQLite 3.35.0+ supports ADD COLUMN, older versions need table recreation
+ALTER TABLE users ADD COLUMN deleted_at INTEGER;
+ALTER TABLE accounts ADD COLUMN deleted_at INTEGER;
+ALTER TABLE sessions ADD COLUMN deleted_at INTEGER;
+
+-- Indexes for filtering out deleted rows
+CREATE INDEX IF NOT EXISTS idx_users_deleted_at ON users(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_accounts_deleted_at ON accounts(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_sessions_deleted_at ON sessions(deleted_at);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Remove soft delete feature
+-- Note: SQLite doesn't support DROP COLUMN in older versions
+-- This requires table recreation for full compatibility
+
+-- Drop the indexes (an indexed column cannot be dropped)
+DROP INDEX IF EXISTS idx_sessions_deleted_at;
+DROP INDEX IF EXISTS idx_accounts_deleted_at;
+DROP INDEX IF EXISTS idx_users_deleted_at;
+
+-- For SQLite 3.35.0+, we can drop columns directly
+ALTER TABLE sessions DROP COLUMN deleted_at;
+ALTER TABLE accounts DROP COLUMN deleted_at;
+ALTER TABLE users DROP COLUMN deleted_at;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLES: [&str; 3] = ["users", "accounts", "sessions"];
+
+    #[test]
+    fn test_up_adds_column_to_all_tables() {
+        for table in TABLES {
+            assert!(POSTGRES_UP.contains(&format!(
+                "ALTER TABLE {} ADD COLUMN IF NOT EXISTS deleted_at BIGINT",
+                table
+            )));
+            assert!(SQLITE_UP.contains(&format!(
+                "ALTER TABLE {} ADD COLUMN deleted_at INTEGER",
+                table
+            )));
+        }
+    }
+
+    #[test]
+    fn test_postgres_indexes_are_partial() {
+        assert_eq!(POSTGRES_UP.matches("WHERE deleted_at IS NULL").count(), 3);
+        assert!(!SQLITE_UP.contains("WHERE"));
+    }
+
+    #[test]
+    fn test_down_drops_indexes_before_columns() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let last_index = down.rfind("DROP INDEX").unwrap();
+            let first_column = down.find("ALTER TABLE sessions DROP COLUMN").unwrap();
+            assert!(last_index < first_column);
+            for table in TABLES {
+                assert!(down.contains(&format!("ALTER TABLE {} DROP COLUMN", table)));
+            }
+        }
+    }
+}
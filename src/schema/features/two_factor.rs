@@ -0,0 +1,113 @@
+//! Two-factor authentication feature schema
+//!
+//! This feature adds TOTP-based 2FA support by creating:
+//! - two_factor: TOTP secret and enrollment state per user
+//! - backup_codes: One-time recovery codes
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Two-Factor Authentication Feature
+-- Adds TOTP secrets and backup codes
+
+-- Two-factor table: TOTP secret and enrollment state
+CREATE TABLE IF NOT EXISTS two_factor (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    verified_at BIGINT,
+    created_at BIGINT NOT NULL
+);
+
+-- Backup codes table: One-time recovery codes
+CREATE TABLE IF NOT EXISTS backup_codes (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    used_at BIGINT,
+    created_at BIGINT NOT NULL
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_two_factor_user_id ON two_factor(user_id);
+CREATE INDEX IF NOT EXISTS idx_backup_codes_user_id ON backup_codes(user_id);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_backup_codes_user_id;
+DROP INDEX IF EXISTS idx_two_factor_user_id;
+
+-- Drop tables in reverse order (respecting foreign keys)
+DROP TABLE IF EXISTS backup_codes;
+DROP TABLE IF EXISTS two_factor;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Two-Factor Authentication Feature
+-- Adds TOTP secrets and backup codes
+
+-- Two-factor table: TOTP secret and enrollment state
+CREATE TABLE IF NOT EXISTS two_factor (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 0,
+    verified_at INTEGER,
+    created_at INTEGER NOT NULL
+);
+
+-- Backup codes table: One-time recovery codes
+CREATE TABLE IF NOT EXISTS backup_codes (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    used_at INTEGER,
+    created_at INTEGER NOT NULL
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_two_factor_user_id ON two_factor(user_id);
+CREATE INDEX IF NOT EXISTS idx_backup_codes_user_id ON backup_codes(user_id);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_backup_codes_user_id;
+DROP INDEX IF EXISTS idx_two_factor_user_id;
+
+-- Drop tables in reverse order (respecting foreign keys)
+DROP TABLE IF EXISTS backup_codes;
+DROP TABLE IF EXISTS two_factor;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_contains_all_tables() {
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS two_factor"));
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS backup_codes"));
+        assert!(POSTGRES_UP.contains("REFERENCES users(id)"));
+    }
+
+    #[test]
+    fn test_sqlite_up_contains_all_tables() {
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS two_factor"));
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS backup_codes"));
+        assert!(SQLITE_UP.contains("enabled INTEGER NOT NULL DEFAULT 0"));
+    }
+
+    #[test]
+    fn test_down_migrations_drop_backup_codes_first() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let backup_codes = down.find("DROP TABLE IF EXISTS backup_codes").unwrap();
+            let two_factor = down.find("DROP TABLE IF EXISTS two_factor").unwrap();
+            assert!(backup_codes < two_factor);
+        }
+    }
+}
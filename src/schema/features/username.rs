@@ -0,0 +1,79 @@
+//! Username feature schema
+//!
+//! This feature adds username-based sign-in by:
+//! - Adding a nullable username column to users table
+//! - Enforcing uniqueness with a unique index (existing users keep NULL until they pick one)
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit Username Feature
+-- Adds a unique username to users table
+
+-- Add username column to users table (nullable so existing users stay valid)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS username TEXT;
+
+-- Enforce unique usernames (NULLs are not considered equal)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username ON users(username);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Remove username feature
+
+-- Drop index first
+DROP INDEX IF EXISTS idx_users_username;
+
+-- Remove username column from users table
+ALTER TABLE users DROP COLUMN IF EXISTS username;
+"#;
+
+/// SQLite schema - UP migration
+/// Note: SQLite cannot add a UNIQUE column with ALTER TABLE, so uniqueness comes from the index
+pub const SQLITE_UP: &str = r#"
+-- AuthKit Username Feature
+-- Adds a unique username to users table
+
+-- SQLite: Add username column (ADD COLUMN does not allow UNIQUE constraints)
+ALTER TABLE users ADD COLUMN username TEXT;
+
+-- Enforce unique usernames (NULLs are not considered equal)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username ON users(username);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Remove username feature
+-- Note: SQLite doesn't support DROP COLUMN in older versions
+-- This requires table recreation for full compatibility
+
+-- Drop the index (an indexed column cannot be dropped)
+DROP INDEX IF EXISTS idx_users_username;
+
+-- For SQLite 3.35.0+, we can drop columns directly
+ALTER TABLE users DROP COLUMN username;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_up_adds_column_and_unique_index() {
+        for up in [POSTGRES_UP, SQLITE_UP] {
+            assert!(up.contains("ALTER TABLE users ADD COLUMN"));
+            assert!(up.contains("username TEXT;"));
+            assert!(up.contains("CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username"));
+        }
+    }
+
+    #[test]
+    fn test_down_drops_index_before_column() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            let index = down
+                .find("DROP INDEX IF EXISTS idx_users_username")
+                .unwrap();
+            let column = down.find("ALTER TABLE users DROP COLUMN").unwrap();
+            assert!(index < column);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! WebAuthn feature schema
+//!
+//! This feature adds passkey support by creating:
+//! - webauthn_credentials: Registered authenticator public keys per user
+
+/// PostgreSQL schema - UP migration
+pub const POSTGRES_UP: &str = r#"
+-- AuthKit WebAuthn Feature
+-- Adds passkey credential storage
+
+-- WebAuthn credentials table: Registered authenticators
+CREATE TABLE IF NOT EXISTS webauthn_credentials (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    credential_id TEXT NOT NULL,
+    public_key BYTEA NOT NULL,
+    counter BIGINT NOT NULL DEFAULT 0,
+    transports TEXT,
+    created_at BIGINT NOT NULL,
+    last_used_at BIGINT
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_webauthn_credentials_user_id ON webauthn_credentials(user_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_webauthn_credentials_credential_id ON webauthn_credentials(credential_id);
+"#;
+
+/// PostgreSQL schema - DOWN migration
+pub const POSTGRES_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_webauthn_credentials_credential_id;
+DROP INDEX IF EXISTS idx_webauthn_credentials_user_id;
+
+-- Drop table
+DROP TABLE IF EXISTS webauthn_credentials;
+"#;
+
+/// SQLite schema - UP migration
+pub const SQLITE_UP: &str = r#"
+-- AuthKit WebAuthn Feature
+-- Adds passkey credential storage
+
+-- WebAuthn credentials table: Registered authenticators
+CREATE TABLE IF NOT EXISTS webauthn_credentials (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    credential_id TEXT NOT NULL,
+    public_key BLOB NOT NULL,
+    counter INTEGER NOT NULL DEFAULT 0,
+    transports TEXT,
+    created_at INTEGER NOT NULL,
+    last_used_at INTEGER
+);
+
+-- Indexes for better query performance
+CREATE INDEX IF NOT EXISTS idx_webauthn_credentials_user_id ON webauthn_credentials(user_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_webauthn_credentials_credential_id ON webauthn_credentials(credential_id);
+"#;
+
+/// SQLite schema - DOWN migration
+pub const SQLITE_DOWN: &str = r#"
+-- Drop indexes first
+DROP INDEX IF EXISTS idx_webauthn_credentials_credential_id;
+DROP INDEX IF EXISTS idx_webauthn_credentials_user_id;
+
+-- Drop table
+DROP TABLE IF EXISTS webauthn_credentials;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_postgres_up_creates_credentials_table() {
+        assert!(POSTGRES_UP.contains("CREATE TABLE IF NOT EXISTS webauthn_credentials"));
+        assert!(POSTGRES_UP.contains("public_key BYTEA NOT NULL"));
+        assert!(POSTGRES_UP
+            .contains("CREATE UNIQUE INDEX IF NOT EXISTS idx_webauthn_credentials_credential_id"));
+    }
+
+    #[test]
+    fn test_sqlite_up_creates_credentials_table() {
+        assert!(SQLITE_UP.contains("CREATE TABLE IF NOT EXISTS webauthn_credentials"));
+        assert!(SQLITE_UP.contains("public_key BLOB NOT NULL"));
+        assert!(SQLITE_UP
+            .contains("CREATE UNIQUE INDEX IF NOT EXISTS idx_webauthn_credentials_credential_id"));
+    }
+
+    #[test]
+    fn test_up_migrations_have_passkey_columns() {
+        for (up, counter) in [
+            (POSTGRES_UP, "counter BIGINT NOT NULL DEFAULT 0"),
+            // SQLite INTEGER columns hold 64-bit values, like BIGINT
+            (SQLITE_UP, "counter INTEGER NOT NULL DEFAULT 0"),
+        ] {
+            assert!(up.contains("user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE"));
+            assert!(up.contains("credential_id TEXT NOT NULL"));
+            assert!(up.contains(counter));
+            assert!(up.contains("transports TEXT"));
+            assert!(up.contains("last_used_at"));
+            assert!(up.contains(
+                "CREATE INDEX IF NOT EXISTS idx_webauthn_credentials_user_id ON webauthn_credentials(user_id)"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_down_migrations_drop_table() {
+        for down in [POSTGRES_DOWN, SQLITE_DOWN] {
+            assert!(down.contains("DROP INDEX IF EXISTS idx_webauthn_credentials_credential_id"));
+            assert!(down.contains("DROP INDEX IF EXISTS idx_webauthn_credentials_user_id"));
+            let index = down.find("DROP INDEX IF EXISTS").unwrap();
+            let table = down
+                .find("DROP TABLE IF EXISTS webauthn_credentials")
+                .unwrap();
+            assert!(index < table);
+        }
+    }
+}
@@ -3,18 +3,37 @@
 //! This module provides SQL schema for each feature, organized by database type.
 //! Migrations are generated per-feature rather than per-table.
 
+pub mod diff;
 pub mod features;
+pub mod objects;
+
+use std::collections::HashMap;
 
 use crate::cli::DatabaseType;
-use crate::config::Feature;
+use crate::config::{check_unique_versions, AuthKitConfig, Feature, IdType};
+use crate::error::{CliError, CliResult};
 use crate::migrations::Migration;
 
-/// Get the migration for a specific feature and database type
+/// Get the migration for a specific feature and database type, with text ids
 pub fn get_feature_migration(feature: Feature, db_type: DatabaseType) -> Migration {
+    get_feature_migration_with(feature, db_type, IdType::Text)
+}
+
+/// Get the migration for a specific feature and database type.
+///
+/// With UUID ids on PostgreSQL the base tables get `UUID` ids, and the other
+/// features' `user_id` columns are typed to match so their foreign keys stay valid.
+pub fn get_feature_migration_with(
+    feature: Feature,
+    db_type: DatabaseType,
+    id_type: IdType,
+) -> Migration {
+    let base_up;
     let (up_sql, down_sql) = match (feature, db_type) {
         // Base (email_password) migrations
         (Feature::EmailPassword, DatabaseType::Postgres) => {
-            (features::base::POSTGRES_UP, features::base::POSTGRES_DOWN)
+            base_up = features::base::postgres_up(id_type);
+            (base_up.as_str(), features::base::POSTGRES_DOWN)
         }
         (Feature::EmailPassword, DatabaseType::Sqlite) => {
             (features::base::SQLITE_UP, features::base::SQLITE_DOWN)
@@ -29,29 +48,357 @@ pub fn get_feature_migration(feature: Feature, db_type: DatabaseType) -> Migrati
             features::email_verification::SQLITE_UP,
             features::email_verification::SQLITE_DOWN,
         ),
+
+        // OAuth migrations
+        (Feature::OAuth, DatabaseType::Postgres) => {
+            (features::oauth::POSTGRES_UP, features::oauth::POSTGRES_DOWN)
+        }
+        (Feature::OAuth, DatabaseType::Sqlite) => {
+            (features::oauth::SQLITE_UP, features::oauth::SQLITE_DOWN)
+        }
+
+        // Two-factor authentication migrations
+        (Feature::TwoFactor, DatabaseType::Postgres) => (
+            features::two_factor::POSTGRES_UP,
+            features::two_factor::POSTGRES_DOWN,
+        ),
+        (Feature::TwoFactor, DatabaseType::Sqlite) => (
+            features::two_factor::SQLITE_UP,
+            features::two_factor::SQLITE_DOWN,
+        ),
+
+        // Case-insensitive email migrations
+        (Feature::EmailCaseInsensitive, DatabaseType::Postgres) => (
+            features::email_case_insensitive::POSTGRES_UP,
+            features::email_case_insensitive::POSTGRES_DOWN,
+        ),
+        (Feature::EmailCaseInsensitive, DatabaseType::Sqlite) => (
+            features::email_case_insensitive::SQLITE_UP,
+            features::email_case_insensitive::SQLITE_DOWN,
+        ),
+
+        // Username migrations
+        (Feature::Username, DatabaseType::Postgres) => (
+            features::username::POSTGRES_UP,
+            features::username::POSTGRES_DOWN,
+        ),
+        (Feature::Username, DatabaseType::Sqlite) => (
+            features::username::SQLITE_UP,
+            features::username::SQLITE_DOWN,
+        ),
+
+        // Audit log migrations
+        (Feature::AuditLog, DatabaseType::Postgres) => (
+            features::audit_log::POSTGRES_UP,
+            features::audit_log::POSTGRES_DOWN,
+        ),
+        (Feature::AuditLog, DatabaseType::Sqlite) => (
+            features::audit_log::SQLITE_UP,
+            features::audit_log::SQLITE_DOWN,
+        ),
+
+        // Account lockout migrations
+        (Feature::AccountLockout, DatabaseType::Postgres) => (
+            features::account_lockout::POSTGRES_UP,
+            features::account_lockout::POSTGRES_DOWN,
+        ),
+        (Feature::AccountLockout, DatabaseType::Sqlite) => (
+            features::account_lockout::SQLITE_UP,
+            features::account_lockout::SQLITE_DOWN,
+        ),
+
+        // API keys migrations
+        (Feature::ApiKeys, DatabaseType::Postgres) => (
+            features::api_keys::POSTGRES_UP,
+            features::api_keys::POSTGRES_DOWN,
+        ),
+        (Feature::ApiKeys, DatabaseType::Sqlite) => (
+            features::api_keys::SQLITE_UP,
+            features::api_keys::SQLITE_DOWN,
+        ),
+
+        // Rate limit migrations
+        (Feature::RateLimit, DatabaseType::Postgres) => (
+            features::rate_limit::POSTGRES_UP,
+            features::rate_limit::POSTGRES_DOWN,
+        ),
+        (Feature::RateLimit, DatabaseType::Sqlite) => (
+            features::rate_limit::SQLITE_UP,
+            features::rate_limit::SQLITE_DOWN,
+        ),
+
+        // WebAuthn migrations
+        (Feature::WebAuthn, DatabaseType::Postgres) => (
+            features::webauthn::POSTGRES_UP,
+            features::webauthn::POSTGRES_DOWN,
+        ),
+        (Feature::WebAuthn, DatabaseType::Sqlite) => (
+            features::webauthn::SQLITE_UP,
+            features::webauthn::SQLITE_DOWN,
+        ),
+
+        // Organizations migrations
+        (Feature::Organizations, DatabaseType::Postgres) => (
+            features::organizations::POSTGRES_UP,
+            features::organizations::POSTGRES_DOWN,
+        ),
+        (Feature::Organizations, DatabaseType::Sqlite) => (
+            features::organizations::SQLITE_UP,
+            features::organizations::SQLITE_DOWN,
+        ),
+
+        // Login tracking migrations
+        (Feature::LoginTracking, DatabaseType::Postgres) => (
+            features::login_tracking::POSTGRES_UP,
+            features::login_tracking::POSTGRES_DOWN,
+        ),
+        (Feature::LoginTracking, DatabaseType::Sqlite) => (
+            features::login_tracking::SQLITE_UP,
+            features::login_tracking::SQLITE_DOWN,
+        ),
+
+        // Soft delete migrations
+        (Feature::SoftDelete, DatabaseType::Postgres) => (
+            features::soft_delete::POSTGRES_UP,
+            features::soft_delete::POSTGRES_DOWN,
+        ),
+        (Feature::SoftDelete, DatabaseType::Sqlite) => (
+            features::soft_delete::SQLITE_UP,
+            features::soft_delete::SQLITE_DOWN,
+        ),
+
+        // Phone migrations
+        (Feature::Phone, DatabaseType::Postgres) => {
+            (features::phone::POSTGRES_UP, features::phone::POSTGRES_DOWN)
+        }
+        (Feature::Phone, DatabaseType::Sqlite) => {
+            (features::phone::SQLITE_UP, features::phone::SQLITE_DOWN)
+        }
+
+        // Refresh token migrations
+        (Feature::RefreshTokens, DatabaseType::Postgres) => (
+            features::refresh_tokens::POSTGRES_UP,
+            features::refresh_tokens::POSTGRES_DOWN,
+        ),
+        (Feature::RefreshTokens, DatabaseType::Sqlite) => (
+            features::refresh_tokens::SQLITE_UP,
+            features::refresh_tokens::SQLITE_DOWN,
+        ),
+
+        // Magic link migrations
+        (Feature::MagicLink, DatabaseType::Postgres) => (
+            features::magic_link::POSTGRES_UP,
+            features::magic_link::POSTGRES_DOWN,
+        ),
+        (Feature::MagicLink, DatabaseType::Sqlite) => (
+            features::magic_link::SQLITE_UP,
+            features::magic_link::SQLITE_DOWN,
+        ),
+
+        // Memberships migrations
+        (Feature::Memberships, DatabaseType::Postgres) => (
+            features::memberships::POSTGRES_UP,
+            features::memberships::POSTGRES_DOWN,
+        ),
+        (Feature::Memberships, DatabaseType::Sqlite) => (
+            features::memberships::SQLITE_UP,
+            features::memberships::SQLITE_DOWN,
+        ),
+    };
+
+    let up_sql = match (db_type, id_type) {
+        (DatabaseType::Postgres, IdType::Uuid) if feature != Feature::EmailPassword => {
+            up_sql.replace("user_id TEXT", "user_id UUID")
+        }
+        _ => up_sql.to_string(),
     };
 
     Migration {
         version: feature.version(),
         name: feature.migration_name().to_string(),
+        checksum: crate::migrations::compute_checksum(&up_sql),
         up_sql,
-        down_sql,
-        checksum: crate::migrations::compute_checksum(up_sql),
+        down_sql: down_sql.to_string(),
+    }
+}
+
+/// Get a feature's migration as a config generates it: ids typed by
+/// `[schema] id_type` and names prefixed by `[database] table_prefix`
+pub fn get_feature_migration_for(
+    config: &AuthKitConfig,
+    feature: Feature,
+    db_type: DatabaseType,
+) -> Migration {
+    let migration = get_feature_migration_with(feature, db_type, config.schema.id_type);
+    let prefix = config.table_prefix();
+    if prefix.is_empty() {
+        return migration;
     }
+
+    let up_sql = prefix_tables(&migration.up_sql, prefix);
+    Migration {
+        checksum: crate::migrations::compute_checksum(&up_sql),
+        up_sql,
+        down_sql: prefix_tables(&migration.down_sql, prefix),
+        ..migration
+    }
+}
+
+/// Prefix every AuthKit table and index name in a feature script, e.g. `users`
+/// becomes `auth_users` and `idx_users_email` becomes `auth_idx_users_email`.
+/// Only whole identifiers match, so columns such as `user_id` are left alone,
+/// and `--` comments and string literals are copied unchanged.
+pub fn prefix_tables(sql: &str, prefix: &str) -> String {
+    let tables: Vec<String> = Feature::all()
+        .iter()
+        .flat_map(|f| feature_tables(*f))
+        .collect();
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let mut out = String::with_capacity(sql.len());
+    let mut rest = sql;
+    while let Some(start) = rest.find(|c: char| is_ident(c) || c == '\'' || c == '-') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = if rest.starts_with("--") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with('-') {
+            1
+        } else if let Some(literal) = rest.strip_prefix('\'') {
+            // '' inside a literal is an escaped quote, which this also skips over
+            literal.find('\'').map_or(rest.len(), |i| i + 2)
+        } else {
+            let end = rest.find(|c: char| !is_ident(c)).unwrap_or(rest.len());
+            let word = &rest[..end];
+            if word.starts_with("idx_") || tables.iter().any(|t| t == word) {
+                out.push_str(&prefixed(word, prefix));
+            } else {
+                out.push_str(word);
+            }
+            rest = &rest[end..];
+            continue;
+        };
+        out.push_str(&rest[..end]);
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A table or index name with `prefix`, unless it already starts with it
+/// (`auth_audit_log` stays `auth_audit_log` under the `auth_` prefix)
+pub fn prefixed(name: &str, prefix: &str) -> String {
+    if name.starts_with(prefix) {
+        name.to_string()
+    } else {
+        format!("{}{}", prefix, name)
+    }
+}
+
+/// Get all migrations for the enabled features, refusing features that share a version
+pub fn get_migrations_for_features(
+    features: &[Feature],
+    db_type: DatabaseType,
+    id_type: IdType,
+) -> CliResult<Vec<Migration>> {
+    let migrations: Vec<Migration> = features
+        .iter()
+        .map(|f| get_feature_migration_with(*f, db_type, id_type))
+        .collect();
+    check_unique_versions(migrations.iter().map(|m| (m.version, m.name.as_str())))?;
+    Ok(migrations)
 }
 
-/// Get all migrations for the enabled features
-pub fn get_migrations_for_features(features: &[Feature], db_type: DatabaseType) -> Vec<Migration> {
-    features
+/// Name of the table that tracks applied migrations
+pub const MIGRATIONS_TABLE: &str = "_authkit_migrations";
+
+/// Name of the table used as a migration lock on SQLite
+pub const MIGRATIONS_LOCK_TABLE: &str = "_authkit_migrations_lock";
+
+/// All tables created by AuthKit features, in the order they should be dropped
+/// (dependents before the tables they reference), followed by the lock and tracking tables.
+/// Feature tables carry `prefix`; the tracking tables are never prefixed.
+///
+/// Derived from the feature SQL so new features are picked up automatically.
+pub fn authkit_tables(prefix: &str) -> Vec<String> {
+    let mut tables: Vec<String> = Feature::all()
         .iter()
-        .map(|f| get_feature_migration(*f, db_type))
+        .flat_map(|f| feature_tables(*f))
+        .map(|t| prefixed(&t, prefix))
+        .collect();
+
+    tables.reverse();
+    tables.push(MIGRATIONS_LOCK_TABLE.to_string());
+    tables.push(MIGRATIONS_TABLE.to_string());
+    tables
+}
+
+/// Tables created by a single feature, in creation order
+pub fn feature_tables(feature: Feature) -> Vec<String> {
+    let migration = get_feature_migration(feature, DatabaseType::Postgres);
+    objects::parse(&migration.up_sql)
+        .tables
+        .into_iter()
+        .map(|t| t.name)
+        .collect()
+}
+
+/// Tables a feature needs to keep working: its own plus those of every
+/// feature it (transitively) depends on, with `prefix`
+pub fn tables_required_by(feature: Feature, prefix: &str) -> Vec<String> {
+    feature
+        .with_dependencies()
+        .into_iter()
+        .flat_map(feature_tables)
+        .map(|t| prefixed(&t, prefix))
         .collect()
 }
 
+/// Check whether a table belongs to AuthKit, with feature tables named with `prefix`
+pub fn is_authkit_table(name: &str, prefix: &str) -> bool {
+    name.starts_with("_authkit") || authkit_tables(prefix).iter().any(|t| t == name)
+}
+
+/// Ensure no index name is created by more than one migration.
+///
+/// Index names are scoped to the schema (Postgres) or database (SQLite),
+/// not to the table, so two features reusing a name would collide.
+pub fn validate_index_names(migrations: &[Migration]) -> CliResult<()> {
+    let mut seen: HashMap<String, &str> = HashMap::new();
+
+    for migration in migrations {
+        for name in objects::parse(&migration.up_sql).indexes {
+            if let Some(first) = seen.get(&name) {
+                return Err(CliError::DuplicateIndex {
+                    name,
+                    first: first.to_string(),
+                    second: migration.name.clone(),
+                });
+            }
+            seen.insert(name, &migration.name);
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tables_required_by_includes_dependencies() {
+        assert_eq!(
+            tables_required_by(Feature::EmailPassword, ""),
+            vec!["users", "accounts", "sessions", "verification"]
+        );
+
+        let two_factor = tables_required_by(Feature::TwoFactor, "");
+        assert!(two_factor.contains(&"users".to_string()));
+        assert!(two_factor.contains(&"backup_codes".to_string()));
+        assert!(!two_factor.contains(&"webauthn_credentials".to_string()));
+    }
+
     #[test]
     fn test_base_migration_postgres() {
         let migration = get_feature_migration(Feature::EmailPassword, DatabaseType::Postgres);
@@ -69,12 +416,362 @@ mod tests {
         assert!(migration.up_sql.contains("ALTER TABLE"));
     }
 
+    #[test]
+    fn test_oauth_migration_sqlite() {
+        let migration = get_feature_migration(Feature::OAuth, DatabaseType::Sqlite);
+        assert_eq!(migration.version, 3);
+        assert_eq!(migration.name, "oauth");
+        assert!(migration.up_sql.contains("ALTER TABLE accounts"));
+    }
+
+    #[test]
+    fn test_two_factor_migration_postgres() {
+        let migration = get_feature_migration(Feature::TwoFactor, DatabaseType::Postgres);
+        assert_eq!(migration.version, 4);
+        assert_eq!(migration.name, "two_factor");
+        assert!(migration
+            .up_sql
+            .contains("CREATE TABLE IF NOT EXISTS backup_codes"));
+    }
+
+    #[test]
+    fn test_username_migration_sqlite() {
+        let migration = get_feature_migration(Feature::Username, DatabaseType::Sqlite);
+        assert_eq!(migration.version, 6);
+        assert_eq!(migration.name, "username");
+        assert!(migration.up_sql.contains("idx_users_username"));
+        assert!(migration
+            .down_sql
+            .contains("ALTER TABLE users DROP COLUMN username"));
+    }
+
+    #[test]
+    fn test_audit_log_migration_postgres() {
+        let migration = get_feature_migration(Feature::AuditLog, DatabaseType::Postgres);
+        assert_eq!(migration.version, 7);
+        assert_eq!(migration.name, "audit_log");
+        assert!(migration.up_sql.contains("auth_audit_log"));
+    }
+
+    #[test]
+    fn test_account_lockout_migration_sqlite() {
+        let migration = get_feature_migration(Feature::AccountLockout, DatabaseType::Sqlite);
+        assert_eq!(migration.version, 8);
+        assert_eq!(migration.name, "account_lockout");
+        assert!(migration
+            .up_sql
+            .contains("ALTER TABLE accounts ADD COLUMN locked_until"));
+    }
+
+    #[test]
+    fn test_rate_limit_migration_postgres() {
+        let migration = get_feature_migration(Feature::RateLimit, DatabaseType::Postgres);
+        assert_eq!(migration.version, 10);
+        assert_eq!(migration.name, "rate_limit");
+        assert!(migration.up_sql.contains("rate_limits"));
+    }
+
+    #[test]
+    fn test_webauthn_migration_sqlite() {
+        let migration = get_feature_migration(Feature::WebAuthn, DatabaseType::Sqlite);
+        assert_eq!(migration.version, 11);
+        assert_eq!(migration.name, "webauthn");
+        assert!(migration.up_sql.contains("webauthn_credentials"));
+        assert!(migration
+            .down_sql
+            .contains("DROP TABLE IF EXISTS webauthn_credentials"));
+    }
+
+    #[test]
+    fn test_organizations_migration_postgres() {
+        let migration = get_feature_migration(Feature::Organizations, DatabaseType::Postgres);
+        assert_eq!(migration.version, 12);
+        assert_eq!(migration.name, "organizations");
+        assert!(migration.up_sql.contains("organization_members"));
+    }
+
+    #[test]
+    fn test_login_tracking_migration_postgres() {
+        let migration = get_feature_migration(Feature::LoginTracking, DatabaseType::Postgres);
+        assert_eq!(migration.version, 13);
+        assert_eq!(migration.name, "login_tracking");
+        assert!(migration.up_sql.contains("last_login_at BIGINT"));
+    }
+
+    #[test]
+    fn test_soft_delete_migration_sqlite() {
+        let migration = get_feature_migration(Feature::SoftDelete, DatabaseType::Sqlite);
+        assert_eq!(migration.version, 14);
+        assert_eq!(migration.name, "soft_delete");
+        assert!(migration
+            .up_sql
+            .contains("ALTER TABLE sessions ADD COLUMN deleted_at"));
+    }
+
+    #[test]
+    fn test_phone_migration_postgres() {
+        let migration = get_feature_migration(Feature::Phone, DatabaseType::Postgres);
+        assert_eq!(migration.version, 15);
+        assert_eq!(migration.name, "phone");
+        assert!(migration
+            .up_sql
+            .contains("CREATE TABLE IF NOT EXISTS phone_verification"));
+    }
+
+    #[test]
+    fn test_refresh_tokens_migration_sqlite() {
+        let migration = get_feature_migration(Feature::RefreshTokens, DatabaseType::Sqlite);
+        assert_eq!(migration.version, 16);
+        assert_eq!(migration.name, "refresh_tokens");
+        assert!(migration
+            .up_sql
+            .contains("CREATE TABLE IF NOT EXISTS refresh_tokens"));
+    }
+
+    #[test]
+    fn test_memberships_migration_both_backends() {
+        for db_type in [DatabaseType::Postgres, DatabaseType::Sqlite] {
+            let migration = get_feature_migration(Feature::Memberships, db_type);
+            assert_eq!(migration.version, 18);
+            assert_eq!(migration.name, "memberships");
+            assert!(migration
+                .up_sql
+                .contains("CREATE TABLE IF NOT EXISTS memberships"));
+            assert!(migration
+                .down_sql
+                .contains("DROP TABLE IF EXISTS memberships"));
+        }
+        assert_eq!(
+            Feature::Memberships.dependencies(),
+            &[Feature::EmailPassword, Feature::Organizations]
+        );
+    }
+
+    #[test]
+    fn test_webauthn_migration_both_backends() {
+        for db_type in [DatabaseType::Postgres, DatabaseType::Sqlite] {
+            let migration = get_feature_migration(Feature::WebAuthn, db_type);
+            assert_eq!(migration.version, 11);
+            assert_eq!(migration.name, "webauthn");
+            assert!(migration
+                .up_sql
+                .contains("CREATE TABLE IF NOT EXISTS webauthn_credentials"));
+            assert!(migration
+                .down_sql
+                .contains("DROP TABLE IF EXISTS webauthn_credentials"));
+        }
+    }
+
+    #[test]
+    fn test_magic_link_migration_postgres() {
+        let migration = get_feature_migration(Feature::MagicLink, DatabaseType::Postgres);
+        assert_eq!(migration.version, 17);
+        assert_eq!(migration.name, "magic_link");
+        assert!(migration
+            .down_sql
+            .contains("DROP TABLE IF EXISTS magic_links"));
+    }
+
+    #[test]
+    fn test_uuid_ids_postgres() {
+        let base = get_feature_migration_with(
+            Feature::EmailPassword,
+            DatabaseType::Postgres,
+            IdType::Uuid,
+        );
+        assert!(base
+            .up_sql
+            .contains("id UUID PRIMARY KEY DEFAULT gen_random_uuid()"));
+        assert_ne!(
+            base.checksum,
+            get_feature_migration(Feature::EmailPassword, DatabaseType::Postgres).checksum
+        );
+
+        // Foreign keys to users(id) must use the same type
+        let api_keys =
+            get_feature_migration_with(Feature::ApiKeys, DatabaseType::Postgres, IdType::Uuid);
+        assert!(api_keys
+            .up_sql
+            .contains("user_id UUID NOT NULL REFERENCES users(id)"));
+    }
+
+    #[test]
+    fn test_text_ids_postgres() {
+        let base = get_feature_migration(Feature::EmailPassword, DatabaseType::Postgres);
+        assert!(base.up_sql.contains("id TEXT PRIMARY KEY,"));
+        assert!(!base.up_sql.contains("UUID"));
+    }
+
     #[test]
     fn test_migrations_for_features() {
         let features = vec![Feature::EmailPassword, Feature::EmailVerification];
-        let migrations = get_migrations_for_features(&features, DatabaseType::Postgres);
+        let migrations =
+            get_migrations_for_features(&features, DatabaseType::Postgres, IdType::Text).unwrap();
         assert_eq!(migrations.len(), 2);
         assert_eq!(migrations[0].version, 1);
         assert_eq!(migrations[1].version, 2);
     }
+
+    #[test]
+    fn test_migrations_for_features_with_two_factor() {
+        let features = vec![Feature::EmailPassword, Feature::TwoFactor];
+        let migrations =
+            get_migrations_for_features(&features, DatabaseType::Sqlite, IdType::Text).unwrap();
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(migrations[1].version, 4);
+        assert_eq!(migrations[1].name, "two_factor");
+    }
+
+    #[test]
+    fn test_migrations_for_features_with_api_keys() {
+        let features = vec![Feature::EmailPassword, Feature::ApiKeys];
+        let migrations =
+            get_migrations_for_features(&features, DatabaseType::Postgres, IdType::Text).unwrap();
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(migrations[1].version, 9);
+        assert_eq!(migrations[1].name, "api_keys");
+        assert!(migrations[1].up_sql.contains("scopes JSONB"));
+        assert!(migrations[1]
+            .down_sql
+            .contains("DROP TABLE IF EXISTS api_keys"));
+    }
+
+    #[test]
+    fn test_index_names_are_unique_across_features() {
+        for db_type in [DatabaseType::Postgres, DatabaseType::Sqlite] {
+            let migrations =
+                get_migrations_for_features(Feature::all(), db_type, IdType::Text).unwrap();
+            assert!(validate_index_names(&migrations).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_duplicate_index_name_is_rejected() {
+        let mut duplicate = get_feature_migration(Feature::OAuth, DatabaseType::Postgres);
+        duplicate.name = "test_feature".to_string();
+        duplicate.up_sql =
+            "CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);".to_string();
+
+        let mut migrations = get_migrations_for_features(
+            &[Feature::EmailPassword],
+            DatabaseType::Postgres,
+            IdType::Text,
+        )
+        .unwrap();
+        migrations.push(duplicate);
+
+        match validate_index_names(&migrations) {
+            Err(CliError::DuplicateIndex {
+                name,
+                first,
+                second,
+            }) => {
+                assert_eq!(name, "idx_users_email");
+                assert_eq!(first, "base");
+                assert_eq!(second, "test_feature");
+            }
+            other => panic!("expected DuplicateIndex error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_authkit_tables_drop_order() {
+        let tables = authkit_tables("");
+        let position = |name: &str| tables.iter().position(|t| t == name).unwrap();
+
+        assert!(position("sessions") < position("users"));
+        assert!(position("organization_members") < position("organizations"));
+        assert!(position("backup_codes") < position("users"));
+        assert_eq!(tables.last().map(String::as_str), Some(MIGRATIONS_TABLE));
+    }
+
+    #[test]
+    fn test_table_prefix_applies_to_tables_and_indexes() {
+        let mut config = AuthKitConfig::default_config(DatabaseType::Sqlite);
+        config.database.table_prefix = Some("auth_".to_string());
+
+        let base = get_feature_migration_for(&config, Feature::EmailPassword, DatabaseType::Sqlite);
+        assert!(base
+            .up_sql
+            .contains("CREATE TABLE IF NOT EXISTS auth_users ("));
+        assert!(base.up_sql.contains("REFERENCES auth_users(id)"));
+        assert!(base
+            .up_sql
+            .contains("CREATE INDEX IF NOT EXISTS auth_idx_users_email ON auth_users(email)"));
+        assert!(base.up_sql.contains("user_id TEXT NOT NULL"));
+        assert!(base
+            .up_sql
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("--"))
+            .all(|line| !line.contains(" users")));
+        assert!(base
+            .up_sql
+            .contains("-- Accounts table: Links authentication providers to users"));
+        assert!(base.down_sql.contains("DROP TABLE IF EXISTS auth_users"));
+        assert_eq!(
+            base.checksum,
+            crate::migrations::compute_checksum(&base.up_sql)
+        );
+
+        let plain = get_feature_migration(Feature::EmailPassword, DatabaseType::Sqlite);
+        assert_ne!(base.checksum, plain.checksum);
+
+        let verification =
+            get_feature_migration_for(&config, Feature::EmailVerification, DatabaseType::Sqlite);
+        assert!(verification
+            .up_sql
+            .contains("ALTER TABLE auth_users ADD COLUMN"));
+    }
+
+    #[test]
+    fn test_table_prefix_skips_comments_and_literals() {
+        let sql = "-- users and sessions\nINSERT INTO users (id, note) VALUES ('1', 'users');\n";
+        assert_eq!(
+            prefix_tables(sql, "app_"),
+            "-- users and sessions\nINSERT INTO app_users (id, note) VALUES ('1', 'users');\n"
+        );
+        assert_eq!(
+            prefix_tables("SELECT 'it''s users' FROM sessions", "app_"),
+            "SELECT 'it''s users' FROM app_sessions"
+        );
+    }
+
+    #[test]
+    fn test_table_prefix_is_not_applied_twice() {
+        let mut config = AuthKitConfig::default_config(DatabaseType::Sqlite);
+        config.database.table_prefix = Some("auth_".to_string());
+
+        let audit = get_feature_migration_for(&config, Feature::AuditLog, DatabaseType::Sqlite);
+        assert!(audit
+            .up_sql
+            .contains("CREATE TABLE IF NOT EXISTS auth_audit_log ("));
+        assert!(audit
+            .down_sql
+            .contains("DROP TABLE IF EXISTS auth_audit_log"));
+        assert!(!audit.up_sql.contains("auth_auth_"));
+        assert!(!audit.down_sql.contains("auth_auth_"));
+    }
+
+    #[test]
+    fn test_prefixed_table_lists() {
+        let tables = authkit_tables("auth_");
+        assert!(tables.contains(&"auth_users".to_string()));
+        assert!(tables.contains(&"auth_audit_log".to_string()));
+        assert!(!tables.iter().any(|t| t.starts_with("auth_auth_")));
+        assert!(!tables.contains(&"users".to_string()));
+        assert_eq!(tables.last().map(String::as_str), Some(MIGRATIONS_TABLE));
+
+        assert!(is_authkit_table("auth_sessions", "auth_"));
+        assert!(is_authkit_table(MIGRATIONS_TABLE, "auth_"));
+        assert!(!is_authkit_table("sessions", "auth_"));
+        assert_eq!(
+            tables_required_by(Feature::EmailPassword, "auth_"),
+            vec![
+                "auth_users",
+                "auth_accounts",
+                "auth_sessions",
+                "auth_verification"
+            ]
+        );
+    }
 }
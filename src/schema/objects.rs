@@ -0,0 +1,247 @@
+//! Lightweight parser for the objects created by feature SQL
+//!
+//! Only the statement shapes used by AuthKit's own migrations are understood:
+//! `CREATE TABLE`, `ALTER TABLE ... ADD COLUMN` and `CREATE [UNIQUE] INDEX`.
+
+/// A table created by a migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableDef {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+/// A column added to an existing table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDef {
+    pub table: String,
+    pub name: String,
+}
+
+/// Objects created by a SQL script
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaObjects {
+    pub tables: Vec<TableDef>,
+    pub columns: Vec<ColumnDef>,
+    pub indexes: Vec<String>,
+}
+
+/// Split a SQL script into statements, dropping `--` comments
+pub fn statements(sql: &str) -> Vec<String> {
+    let code = sql
+        .lines()
+        .map(|line| line.split("--").next().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    code.split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Parse the tables, added columns and indexes created by a SQL script
+pub fn parse(sql: &str) -> SchemaObjects {
+    let mut objects = SchemaObjects::default();
+
+    for statement in statements(sql) {
+        let tokens: Vec<&str> = statement.split_whitespace().collect();
+        let upper: Vec<String> = tokens.iter().map(|t| t.to_ascii_uppercase()).collect();
+        let keywords: Vec<&str> = upper.iter().map(String::as_str).collect();
+
+        match keywords.as_slice() {
+            ["CREATE", "TABLE", ..] => {
+                if let Some(name) = name_at(&tokens, 2) {
+                    objects.tables.push(TableDef {
+                        name,
+                        columns: table_columns(&statement),
+                    });
+                }
+            }
+            ["CREATE", "INDEX", ..] => objects.indexes.extend(name_at(&tokens, 2)),
+            ["CREATE", "UNIQUE", "INDEX", ..] => objects.indexes.extend(name_at(&tokens, 3)),
+            ["ALTER", "TABLE", _, "ADD", "COLUMN", ..] => {
+                if let (Some(table), Some(name)) = (name_at(&tokens, 2), name_at(&tokens, 5)) {
+                    objects.columns.push(ColumnDef { table, name });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    objects
+}
+
+/// Reorder a script so each `CREATE TABLE` is directly followed by the indexes on it.
+///
+/// Indexes on tables the script doesn't create, or also alters, keep their place
+/// since they may depend on statements in between. Moved indexes lose their comments.
+pub fn group_by_table(sql: &str) -> String {
+    let objects = parse(sql);
+    let movable = |table: &str| {
+        objects.tables.iter().any(|t| t.name == table)
+            && !objects.columns.iter().any(|c| c.table == table)
+    };
+
+    let chunks: Vec<(&str, String)> = sql
+        .split(';')
+        .map(str::trim)
+        .filter(|chunk| !chunk.is_empty())
+        .map(|chunk| (chunk, statements(chunk).join(";")))
+        .collect();
+
+    let mut indexes: Vec<(String, &str)> = Vec::new();
+    for (_, code) in &chunks {
+        if let Some(table) = index_table(code).filter(|t| movable(t)) {
+            indexes.push((table, code.as_str()));
+        }
+    }
+
+    let mut parts = Vec::new();
+    for (chunk, code) in &chunks {
+        if code.is_empty() {
+            parts.push(chunk.to_string());
+            continue;
+        }
+        if index_table(code).is_some_and(|t| movable(&t)) {
+            continue;
+        }
+
+        let mut part = format!("{};", chunk);
+        if let Some(table) = parse(code).tables.first() {
+            for (_, index) in indexes.iter().filter(|(t, _)| *t == table.name) {
+                part.push_str(&format!("\n{};", index));
+            }
+        }
+        parts.push(part);
+    }
+
+    format!("\n{}\n", parts.join("\n\n"))
+}
+
+/// Table an index statement is created on
+fn index_table(code: &str) -> Option<String> {
+    let tokens: Vec<&str> = code.split_whitespace().collect();
+    let keywords: Vec<String> = tokens
+        .iter()
+        .take(3)
+        .map(|t| t.to_ascii_uppercase())
+        .collect();
+    let is_index = matches!(
+        keywords
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice(),
+        ["CREATE", "INDEX", ..] | ["CREATE", "UNIQUE", "INDEX"]
+    );
+    if !is_index {
+        return None;
+    }
+
+    let on = tokens.iter().position(|t| t.eq_ignore_ascii_case("ON"))?;
+    name_at(&tokens, on + 1)
+}
+
+/// Read the identifier at `index`, skipping an `IF NOT EXISTS` clause
+fn name_at(tokens: &[&str], index: usize) -> Option<String> {
+    let mut index = index;
+    if tokens
+        .get(index)
+        .is_some_and(|t| t.eq_ignore_ascii_case("IF"))
+    {
+        index += 3;
+    }
+
+    let token = tokens.get(index)?;
+    let name = token.split('(').next().unwrap_or(token).trim_matches('"');
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Extract the column names from a `CREATE TABLE` body, ignoring table constraints
+fn table_columns(statement: &str) -> Vec<String> {
+    let (Some(start), Some(end)) = (statement.find('('), statement.rfind(')')) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in statement[start + 1..end].chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                entries.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    entries.push(current);
+
+    entries
+        .iter()
+        .filter_map(|entry| entry.split_whitespace().next())
+        .map(|word| word.split('(').next().unwrap_or(word))
+        .filter(|word| {
+            !matches!(
+                word.to_ascii_uppercase().as_str(),
+                "PRIMARY" | "UNIQUE" | "FOREIGN" | "CONSTRAINT" | "CHECK" | ""
+            )
+        })
+        .map(|word| word.trim_matches('"').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_create_table() {
+        let objects = parse(
+            "-- Accounts\nCREATE TABLE IF NOT EXISTS accounts (\n    id TEXT PRIMARY KEY,\n    provider TEXT NOT NULL,\n    UNIQUE(provider, id)\n);",
+        );
+        assert_eq!(
+            objects.tables,
+            vec![TableDef {
+                name: "accounts".to_string(),
+                columns: vec!["id".to_string(), "provider".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_group_by_table() {
+        let sql = "-- Users\nCREATE TABLE users (id TEXT);\n-- Posts\nCREATE TABLE posts (id TEXT);\n\n-- Indexes\nCREATE INDEX idx_posts_id ON posts(id);\nCREATE UNIQUE INDEX idx_users_id ON users (id);\nCREATE INDEX idx_other ON other(id);\n";
+        assert_eq!(
+            group_by_table(sql),
+            "\n-- Users\nCREATE TABLE users (id TEXT);\nCREATE UNIQUE INDEX idx_users_id ON users (id);\n\n-- Posts\nCREATE TABLE posts (id TEXT);\nCREATE INDEX idx_posts_id ON posts(id);\n\nCREATE INDEX idx_other ON other(id);\n"
+        );
+    }
+
+    #[test]
+    fn test_group_by_table_keeps_indexes_on_altered_tables() {
+        let sql = "CREATE TABLE users (id TEXT);\nALTER TABLE users ADD COLUMN email TEXT;\nCREATE INDEX idx_users_email ON users(email);";
+        assert!(group_by_table(sql).ends_with(
+            "ADD COLUMN email TEXT;\n\nCREATE INDEX idx_users_email ON users(email);\n"
+        ));
+    }
+
+    #[test]
+    fn test_parse_added_columns_and_indexes() {
+        let objects = parse(
+            "ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified BOOLEAN;\n-- Create index for lookups\nCREATE UNIQUE INDEX idx_a ON t(a);\ncreate index if not exists idx_b on t(b);",
+        );
+        assert_eq!(
+            objects.columns,
+            vec![ColumnDef {
+                table: "users".to_string(),
+                name: "email_verified".to_string(),
+            }]
+        );
+        assert_eq!(objects.indexes, vec!["idx_a", "idx_b"]);
+    }
+}
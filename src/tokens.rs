@@ -0,0 +1,63 @@
+//! Lifetimes of the tokens stored in the `verification` table
+//!
+//! AuthKit only stores `expires_at`; the application computes it when issuing a
+//! token. These defaults keep the lifetimes short enough that a leaked link is
+//! of little use, and match the comments in the generated base schema.
+
+/// Lifetime of a token type without a specific default, in seconds (1 hour)
+pub const DEFAULT_TOKEN_TTL_SECONDS: i64 = 60 * 60;
+
+/// Suggested lifetime of a verification token in seconds, by `token_type`:
+/// `magic_link` 15 minutes, `password_reset` 1 hour, `email_verify` 24 hours.
+/// Other types get [`DEFAULT_TOKEN_TTL_SECONDS`].
+///
+/// ```
+/// let issued_at = 1_700_000_000;
+/// let expires_at = issued_at + authkit_cli::default_token_ttl_seconds("password_reset");
+/// assert_eq!(expires_at, 1_700_003_600);
+/// ```
+pub fn default_token_ttl_seconds(token_type: &str) -> i64 {
+    match token_type {
+        "magic_link" => 15 * 60,
+        "password_reset" => 60 * 60,
+        "email_verify" => 24 * 60 * 60,
+        _ => DEFAULT_TOKEN_TTL_SECONDS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IdType;
+    use crate::schema::features::base;
+
+    #[test]
+    fn test_ttl_per_token_type() {
+        assert_eq!(default_token_ttl_seconds("magic_link"), 900);
+        assert_eq!(default_token_ttl_seconds("password_reset"), 3600);
+        assert_eq!(default_token_ttl_seconds("email_verify"), 86400);
+    }
+
+    #[test]
+    fn test_unknown_type_falls_back_to_default() {
+        assert_eq!(
+            default_token_ttl_seconds("invite"),
+            DEFAULT_TOKEN_TTL_SECONDS
+        );
+        assert_eq!(default_token_ttl_seconds(""), DEFAULT_TOKEN_TTL_SECONDS);
+    }
+
+    #[test]
+    fn test_schema_comments_match_defaults() {
+        let postgres_up = base::postgres_up(IdType::Text);
+        for token_type in ["password_reset", "email_verify", "magic_link"] {
+            let line = format!(
+                "--   {}: {} seconds",
+                token_type,
+                default_token_ttl_seconds(token_type)
+            );
+            assert!(postgres_up.contains(&line), "{}", line);
+            assert!(base::SQLITE_UP.contains(&line), "{}", line);
+        }
+    }
+}
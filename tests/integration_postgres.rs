@@ -1,10 +1,35 @@
+// `Command::cargo_bin` is deprecated in newer assert_cmd releases but still works for us
+#![allow(deprecated)]
+
 use assert_cmd::Command;
 use predicates::prelude::*;
+use tempfile::tempdir;
 
 fn get_test_postgres_url() -> Option<String> {
     std::env::var("TEST_POSTGRES_URL").ok()
 }
 
+/// Run a query returning a single integer value
+fn query_scalar(db_url: &str, sql: &str) -> i64 {
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::AnyPool::connect(db_url).await.unwrap();
+        let (value,): (i64,) = sqlx::query_as(sql).fetch_one(&pool).await.unwrap();
+        pool.close().await;
+        value
+    })
+}
+
+/// Execute a raw SQL statement against the database
+fn execute_sql(db_url: &str, sql: &str) {
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::AnyPool::connect(db_url).await.unwrap();
+        sqlx::query(sql).execute(&pool).await.unwrap();
+        pool.close().await;
+    });
+}
+
 #[test]
 #[ignore] // Run with: cargo test --test integration_postgres -- --ignored
 fn test_migrate_postgres() {
@@ -223,3 +248,465 @@ fn test_schema_from_postgres_database() {
         .success()
         .stdout(predicate::str::contains("Actual schema from database"));
 }
+
+#[test]
+#[ignore]
+fn test_transaction_mode_all_rolls_back_postgres() {
+    let db_url = match get_test_postgres_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping: TEST_POSTGRES_URL not set");
+            return;
+        }
+    };
+
+    let temp = tempdir().unwrap();
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        "[database]\ntype = \"postgres\"\n\n[features]\nemail_password = true\nemail_verification = true\ntwo_factor = true\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success();
+
+    // A pre-existing backup_codes table without user_id makes the last migration fail
+    execute_sql(&db_url, "CREATE TABLE backup_codes (id TEXT PRIMARY KEY)");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            config.to_str().unwrap(),
+            "--transaction-mode",
+            "all",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Rolled back all 3 pending migration(s)",
+        ));
+
+    let users = query_scalar(
+        &db_url,
+        "SELECT COUNT(*) FROM pg_tables WHERE schemaname = 'public' AND tablename = 'users'",
+    );
+    assert_eq!(users, 0);
+    let recorded = query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations");
+    assert_eq!(recorded, 0);
+
+    execute_sql(&db_url, "DROP TABLE backup_codes");
+}
+
+#[test]
+#[ignore]
+fn test_migrate_fails_fast_while_advisory_lock_held_postgres() {
+    let db_url = match get_test_postgres_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping: TEST_POSTGRES_URL not set");
+            return;
+        }
+    };
+
+    // Hold the same advisory lock a concurrent migrate run would take
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let holder = runtime.block_on(async {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect(&db_url)
+            .await
+            .unwrap();
+        sqlx::query("SELECT pg_advisory_lock(27432215569459572)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    });
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--lock-timeout", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MigrationInProgress"));
+
+    runtime.block_on(holder.close());
+}
+
+#[test]
+#[ignore]
+fn test_migrate_into_custom_schema_postgres() {
+    let db_url = match get_test_postgres_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping: TEST_POSTGRES_URL not set");
+            return;
+        }
+    };
+
+    let temp = tempdir().unwrap();
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        "[database]\ntype = \"postgres\"\nschema = \"authkit_tenant\"\n\n[features]\nemail_password = true\n",
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    execute_sql(&db_url, "DROP SCHEMA IF EXISTS authkit_tenant CASCADE");
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", config])
+        .assert()
+        .success();
+
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pg_tables WHERE schemaname = 'authkit_tenant' AND tablename IN ('users', '_authkit_migrations')"
+        ),
+        2
+    );
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pg_tables WHERE schemaname = 'public' AND tablename = 'users'"
+        ),
+        0
+    );
+
+    // Status resolves the tracking table through the same schema
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Database is up to date"));
+
+    execute_sql(&db_url, "DROP SCHEMA authkit_tenant CASCADE");
+}
+
+#[test]
+#[ignore]
+fn test_dump_without_tracking_table_postgres() {
+    let db_url = match get_test_postgres_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping: TEST_POSTGRES_URL not set");
+            return;
+        }
+    };
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success();
+
+    // Reading applied migrations on a fresh database yields nothing rather than an error
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["dump", "--db-url", &db_url])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let dump: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(dump["migrations"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+#[ignore]
+fn test_statement_timeout_aborts_slow_migration_postgres() {
+    let db_url = match get_test_postgres_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping: TEST_POSTGRES_URL not set");
+            return;
+        }
+    };
+
+    let temp = tempdir().unwrap();
+    let custom_dir = temp.path().join("custom_migrations");
+    std::fs::create_dir(&custom_dir).unwrap();
+    std::fs::write(custom_dir.join("100_slow.up.sql"), "SELECT pg_sleep(5);").unwrap();
+    std::fs::write(custom_dir.join("100_slow.down.sql"), "SELECT 1;").unwrap();
+
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[database]\ntype = \"postgres\"\n\n[features]\nemail_password = true\n\n[migrations]\ndir = \"{}\"\n",
+            custom_dir.display()
+        ),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            config,
+            "--statement-timeout",
+            "100",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("statement timeout"));
+
+    // The option is global, so it also applies when given before the subcommand
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "--statement-timeout",
+            "100",
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            config,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("statement timeout"));
+}
+
+#[test]
+#[ignore]
+fn test_schema_single_table_postgres() {
+    let db_url = match get_test_postgres_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping: TEST_POSTGRES_URL not set");
+            return;
+        }
+    };
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success();
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "schema", "--db-url", &db_url, "--format", "json", "--table", "users",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let tables = schema["tables"].as_array().unwrap();
+    assert_eq!(tables.len(), 1);
+    let create_sql = tables[0]["create_sql"].as_str().unwrap();
+    assert!(create_sql.starts_with("CREATE TABLE users ("));
+    assert!(create_sql.contains("id TEXT NOT NULL"));
+}
+
+#[test]
+#[ignore]
+fn test_schema_sql_includes_columns_postgres() {
+    let db_url = match get_test_postgres_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping: TEST_POSTGRES_URL not set");
+            return;
+        }
+    };
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success();
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["schema", "--db-url", &db_url])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("id TEXT"))
+        .stdout(predicate::str::contains("email TEXT NOT NULL"))
+        .stdout(predicate::str::contains("PRIMARY KEY (id)"))
+        .stdout(predicate::str::contains("schema not available").not());
+}
+
+#[test]
+#[ignore]
+fn test_migrate_concurrent_indexes_postgres() {
+    let db_url = match get_test_postgres_url() {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping: TEST_POSTGRES_URL not set");
+            return;
+        }
+    };
+    let temp = tempdir().unwrap();
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        "[database]\ntype = \"postgres\"\n\n[features]\nemail_password = true\nrefresh_tokens = true\n",
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success();
+
+    // A leftover users table with an INTEGER id breaks the accounts foreign key,
+    // failing the base migration before any index is built
+    execute_sql(&db_url, "CREATE TABLE users (id INTEGER PRIMARY KEY)");
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            config,
+            "--allow-dirty",
+            "--concurrent-indexes",
+        ])
+        .assert()
+        .failure();
+    // The non-index statements ran in one transaction, so none of them were kept
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM information_schema.tables WHERE table_name IN ('accounts', 'sessions')"
+        ),
+        0
+    );
+    execute_sql(&db_url, "DROP TABLE users");
+
+    // A killed or failed concurrent build leaves an invalid index behind, which
+    // CREATE INDEX IF NOT EXISTS would otherwise skip
+    execute_sql(&db_url, "DROP TABLE IF EXISTS leftover");
+    execute_sql(&db_url, "CREATE TABLE leftover (x INTEGER)");
+    execute_sql(&db_url, "INSERT INTO leftover VALUES (1), (1)");
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::AnyPool::connect(&db_url).await.unwrap();
+        let result =
+            sqlx::query("CREATE UNIQUE INDEX CONCURRENTLY idx_sessions_user_id ON leftover (x)")
+                .execute(&pool)
+                .await;
+        assert!(result.is_err());
+        pool.close().await;
+    });
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pg_index i JOIN pg_class c ON c.oid = i.indexrelid \
+             WHERE c.relname = 'idx_sessions_user_id' AND NOT i.indisvalid"
+        ),
+        1
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            config,
+            "--concurrent-indexes",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 2 migration(s)"));
+
+    // The leftover was dropped and the index rebuilt on sessions
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pg_index i JOIN pg_class c ON c.oid = i.indexrelid \
+             WHERE c.relname = 'idx_sessions_user_id' AND i.indrelid = 'sessions'::regclass"
+        ),
+        1
+    );
+    execute_sql(&db_url, "DROP TABLE leftover");
+
+    // Every index was built and is valid
+    for index in [
+        "idx_sessions_user_id",
+        "idx_refresh_tokens_family_id",
+        "idx_refresh_tokens_token_hash",
+    ] {
+        assert_eq!(
+            query_scalar(
+                &db_url,
+                &format!(
+                    "SELECT COUNT(*) FROM pg_index i JOIN pg_class c ON c.oid = i.indexrelid \
+                     WHERE c.relname = '{}' AND i.indisvalid",
+                    index
+                )
+            ),
+            1,
+            "{}",
+            index
+        );
+    }
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        2
+    );
+
+    // --transaction-mode all can't wrap concurrent index builds
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            config,
+            "--concurrent-indexes",
+            "--transaction-mode",
+            "all",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--concurrent-indexes"));
+}
@@ -1,7 +1,45 @@
+// `Command::cargo_bin` is deprecated in newer assert_cmd releases but still works for us
+#![allow(deprecated)]
+
 use assert_cmd::Command;
 use predicates::prelude::*;
+use std::path::Path;
 use tempfile::tempdir;
 
+/// Write a SQLite authkit.toml enabling the base feature plus `features`
+fn write_config(dir: &Path, features: &[&str]) -> String {
+    let mut content =
+        String::from("[database]\ntype = \"sqlite\"\n\n[features]\nemail_password = true\n");
+    for feature in features {
+        content.push_str(&format!("{} = true\n", feature));
+    }
+
+    let path = dir.join("authkit.toml");
+    std::fs::write(&path, content).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+/// Execute a raw SQL statement against the database
+fn execute_sql(db_url: &str, sql: &str) {
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::AnyPool::connect(db_url).await.unwrap();
+        sqlx::query(sql).execute(&pool).await.unwrap();
+        pool.close().await;
+    });
+}
+
+/// Run a query returning a single integer value
+fn query_scalar(db_url: &str, sql: &str) -> i64 {
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        sqlx::any::install_default_drivers();
+        let pool = sqlx::AnyPool::connect(db_url).await.unwrap();
+        let (value,): (i64,) = sqlx::query_as(sql).fetch_one(&pool).await.unwrap();
+        pool.close().await;
+        value
+    })
+}
+
 #[test]
 fn test_help_command() {
     Command::cargo_bin("authkit")
@@ -28,11 +66,12 @@ fn test_migrate_sqlite() {
     let temp = tempdir().unwrap();
     let db_path = temp.path().join("test.db");
     let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // Run migrate
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["migrate", "--db-url", &db_url])
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
         .assert()
         .success()
         .stdout(predicate::str::contains("Applied"));
@@ -43,11 +82,19 @@ fn test_migrate_dry_run() {
     let temp = tempdir().unwrap();
     let db_path = temp.path().join("test.db");
     let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // Run migrate with dry-run
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["migrate", "--db-url", &db_url, "--dry-run"])
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--dry-run",
+            "--config",
+            &config,
+        ])
         .assert()
         .success()
         .stdout(predicate::str::contains("Dry run"))
@@ -59,18 +106,19 @@ fn test_status_after_migrate() {
     let temp = tempdir().unwrap();
     let db_path = temp.path().join("test.db");
     let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // First migrate
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["migrate", "--db-url", &db_url])
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
         .assert()
         .success();
 
     // Then check status
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["status", "--db-url", &db_url])
+        .args(["status", "--db-url", &db_url, "--config", &config])
         .assert()
         .success()
         .stdout(predicate::str::contains("up to date"));
@@ -96,11 +144,12 @@ fn test_destroy_with_force() {
     let temp = tempdir().unwrap();
     let db_path = temp.path().join("test.db");
     let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // First migrate
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["migrate", "--db-url", &db_url])
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
         .assert()
         .success();
 
@@ -132,13 +181,14 @@ fn test_destroy_nothing_to_destroy() {
 fn test_generate_sqlite() {
     let temp = tempdir().unwrap();
     let output_dir = temp.path().join("migrations");
+    let config = write_config(temp.path(), &["email_verification"]);
 
     Command::cargo_bin("authkit")
         .unwrap()
         .args([
             "generate",
-            "--db",
-            "sqlite",
+            "--config",
+            &config,
             "--output",
             output_dir.to_str().unwrap(),
         ])
@@ -146,62 +196,70 @@ fn test_generate_sqlite() {
         .success()
         .stdout(predicate::str::contains("Generated"));
 
-    // Verify files were created
-    assert!(output_dir
-        .join("sqlite")
-        .join("001_create_users_table.up.sql")
-        .exists());
+    // Verify files were created in the sqlite/ subdirectory
+    assert!(output_dir.join("sqlite").join("001_base.up.sql").exists());
+    assert!(output_dir.join("sqlite").join("001_base.down.sql").exists());
     assert!(output_dir
         .join("sqlite")
-        .join("001_create_users_table.down.sql")
-        .exists());
-    assert!(output_dir
-        .join("sqlite")
-        .join("004_create_indexes.up.sql")
+        .join("002_email_verification.up.sql")
         .exists());
+    assert!(!output_dir.join("001_base.up.sql").exists());
 }
 
 #[test]
 fn test_generate_postgres() {
     let temp = tempdir().unwrap();
     let output_dir = temp.path().join("migrations");
+    let output = output_dir.to_str().unwrap();
+    let sqlite_config = write_config(temp.path(), &[]);
+    let postgres_config = temp.path().join("postgres.toml");
+    std::fs::write(
+        &postgres_config,
+        "[database]\ntype = \"postgres\"\n\n[features]\nemail_password = true\n",
+    )
+    .unwrap();
 
     Command::cargo_bin("authkit")
         .unwrap()
         .args([
             "generate",
-            "--db",
-            "postgres",
+            "--config",
+            postgres_config.to_str().unwrap(),
             "--output",
-            output_dir.to_str().unwrap(),
+            output,
         ])
         .assert()
         .success()
         .stdout(predicate::str::contains("Generated"));
 
-    // Verify files were created (PostgreSQL has more files due to individual indexes)
-    assert!(output_dir
-        .join("postgres")
-        .join("001_create_users_table.up.sql")
-        .exists());
-    assert!(output_dir
-        .join("postgres")
-        .join("010_create_tokens_type_index.up.sql")
-        .exists());
+    // The sqlite set lands next to the postgres one without a conflict
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["generate", "--config", &sqlite_config, "--output", output])
+        .assert()
+        .success();
+
+    let postgres_up =
+        std::fs::read_to_string(output_dir.join("postgres").join("001_base.up.sql")).unwrap();
+    let sqlite_up =
+        std::fs::read_to_string(output_dir.join("sqlite").join("001_base.up.sql")).unwrap();
+    assert!(postgres_up.contains("BIGINT"));
+    assert!(!sqlite_up.contains("BIGINT"));
 }
 
 #[test]
 fn test_generate_file_exists_error() {
     let temp = tempdir().unwrap();
     let output_dir = temp.path().join("migrations");
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // First generate
     Command::cargo_bin("authkit")
         .unwrap()
         .args([
             "generate",
-            "--db",
-            "sqlite",
+            "--config",
+            &config,
             "--output",
             output_dir.to_str().unwrap(),
         ])
@@ -213,47 +271,105 @@ fn test_generate_file_exists_error() {
         .unwrap()
         .args([
             "generate",
-            "--db",
-            "sqlite",
+            "--config",
+            &config,
             "--output",
             output_dir.to_str().unwrap(),
         ])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("FileExists"));
+        .stderr(predicate::str::contains("FileExists"))
+        .stderr(predicate::str::contains("sqlite"));
 }
 
 #[test]
 fn test_generate_force_overwrites() {
     let temp = tempdir().unwrap();
     let output_dir = temp.path().join("migrations");
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // First generate
     Command::cargo_bin("authkit")
         .unwrap()
         .args([
             "generate",
-            "--db",
-            "sqlite",
+            "--config",
+            &config,
             "--output",
             output_dir.to_str().unwrap(),
         ])
         .assert()
         .success();
 
+    let edited = output_dir.join("sqlite").join("001_base.up.sql");
+    std::fs::write(&edited, "-- edited\n").unwrap();
+
     // Second generate with --force should succeed
     Command::cargo_bin("authkit")
         .unwrap()
         .args([
             "generate",
-            "--db",
-            "sqlite",
+            "--config",
+            &config,
             "--output",
             output_dir.to_str().unwrap(),
             "--force",
         ])
         .assert()
         .success();
+
+    assert_ne!(std::fs::read_to_string(&edited).unwrap(), "-- edited\n");
+}
+
+#[test]
+fn test_export_sqlx_writes_sqlx_named_files() {
+    let temp = tempdir().unwrap();
+    let output_dir = temp.path().join("migrations");
+    let output = output_dir.to_str().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "export", "--config", &config, "--format", "sqlx", "--output", output,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 2 migration files"));
+
+    let mut files: Vec<String> = std::fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["001_base.sql", "002_email_verification.sql"]);
+
+    // Existing files are protected without --force
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["export", "--config", &config, "--output", output])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("FileExists"));
+
+    let reversible_dir = temp.path().join("reversible");
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "export",
+            "--config",
+            &config,
+            "--output",
+            reversible_dir.to_str().unwrap(),
+            "--reversible",
+        ])
+        .assert()
+        .success();
+    assert!(reversible_dir.join("001_base.up.sql").exists());
+    assert!(reversible_dir.join("001_base.down.sql").exists());
+    assert!(reversible_dir
+        .join("002_email_verification.down.sql")
+        .exists());
 }
 
 #[test]
@@ -261,17 +377,18 @@ fn test_idempotent_migrate() {
     let temp = tempdir().unwrap();
     let db_path = temp.path().join("test.db");
     let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // Run migrate twice
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["migrate", "--db-url", &db_url])
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
         .assert()
         .success();
 
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["migrate", "--db-url", &db_url])
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
         .assert()
         .success()
         .stdout(predicate::str::contains("up to date"));
@@ -299,6 +416,34 @@ fn test_schema_json_output() {
         .stdout(predicate::str::contains("\"name\""));
 }
 
+#[test]
+fn test_schema_json_stdout_is_pure_json() {
+    let temp = tempdir().unwrap();
+    let missing = temp.path().join("missing.toml");
+
+    // A missing config prints a note, which must not end up in the payload
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "schema",
+            "--db",
+            "sqlite",
+            "--format",
+            "json",
+            "--config",
+            missing.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Note:"));
+    let schema: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(schema["migrations"].is_array());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Config not found"));
+}
+
 #[test]
 fn test_schema_table_output() {
     Command::cargo_bin("authkit")
@@ -309,16 +454,50 @@ fn test_schema_table_output() {
         .stdout(predicate::str::contains("Migration"));
 }
 
+#[test]
+fn test_schema_include_down() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+
+    for format in ["sql", "table"] {
+        Command::cargo_bin("authkit")
+            .unwrap()
+            .args([
+                "schema",
+                "--config",
+                &config,
+                "--db",
+                "sqlite",
+                "--format",
+                format,
+                "--include-down",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("CREATE TABLE IF NOT EXISTS users"))
+            .stdout(predicate::str::contains("-- DOWN"))
+            .stdout(predicate::str::contains("DROP TABLE IF EXISTS users"));
+    }
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["schema", "--config", &config, "--db", "sqlite"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-- DOWN").not());
+}
+
 #[test]
 fn test_schema_from_database() {
     let temp = tempdir().unwrap();
     let db_path = temp.path().join("test.db");
     let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // First migrate to create tables
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["migrate", "--db-url", &db_url])
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
         .assert()
         .success();
 
@@ -346,11 +525,12 @@ fn test_full_workflow() {
     let temp = tempdir().unwrap();
     let db_path = temp.path().join("workflow_test.db");
     let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
 
     // 1. Check initial status (should show pending)
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["status", "--db-url", &db_url])
+        .args(["status", "--db-url", &db_url, "--config", &config])
         .assert()
         .success()
         .stdout(predicate::str::contains("pending"));
@@ -358,7 +538,7 @@ fn test_full_workflow() {
     // 2. Run migrations
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["migrate", "--db-url", &db_url])
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
         .assert()
         .success()
         .stdout(predicate::str::contains("Applied"));
@@ -366,7 +546,7 @@ fn test_full_workflow() {
     // 3. Check status after migration
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["status", "--db-url", &db_url])
+        .args(["status", "--db-url", &db_url, "--config", &config])
         .assert()
         .success()
         .stdout(predicate::str::contains("up to date"));
@@ -382,8 +562,3896 @@ fn test_full_workflow() {
     // 5. Check status after destroy (should show pending again)
     Command::cargo_bin("authkit")
         .unwrap()
-        .args(["status", "--db-url", &db_url])
+        .args(["status", "--db-url", &db_url, "--config", &config])
         .assert()
         .success()
         .stdout(predicate::str::contains("pending"));
 }
+
+#[test]
+fn test_migrate_require_clean_aborts_on_missing() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    // Create the tracking table, then record a migration unknown to the config
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(
+        &db_url,
+        "INSERT INTO _authkit_migrations (version, name, applied_at, checksum) VALUES (99, 'removed', 0, 'abc')",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--require-clean",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("099_removed"))
+        .stderr(predicate::str::contains("NotClean"));
+
+    // Nothing should have been applied
+    let users = query_scalar(
+        &db_url,
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'",
+    );
+    assert_eq!(users, 0);
+}
+
+#[test]
+fn test_generate_json_report_categorizes_files() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+    let output = output_dir.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["generate", "--config", &config, "--output", output])
+        .assert()
+        .success();
+
+    // Leave the directory partially populated: one edited file, one removed file
+    std::fs::write(sqlite_dir.join("001_base.up.sql"), "-- edited\n").unwrap();
+    std::fs::remove_file(sqlite_dir.join("002_email_verification.down.sql")).unwrap();
+
+    let assert = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output,
+            "--output-format",
+            "json",
+        ])
+        .assert()
+        .failure();
+
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(
+        report["created"],
+        serde_json::json!(["002_email_verification.down.sql"])
+    );
+    assert_eq!(
+        report["skipped"],
+        serde_json::json!(["001_base.down.sql", "002_email_verification.up.sql"])
+    );
+    assert_eq!(report["conflicts"], serde_json::json!(["001_base.up.sql"]));
+    assert_eq!(report["success"], serde_json::json!(false));
+
+    // The conflicting file is left untouched
+    assert_eq!(
+        std::fs::read_to_string(sqlite_dir.join("001_base.up.sql")).unwrap(),
+        "-- edited\n"
+    );
+}
+
+#[test]
+fn test_verify_detects_tampered_checksum() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["verify", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All applied migrations match"));
+
+    execute_sql(
+        &db_url,
+        "UPDATE _authkit_migrations SET checksum = 'tampered' WHERE version = 2",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["verify", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "002_email_verification: checksum mismatch",
+        ))
+        .stdout(predicate::str::contains("expected: tampered"))
+        .stderr(predicate::str::contains("ChecksumMismatch"));
+}
+
+#[test]
+fn test_verify_accepts_released_base_checksum() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // Databases migrated by the first release carry the SHA-256 of the raw 001_base SQL
+    execute_sql(
+        &db_url,
+        "UPDATE _authkit_migrations SET checksum = '45cfeedf2905166bd97e27123300f8189bc70dd8bd59e2a7c93552d8c1aa4d16' WHERE version = 1",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["verify", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All applied migrations match"));
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--strict",
+            "--require-clean",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_migrate_emit_metrics() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let metrics_path = temp.path().join("authkit.prom");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--emit-metrics",
+            metrics_path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    let metrics = std::fs::read_to_string(&metrics_path).unwrap();
+    assert!(metrics.contains("authkit_schema_version{db_type=\"sqlite\"} 2\n"));
+    assert!(metrics.contains("authkit_migrations_applied_total{db_type=\"sqlite\"} 2\n"));
+    assert!(metrics.contains("authkit_pending_migrations{db_type=\"sqlite\"} 0\n"));
+    assert!(metrics.contains("authkit_last_migrate_timestamp_seconds{db_type=\"sqlite\"}"));
+}
+
+#[test]
+fn test_verify_ignores_config_database_type() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // Switch the config to postgres; checksums must still be compared against SQLite SQL
+    let content = std::fs::read_to_string(&config).unwrap();
+    std::fs::write(
+        &config,
+        content.replace("type = \"sqlite\"", "type = \"postgres\""),
+    )
+    .unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["verify", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All applied migrations match"));
+}
+
+#[test]
+fn test_migrate_no_color() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    // CLICOLOR_FORCE would otherwise enable colors even though stdout is a pipe
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .env("CLICOLOR_FORCE", "1")
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--no-color",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 2 migration(s)"))
+        .stdout(predicate::str::contains("\x1b[").not());
+}
+
+#[test]
+fn test_migrate_multiple_databases() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let first = format!("sqlite:{}?mode=rwc", temp.path().join("a.db").display());
+    let second = format!("sqlite:{}?mode=rwc", temp.path().join("b.db").display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate", "--db-url", &first, "--db-url", &second, "--config", &config,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Database"))
+        .stdout(predicate::str::contains("OK"));
+
+    for db_url in [&first, &second] {
+        let applied = query_scalar(db_url, "SELECT COUNT(*) FROM _authkit_migrations");
+        assert_eq!(applied, 2);
+    }
+}
+
+#[test]
+fn test_migrate_db_url_file_continue_on_error() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+    let good = format!("sqlite:{}?mode=rwc", temp.path().join("good.db").display());
+    let list = temp.path().join("targets.txt");
+    std::fs::write(&list, format!("# shards\nmysql://nope\n\n{}\n", good)).unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url-file",
+            list.to_str().unwrap(),
+            "--config",
+            &config,
+            "--continue-on-error",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Failed"))
+        .stdout(predicate::str::contains("Unknown database type").not())
+        .stderr(predicate::str::contains(
+            "✗ Unknown database type in URL: mysql://nope",
+        ))
+        .stderr(predicate::str::contains("1 of 2 database(s) failed"));
+
+    let applied = query_scalar(&good, "SELECT COUNT(*) FROM _authkit_migrations");
+    assert_eq!(applied, 1);
+}
+
+#[test]
+fn test_migrate_checks_report_on_stderr() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_case_insensitive"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--require-clean",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Database is clean").not())
+        .stdout(predicate::str::contains("No users table yet").not())
+        .stderr(predicate::str::contains("Database is clean"))
+        .stderr(predicate::str::contains("No users table yet"));
+
+    // A drifted checksum fails --require-clean with the problem on stderr only
+    execute_sql(
+        &db_url,
+        "UPDATE _authkit_migrations SET checksum = 'drifted' WHERE version = 5",
+    );
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--require-clean",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("checksum mismatch").not())
+        .stderr(predicate::str::contains("005: checksum mismatch"));
+
+    // Duplicate emails block email_case_insensitive; the rows and hint go to stderr
+    let config = write_config(temp.path(), &[]);
+    let fresh = format!("sqlite:{}?mode=rwc", temp.path().join("dupes.db").display());
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &fresh, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(
+        &fresh,
+        "INSERT INTO users (id, email, created_at, updated_at) VALUES ('u1', 'A@x.com', 0, 0), ('u2', 'a@x.com', 0, 0)",
+    );
+    let config = write_config(temp.path(), &["email_case_insensitive"]);
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &fresh, "--config", &config])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("u1").not())
+        .stdout(predicate::str::contains("Merge or fix").not())
+        .stderr(predicate::str::contains("u1 \"A@x.com\""))
+        .stderr(predicate::str::contains("Merge or fix these users"));
+}
+
+#[test]
+fn test_dump_checksums_compare_dir() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+    let output = output_dir.to_str().unwrap();
+    let compare_dir = sqlite_dir.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["generate", "--config", &config, "--output", output])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "dump-checksums",
+            "--db",
+            "sqlite",
+            "--compare-dir",
+            compare_dir,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All migration files match"));
+
+    // Comments and whitespace don't count as edits
+    let edited = sqlite_dir.join("002_email_verification.up.sql");
+    let content = std::fs::read_to_string(&edited).unwrap();
+    std::fs::write(&edited, format!("{}\n-- local edit\n", content)).unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "dump-checksums",
+            "--db",
+            "sqlite",
+            "--compare-dir",
+            compare_dir,
+        ])
+        .assert()
+        .success();
+
+    std::fs::write(
+        &edited,
+        format!("{}\nCREATE INDEX idx_local ON users(email);\n", content),
+    )
+    .unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "dump-checksums",
+            "--db",
+            "sqlite",
+            "--compare-dir",
+            compare_dir,
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "002_email_verification.up.sql: checksum mismatch",
+        ))
+        .stderr(predicate::str::contains("ChecksumMismatch"));
+}
+
+#[test]
+fn test_dump_checksums_lists_features() {
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["dump-checksums", "--db", "postgres"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("001 base"))
+        .stdout(predicate::str::contains("002 email_verification"));
+}
+
+#[test]
+fn test_json_error_output() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "--error-format",
+            "json",
+            "status",
+            "--db-url",
+            "mysql://localhost/test",
+            "--config",
+            &config,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"kind\":\"UnknownDatabase\""));
+}
+
+#[test]
+fn test_help_features() {
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .arg("--help-features")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "email_verification - Email Verification",
+        ))
+        .stdout(predicate::str::contains(
+            "Enable:     email_verification = true",
+        ))
+        .stdout(predicate::str::contains("users.email_verified"))
+        .stdout(predicate::str::contains(
+            "users (id, email, name, created_at, updated_at)",
+        ));
+}
+
+#[test]
+fn test_dump_after_migrate() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["dump", "--db-url", &db_url])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let dump: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(dump["database_type"], "sqlite");
+
+    let migrations = dump["migrations"].as_array().unwrap();
+    assert_eq!(migrations.len(), 2);
+    assert_eq!(migrations[0]["version"], 1);
+    assert_eq!(migrations[0]["name"], "base");
+    assert_eq!(migrations[1]["name"], "email_verification");
+    assert_eq!(migrations[1]["checksum"].as_str().unwrap().len(), 64);
+
+    let tables: Vec<&str> = dump["tables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t.as_str().unwrap())
+        .collect();
+    assert_eq!(
+        tables,
+        vec![
+            "_authkit_migrations",
+            "accounts",
+            "sessions",
+            "users",
+            "verification"
+        ]
+    );
+}
+
+#[test]
+fn test_baseline_marks_migrations_applied() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "baseline",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--version",
+            "2",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Marked 2 migration(s) as applied"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Database is up to date"))
+        .stdout(predicate::str::contains("Pending").not());
+
+    // No SQL was executed
+    let users = query_scalar(
+        &db_url,
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'",
+    );
+    assert_eq!(users, 0);
+
+    // Refuses to run over existing rows without --force
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "baseline",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--version",
+            "2",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+}
+
+#[test]
+fn test_case_insensitive_email_blocks_on_duplicates() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(
+        &db_url,
+        "INSERT INTO users (id, email, created_at, updated_at) VALUES ('u1', 'A@x.com', 0, 0), ('u2', ' a@x.com', 0, 0), ('u3', 'b@x.com', 0, 0)",
+    );
+
+    let config = write_config(temp.path(), &["email_case_insensitive"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--report-duplicates",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("u1 \"A@x.com\""))
+        .stderr(predicate::str::contains("u2 \" a@x.com\""))
+        .stderr(predicate::str::contains("b@x.com").not());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("DuplicateEmails(2)"));
+
+    let recorded = query_scalar(
+        &db_url,
+        "SELECT COUNT(*) FROM _authkit_migrations WHERE name = 'email_case_insensitive'",
+    );
+    assert_eq!(recorded, 0);
+}
+
+#[test]
+fn test_transaction_mode_all_rolls_back_everything() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification", "two_factor"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    // A pre-existing backup_codes table without user_id makes the last migration fail
+    execute_sql(&db_url, "CREATE TABLE backup_codes (id TEXT PRIMARY KEY)");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--transaction-mode",
+            "all",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Rolled back all 3 pending migration(s)",
+        ));
+
+    let users = query_scalar(
+        &db_url,
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'",
+    );
+    assert_eq!(users, 0);
+    let recorded = query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations");
+    assert_eq!(recorded, 0);
+}
+
+#[test]
+fn test_generate_combined() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--combined",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created schema.up.sql"));
+
+    assert!(!sqlite_dir.join("001_base.up.sql").exists());
+
+    let up = std::fs::read_to_string(sqlite_dir.join("schema.up.sql")).unwrap();
+    assert!(up.contains("CREATE TABLE IF NOT EXISTS users"));
+    assert!(up.contains("email_verified"));
+    assert!(up.find("Feature: base").unwrap() < up.find("Feature: email_verification").unwrap());
+
+    let down = std::fs::read_to_string(sqlite_dir.join("schema.down.sql")).unwrap();
+    assert!(
+        down.find("Feature: email_verification").unwrap() < down.find("Feature: base").unwrap()
+    );
+
+    // Existing files are protected without --force
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--combined",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("FileExists"));
+}
+
+#[test]
+fn test_migrate_require_empty_aborts_on_unknown_tables() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    execute_sql(&db_url, "CREATE TABLE invoices (id INTEGER PRIMARY KEY)");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--require-empty",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("DatabaseNotEmpty(\"invoices\")"));
+
+    let users = query_scalar(
+        &db_url,
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'",
+    );
+    assert_eq!(users, 0);
+
+    // --allow-dirty overrides the gate
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--require-empty",
+            "--allow-dirty",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_generate_timestamp_naming_sorts_in_feature_order() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification", "two_factor"]);
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--naming",
+            "timestamp",
+        ])
+        .assert()
+        .success();
+
+    let mut up_files: Vec<String> = std::fs::read_dir(&sqlite_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".up.sql"))
+        .collect();
+    up_files.sort();
+
+    let names: Vec<&str> = up_files
+        .iter()
+        .map(|f| {
+            let (prefix, rest) = f.split_once('_').unwrap();
+            assert_eq!(prefix.len(), 14);
+            assert!(prefix.chars().all(|c| c.is_ascii_digit()));
+            rest.trim_end_matches(".up.sql")
+        })
+        .collect();
+    assert_eq!(names, vec!["base", "email_verification", "two_factor"]);
+}
+
+#[test]
+fn test_destroy_dry_run_keeps_tables() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- users (0 rows)"))
+        .stdout(predicate::str::contains("Dry run - no tables were dropped"));
+
+    let tables = query_scalar(
+        &db_url,
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('users', 'accounts', 'sessions', 'verification', '_authkit_migrations')",
+    );
+    assert_eq!(tables, 5);
+}
+
+#[test]
+fn test_migrate_reports_partial_application() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification", "two_factor"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let report_path = temp.path().join("report.json");
+
+    // An existing email_verified column makes the second migration fail;
+    // --allow-dirty lets the run start on the untracked users table
+    execute_sql(
+        &db_url,
+        "CREATE TABLE users (id TEXT PRIMARY KEY, email TEXT NOT NULL UNIQUE, name TEXT, created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL, email_verified INTEGER)",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--report",
+            report_path.to_str().unwrap(),
+            "--allow-dirty",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("001_base (applied)"))
+        .stdout(predicate::str::contains("002_email_verification (failed)"))
+        .stdout(predicate::str::contains("004_two_factor (not attempted)"));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["success"], false);
+    assert_eq!(report["applied"], serde_json::json!(["001_base"]));
+    assert_eq!(report["failed"]["migration"], "002_email_verification");
+    assert_eq!(
+        report["not_attempted"],
+        serde_json::json!(["004_two_factor"])
+    );
+}
+
+#[test]
+fn test_destroy_keep_feature() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification", "two_factor"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "destroy",
+            "--db-url",
+            &db_url,
+            "--force",
+            "--keep-feature",
+            "email_password",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- backup_codes"))
+        .stdout(predicate::str::contains(
+            "_authkit_migrations records for features",
+        ))
+        .stdout(predicate::str::contains("004_two_factor removed"))
+        .stdout(predicate::str::contains("002_email_verification kept"));
+
+    let table_count = |name: &str| {
+        query_scalar(
+            &db_url,
+            &format!(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '{}'",
+                name
+            ),
+        )
+    };
+    assert_eq!(table_count("users"), 1);
+    assert_eq!(table_count("accounts"), 1);
+    assert_eq!(table_count("_authkit_migrations"), 1);
+    assert_eq!(table_count("two_factor"), 0);
+    assert_eq!(table_count("backup_codes"), 0);
+
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM _authkit_migrations WHERE version = 4"
+        ),
+        0
+    );
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM _authkit_migrations WHERE version IN (1, 2)"
+        ),
+        2
+    );
+}
+
+#[test]
+fn test_connect_retries_unreachable_database() {
+    // Nothing listens on port 1, so every attempt fails with a connection error
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "status",
+            "--db-url",
+            "postgres://authkit@127.0.0.1:1/authkit",
+            "--connect-retries",
+            "2",
+            "--connect-timeout",
+            "10",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("retrying in 0.5s (attempt 1/2)"))
+        .stderr(predicate::str::contains("retrying in 1.0s (attempt 2/2)"));
+}
+
+#[test]
+fn test_pool_options_are_global() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    // Accepted before the subcommand name as well as after it
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "--max-connections",
+            "2",
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+        ])
+        .assert()
+        .success();
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["--max-connections", "0", "status", "--db-url", &db_url])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '0' for '--max-connections <N>'",
+        ));
+}
+
+#[test]
+fn test_destroy_confirm_db() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("auth.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // A mismatched name aborts without dropping anything
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--confirm-db", "prod.db"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cancelled"));
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'"
+        ),
+        1
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--confirm-db", "auth.db"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All AuthKit tables destroyed"));
+}
+
+#[test]
+fn test_generate_skip_unchanged_is_resumable() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+    let output = output_dir.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["generate", "--config", &config, "--output", output])
+        .assert()
+        .success();
+
+    // Simulate an interrupted run that never got to the last file
+    std::fs::remove_file(sqlite_dir.join("002_email_verification.down.sql")).unwrap();
+
+    let args = [
+        "generate",
+        "--config",
+        &config,
+        "--output",
+        output,
+        "--skip-unchanged",
+    ];
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(args)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Generated 1 of 4 migration files, 3 skipped as unchanged",
+        ));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(args)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Generated 0 of 4 migration files, 4 skipped as unchanged",
+        ));
+}
+
+#[test]
+fn test_generate_line_endings_and_bom() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+    let lf_dir = temp.path().join("lf");
+    let crlf_dir = temp.path().join("crlf");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["generate", "--config", &config])
+        .args(["--output", lf_dir.to_str().unwrap()])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--line-ending",
+            "crlf",
+            "--bom",
+        ])
+        .args(["--output", crlf_dir.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let lf = std::fs::read(lf_dir.join("sqlite").join("001_base.up.sql")).unwrap();
+    assert!(!lf.windows(2).any(|w| w == b"\r\n"));
+    assert!(!lf.starts_with(b"\xEF\xBB\xBF"));
+
+    let crlf = std::fs::read(crlf_dir.join("sqlite").join("001_base.up.sql")).unwrap();
+    assert!(crlf.windows(2).any(|w| w == b"\r\n"));
+    assert!(!crlf
+        .iter()
+        .enumerate()
+        .any(|(i, b)| *b == b'\n' && (i == 0 || crlf[i - 1] != b'\r')));
+    assert!(crlf.starts_with(b"\xEF\xBB\xBF"));
+}
+
+#[test]
+fn test_db_url_from_env_file() {
+    let temp = tempdir().unwrap();
+    write_config(temp.path(), &[]);
+    let db_path = temp.path().join("from_env.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    std::fs::write(
+        temp.path().join(".env"),
+        format!("AUTHKIT_DATABASE_URL={}\n", db_url),
+    )
+    .unwrap();
+
+    // ./.env is picked up from the working directory
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .current_dir(temp.path())
+        .env_remove("AUTHKIT_DATABASE_URL")
+        .arg("migrate")
+        .assert()
+        .success();
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        1
+    );
+
+    // --env-file points elsewhere, and an explicit --db-url still wins
+    let other_path = temp.path().join("explicit.db");
+    let other_url = format!("sqlite:{}?mode=rwc", other_path.display());
+    std::fs::write(
+        temp.path().join("custom.env"),
+        "AUTHKIT_DATABASE_URL=invalid://ignored\n",
+    )
+    .unwrap();
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .current_dir(temp.path())
+        .env_remove("AUTHKIT_DATABASE_URL")
+        .args(["migrate", "--db-url", &other_url, "--env-file"])
+        .arg(temp.path().join("custom.env"))
+        .assert()
+        .success();
+    assert!(other_path.exists());
+}
+
+#[test]
+fn test_migrate_waits_for_lock() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    // Simulate a concurrent run holding the lock
+    execute_sql(
+        &db_url,
+        "CREATE TABLE _authkit_migrations_lock (id INTEGER PRIMARY KEY, holder TEXT NOT NULL, locked_at BIGINT NOT NULL)",
+    );
+    execute_sql(
+        &db_url,
+        "INSERT INTO _authkit_migrations_lock (id, holder, locked_at) VALUES (1, 'pid 4242', CAST(strftime('%s', 'now') AS INTEGER))",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--lock-timeout",
+            "1",
+            "--config",
+            &config,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Waiting for another migration run",
+        ))
+        .stderr(predicate::str::contains("MigrationInProgress"))
+        .stderr(predicate::str::contains("pid 4242"));
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'"
+        ),
+        0
+    );
+
+    // Once released, migrate proceeds and releases the lock itself
+    execute_sql(&db_url, "DELETE FROM _authkit_migrations_lock");
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--lock-timeout",
+            "0",
+            "--config",
+            &config,
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations_lock"),
+        0
+    );
+}
+
+#[test]
+fn test_migrate_takes_over_stale_lock() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    // A row from a run that crashed long ago
+    execute_sql(
+        &db_url,
+        "CREATE TABLE _authkit_migrations_lock (id INTEGER PRIMARY KEY, holder TEXT NOT NULL, locked_at BIGINT NOT NULL)",
+    );
+    execute_sql(
+        &db_url,
+        "INSERT INTO _authkit_migrations_lock (id, holder, locked_at) VALUES (1, 'pid 4242', 0)",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--lock-timeout",
+            "0",
+            "--config",
+            &config,
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Taking over a stale migration lock held by pid 4242",
+        ));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations_lock"),
+        0
+    );
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'"
+        ),
+        1
+    );
+}
+
+#[test]
+fn test_destroy_respects_migration_lock() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // A migrate run is in progress
+    execute_sql(
+        &db_url,
+        "INSERT INTO _authkit_migrations_lock (id, holder, locked_at) VALUES (1, 'pid 4242', CAST(strftime('%s', 'now') AS INTEGER))",
+    );
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("MigrationInProgress"))
+        .stderr(predicate::str::contains("pid 4242"));
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'"
+        ),
+        1
+    );
+
+    // Once released, destroy drops everything, the lock table included
+    execute_sql(&db_url, "DELETE FROM _authkit_migrations_lock");
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success();
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'"
+        ),
+        0
+    );
+}
+
+#[test]
+fn test_schema_group_by_table() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+
+    let assert = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "schema",
+            "--config",
+            &config,
+            "--format",
+            "sql",
+            "--group-by-table",
+        ])
+        .assert()
+        .success();
+    let output = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+
+    // The users table definition ends right before its index
+    let users = output.find("CREATE TABLE IF NOT EXISTS users").unwrap();
+    let end = users + output[users..].find(");").unwrap() + 2;
+    assert!(output[end..]
+        .trim_start()
+        .starts_with("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);"));
+}
+
+#[test]
+fn test_redo_updates_checksum() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["two_factor", "email_verification"]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // Pretend two_factor was applied from an older version of its SQL
+    execute_sql(
+        &db_url,
+        "UPDATE _authkit_migrations SET checksum = 'stale' WHERE version = 4",
+    );
+    execute_sql(
+        &db_url,
+        "INSERT INTO users (id, email, created_at, updated_at) VALUES ('u1', 'a@example.com', 0, 0)",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["redo", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Redid 004_two_factor"));
+
+    // The record now carries the current checksum, which verify accepts
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM _authkit_migrations WHERE version = 4 AND checksum <> 'stale' AND LENGTH(checksum) = 64"
+        ),
+        1
+    );
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["verify", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // Other features and their data are untouched
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM users"), 1);
+}
+
+#[test]
+fn test_redo_base_refuses_with_users() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(
+        &db_url,
+        "INSERT INTO users (id, email, created_at, updated_at) VALUES ('u1', 'a@example.com', 0, 0)",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["redo", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("would delete 1 user(s)"));
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM users"), 1);
+}
+
+#[test]
+fn test_feature_set_hash() {
+    let temp = tempdir().unwrap();
+    let hash = |config: &str| {
+        let assert = Command::cargo_bin("authkit")
+            .unwrap()
+            .args(["feature-set-hash", "--config", config])
+            .assert()
+            .success();
+        String::from_utf8(assert.get_output().stdout.clone())
+            .unwrap()
+            .trim()
+            .to_string()
+    };
+
+    let config = write_config(temp.path(), &["email_verification"]);
+    let first = hash(&config);
+    assert_eq!(first.len(), 64);
+    assert_eq!(hash(&config), first);
+
+    let config = write_config(temp.path(), &["email_verification", "oauth"]);
+    assert_ne!(hash(&config), first);
+}
+
+#[test]
+fn test_clean_expired_removes_expired_sessions() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    execute_sql(
+        &db_url,
+        "INSERT INTO users (id, email, created_at, updated_at) VALUES ('u1', 'a@example.com', 0, 0)",
+    );
+    execute_sql(
+        &db_url,
+        "INSERT INTO sessions (id, user_id, token, expires_at, created_at) VALUES ('old', 'u1', 't1', 1, 0), ('live', 'u1', 't2', 32503680000, 0)",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["clean-expired", "--db-url", &db_url, "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sessions: 1 expired row(s)"));
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM sessions"), 2);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["clean-expired", "--db-url", &db_url])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sessions: 1 deleted row(s)"))
+        .stdout(predicate::str::contains("verification: 0 deleted row(s)"));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM sessions WHERE id = 'live'"),
+        1
+    );
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM sessions"), 1);
+}
+
+#[test]
+fn test_migrate_verbose_logs_statements() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--verbose",
+            "--config",
+            &config,
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Detected sqlite database"))
+        .stderr(predicate::str::contains(
+            "001_base statement 0: CREATE TABLE IF NOT EXISTS users",
+        ));
+
+    // Without --verbose nothing is logged
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("DEBUG").not());
+}
+
+#[test]
+fn test_status_junit_report() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let base_config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &base_config])
+        .assert()
+        .success();
+
+    let config = write_config(temp.path(), &["email_verification"]);
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "status", "--db-url", &db_url, "--config", &config, "--format", "junit",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let xml = String::from_utf8(output).unwrap();
+
+    assert!(xml.contains("<testsuite name=\"authkit.status\" tests=\"2\" failures=\"1\">"));
+    assert_eq!(xml.matches("<testcase ").count(), 2);
+    assert!(xml.contains("<testcase name=\"001_base\" classname=\"authkit.status\"/>"));
+    assert!(xml.contains("<failure message=\"pending: not yet applied\">"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "verify", "--db-url", &db_url, "--config", &config, "--format", "junit",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "<testsuite name=\"authkit.verify\" tests=\"1\" failures=\"0\">",
+        ));
+}
+
+#[test]
+fn test_login_tracking_adds_column_and_index() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["login_tracking"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pragma_table_info('users') WHERE name = 'last_login_at'"
+        ),
+        1
+    );
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_users_last_login_at'"
+        ),
+        1
+    );
+}
+
+#[test]
+fn test_migrate_plan_file_applies_reviewed_plan() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["oauth"]);
+    let plan_path = temp.path().join("plan.json");
+    let plan = plan_path.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--print-plan",
+            "--plan-out",
+            plan,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Migration plan (2 pending)"));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        0
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--plan-file",
+            plan,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Pending migrations match the plan",
+        ));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        2
+    );
+}
+
+#[test]
+fn test_migrate_plan_file_refuses_changed_checksum() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+    let plan_path = temp.path().join("plan.json");
+    let plan = plan_path.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--print-plan",
+            "--plan-out",
+            plan,
+        ])
+        .assert()
+        .success();
+
+    let mut json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&plan_path).unwrap()).unwrap();
+    json["migrations"][0]["checksum"] = "0".repeat(64).into();
+    std::fs::write(&plan_path, json.to_string()).unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--plan-file",
+            plan,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("PlanMismatch"));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        0
+    );
+}
+
+#[test]
+fn test_diff_reports_missing_column() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let base_config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &base_config])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["diff", "--db-url", &db_url, "--config", &base_config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("matches the enabled features"));
+
+    let config = write_config(temp.path(), &["email_verification"]);
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["diff", "--db-url", &db_url, "--config", &config, "--check"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "missing column users.email_verified",
+        ))
+        .stdout(predicate::str::contains(
+            "missing column users.email_verified_at",
+        ));
+
+    let assert = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "diff", "--db-url", &db_url, "--config", &config, "--format", "json",
+        ])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(json["missing_tables"], serde_json::json!([]));
+    assert_eq!(
+        json["missing_columns"][0],
+        serde_json::json!({"table": "users", "column": "email_verified"})
+    );
+}
+
+#[test]
+fn test_diff_check_fails_on_drift_with_json_report() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification", "audit_log"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    let args = [
+        "diff", "--db-url", &db_url, "--config", &config, "--format", "json", "--check",
+    ];
+    let assert = Command::cargo_bin("authkit")
+        .unwrap()
+        .args(args)
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(json["in_sync"], serde_json::json!(true));
+
+    // Drift: a feature table dropped by hand and an unexpected AuthKit table
+    execute_sql(&db_url, "DROP TABLE auth_audit_log");
+    execute_sql(&db_url, "CREATE TABLE two_factor (id TEXT)");
+
+    let assert = Command::cargo_bin("authkit")
+        .unwrap()
+        .args(args)
+        .assert()
+        .code(1);
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(json["in_sync"], serde_json::json!(false));
+    assert_eq!(
+        json["missing_tables"],
+        serde_json::json!(["auth_audit_log"])
+    );
+    assert_eq!(json["missing_columns"], serde_json::json!([]));
+    assert_eq!(json["extra_tables"], serde_json::json!(["two_factor"]));
+}
+
+#[test]
+fn test_migrate_runs_pre_and_post_hooks() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+    let pre = temp.path().join("pre.sql");
+    std::fs::write(
+        &pre,
+        "-- Runs before the features\nCREATE TABLE app_roles (name TEXT PRIMARY KEY);",
+    )
+    .unwrap();
+    let post = temp.path().join("post.sql");
+    std::fs::write(&post, "INSERT INTO app_roles (name) VALUES ('admin');").unwrap();
+
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[database]\ntype = \"sqlite\"\n\n[features]\nemail_password = true\n\n[hooks]\npre_migrate = [{:?}]\npost_migrate = [{:?}]\n",
+            pre.display().to_string(),
+            post.display().to_string()
+        ),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ran pre_migrate hook"))
+        .stdout(predicate::str::contains("Ran post_migrate hook"));
+
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM app_roles"), 1);
+    // Hooks are not tracked as migrations
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        1
+    );
+
+    // Nothing pending: the hooks don't run again
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ran pre_migrate hook").not());
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM app_roles"), 1);
+}
+
+#[test]
+fn test_migrate_applies_custom_migrations_after_features() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    let custom_dir = temp.path().join("custom_migrations");
+    std::fs::create_dir(&custom_dir).unwrap();
+    std::fs::write(
+        custom_dir.join("100_add_widgets.up.sql"),
+        "CREATE TABLE widgets (id TEXT PRIMARY KEY, owner_id TEXT REFERENCES users(id));",
+    )
+    .unwrap();
+    std::fs::write(
+        custom_dir.join("100_add_widgets.down.sql"),
+        "DROP TABLE widgets;",
+    )
+    .unwrap();
+
+    let mut content = std::fs::read_to_string(&config).unwrap();
+    content.push_str(&format!(
+        "\n[migrations]\ndir = \"{}\"\n",
+        custom_dir.display()
+    ));
+    std::fs::write(&config, content).unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 3 migration(s)"));
+
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT version FROM _authkit_migrations ORDER BY version DESC LIMIT 1"
+        ),
+        100
+    );
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM widgets"), 0);
+
+    // A custom migration may not reuse a built-in feature's version
+    std::fs::rename(
+        custom_dir.join("100_add_widgets.up.sql"),
+        custom_dir.join("002_add_widgets.up.sql"),
+    )
+    .unwrap();
+    std::fs::rename(
+        custom_dir.join("100_add_widgets.down.sql"),
+        custom_dir.join("002_add_widgets.down.sql"),
+    )
+    .unwrap();
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must use a version above"));
+}
+
+#[test]
+fn test_migrate_skip_feature() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification", "two_factor"]);
+
+    // two_factor depends on email_verification, so that skip alone is refused
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--skip-feature",
+            "email_verification",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "cannot skip email_verification: the enabled feature two_factor depends on it",
+        ));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--skip-feature",
+            "email_verification",
+            "--skip-feature",
+            "two_factor",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 1 migration(s)"));
+
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        1
+    );
+    assert_eq!(
+        query_scalar(&db_url, "SELECT version FROM _authkit_migrations"),
+        1
+    );
+}
+
+#[test]
+fn test_status_check_and_exit_zero() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "status", "--db-url", &db_url, "--config", &config, "--check",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("OutOfDate"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "status",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--check",
+            "--exit-zero",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 pending migration(s)"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "status", "--db-url", &db_url, "--config", &config, "--check",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_tracking_prune_removes_orphaned_rows() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // Version 0 was never used by a built-in feature
+    execute_sql(
+        &db_url,
+        "INSERT INTO _authkit_migrations (version, name, applied_at, checksum) VALUES (0, 'removed_feature', 0, 'x')",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["tracking", "prune", "--db-url", &db_url, "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("000_removed_feature"))
+        .stdout(predicate::str::contains("Dry run"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["tracking", "prune", "--db-url", &db_url, "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 orphaned row(s)"));
+
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        1
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["tracking", "prune", "--db-url", &db_url, "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No orphaned rows"));
+}
+
+#[test]
+fn test_memberships_reject_duplicate_membership() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["organizations"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // A database that applied 012_organizations as released still verifies
+    execute_sql(
+        &db_url,
+        "UPDATE _authkit_migrations SET checksum = 'f2b360b38c7048f65dc3bc5de325d031ff48ca9246819d97ceedcaad24a013cf' WHERE version = 12",
+    );
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["verify", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(
+        &db_url,
+        "INSERT INTO users (id, email, created_at, updated_at) VALUES ('u1', 'a@example.com', 0, 0)",
+    );
+    execute_sql(
+        &db_url,
+        "INSERT INTO organizations (id, name, created_at, updated_at) VALUES ('o1', 'Acme', 0, 0)",
+    );
+
+    // memberships adds slugs to the existing organizations
+    let config = write_config(temp.path(), &["organizations", "memberships"]);
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 1 migration(s)"));
+    execute_sql(
+        &db_url,
+        "UPDATE organizations SET slug = 'acme' WHERE id = 'o1'",
+    );
+    execute_sql(
+        &db_url,
+        "INSERT INTO memberships (id, org_id, user_id, created_at, updated_at) VALUES ('m1', 'o1', 'u1', 0, 0)",
+    );
+
+    let rejected = |sql: &'static str| {
+        let db_url = db_url.clone();
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(async move {
+                sqlx::any::install_default_drivers();
+                let pool = sqlx::AnyPool::connect(&db_url).await.unwrap();
+                let result = sqlx::query(sql).execute(&pool).await;
+                pool.close().await;
+                result.is_err()
+            })
+    };
+    assert!(rejected(
+        "INSERT INTO memberships (id, org_id, user_id, created_at, updated_at) VALUES ('m2', 'o1', 'u1', 0, 0)"
+    ));
+    assert!(rejected(
+        "INSERT INTO organizations (id, name, slug, created_at, updated_at) VALUES ('o2', 'Acme 2', 'acme', 0, 0)"
+    ));
+
+    // Rolling back memberships leaves the organizations table as v12 created it
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "rollback", "--db-url", &db_url, "--config", &config, "--force",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pragma_table_info('organizations') WHERE name = 'slug'"
+        ),
+        0
+    );
+}
+
+#[test]
+fn test_migrate_continue_on_error_reports_summary() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    let custom_dir = temp.path().join("custom_migrations");
+    std::fs::create_dir(&custom_dir).unwrap();
+    for (file, sql) in [
+        ("100_broken.up.sql", "CREATE TABLE broken (id TEXT;"),
+        ("100_broken.down.sql", "DROP TABLE broken;"),
+        (
+            "101_add_widgets.up.sql",
+            "CREATE TABLE widgets (id TEXT PRIMARY KEY);",
+        ),
+        ("101_add_widgets.down.sql", "DROP TABLE widgets;"),
+    ] {
+        std::fs::write(custom_dir.join(file), sql).unwrap();
+    }
+
+    let mut content = std::fs::read_to_string(&config).unwrap();
+    content.push_str(&format!(
+        "\n[migrations]\ndir = \"{}\"\n",
+        custom_dir.display()
+    ));
+    std::fs::write(&config, content).unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--continue-on-error",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("100_broken (failed)"))
+        .stdout(predicate::str::contains("101_add_widgets (applied)"))
+        .stderr(predicate::str::contains(
+            "1 of 3 pending migration(s) failed",
+        ));
+
+    // The failed migration is never recorded, the ones around it are
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM _authkit_migrations WHERE version = 100"
+        ),
+        0
+    );
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        2
+    );
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM widgets"), 0);
+}
+
+#[test]
+fn test_migrate_records_execution_time() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM _authkit_migrations WHERE execution_ms IS NULL OR execution_ms <= 0"
+        ),
+        0
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Duration"));
+}
+
+#[test]
+fn test_status_json_on_fresh_database() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "status", "--db-url", &db_url, "--config", &config, "--format", "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(status["schema_version"], 0);
+    assert_eq!(status["summary"]["pending"], 2);
+    assert_eq!(status["summary"]["applied"], 0);
+    assert_eq!(status["summary"]["missing"], 0);
+
+    let migrations = status["migrations"].as_array().unwrap();
+    assert_eq!(migrations.len(), 2);
+    assert_eq!(migrations[0]["name"], "base");
+    assert_eq!(migrations[0]["state"], "pending");
+    assert!(migrations[0]["applied_at"].is_null());
+}
+
+#[test]
+fn test_generate_rejects_unrelated_sql_files() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &[]);
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+    std::fs::create_dir_all(&sqlite_dir).unwrap();
+    std::fs::write(sqlite_dir.join("seed_data.sql"), "SELECT 1;").unwrap();
+    let output = output_dir.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["generate", "--config", &config, "--output", output])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("OutputNotEmpty"))
+        .stderr(predicate::str::contains("seed_data.sql"));
+    assert!(!sqlite_dir.join("001_base.up.sql").exists());
+
+    // --clean only removes AuthKit-named files, so the unrelated file still blocks
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate", "--config", &config, "--output", output, "--clean",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("OutputNotEmpty"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate", "--config", &config, "--output", output, "--force",
+        ])
+        .assert()
+        .success();
+    assert!(sqlite_dir.join("001_base.up.sql").exists());
+    assert!(sqlite_dir.join("seed_data.sql").exists());
+}
+
+#[test]
+fn test_generate_clean_removes_stale_feature_files() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+    let output = output_dir.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["generate", "--config", &config, "--output", output])
+        .assert()
+        .success();
+
+    // With email_verification disabled its files are stale
+    let config = write_config(temp.path(), &[]);
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output,
+            "--skip-unchanged",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("002_email_verification.down.sql"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output,
+            "--skip-unchanged",
+            "--clean",
+        ])
+        .assert()
+        .success();
+    assert!(!sqlite_dir.join("002_email_verification.up.sql").exists());
+    assert!(!sqlite_dir.join("002_email_verification.down.sql").exists());
+    assert!(sqlite_dir.join("001_base.up.sql").exists());
+}
+
+#[test]
+fn test_generate_stdout_writes_no_files() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let output_dir = temp.path().join("migrations");
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--stdout",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let markers: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("-- file: "))
+        .collect();
+    assert_eq!(
+        markers,
+        vec![
+            "-- file: 001_base.up.sql",
+            "-- file: 001_base.down.sql",
+            "-- file: 002_email_verification.up.sql",
+            "-- file: 002_email_verification.down.sql",
+        ]
+    );
+    assert!(stdout.contains("CREATE TABLE IF NOT EXISTS users"));
+    assert!(!output_dir.exists());
+}
+
+#[test]
+fn test_clean_expired_removes_expired_rate_limits() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["rate_limit"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    execute_sql(
+        &db_url,
+        "INSERT INTO rate_limits (id, key, count, window_start, expires_at) VALUES ('old', 'ip:/login', 5, 0, 1), ('live', 'user:reset', 1, 0, 32503680000)",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["clean-expired", "--db-url", &db_url])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rate_limits: 1 deleted row(s)"));
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM rate_limits WHERE id = 'live'"
+        ),
+        1
+    );
+}
+
+#[test]
+fn test_migrate_creates_sqlite_file_without_mode() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("fresh.db");
+    let db_url = format!("sqlite:{}", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    assert!(db_path.exists());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &format!("{}?mode=ro", db_url),
+            "--config",
+            &config,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("mode=ro"));
+}
+
+#[test]
+fn test_deleting_user_cascades_to_sessions() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        sqlx::any::install_default_drivers();
+        // One connection, so the PRAGMA applies to every statement below
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .connect(&db_url)
+            .await
+            .unwrap();
+        for sql in [
+            "PRAGMA foreign_keys = ON",
+            "INSERT INTO users (id, email, created_at, updated_at) VALUES ('u1', 'a@example.com', 0, 0)",
+            "INSERT INTO sessions (id, user_id, token, expires_at, created_at) VALUES ('s1', 'u1', 't1', 32503680000, 0)",
+            "DELETE FROM users WHERE id = 'u1'",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool.close().await;
+    });
+
+    assert_eq!(query_scalar(&db_url, "SELECT COUNT(*) FROM sessions"), 0);
+}
+
+#[test]
+fn test_soft_delete_migrates_and_rolls_back() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["soft_delete"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    for table in ["users", "accounts", "sessions"] {
+        assert_eq!(
+            query_scalar(
+                &db_url,
+                &format!(
+                    "SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = 'deleted_at'",
+                    table
+                )
+            ),
+            1
+        );
+    }
+
+    // The down migration must be able to drop the indexed columns again
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["redo", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_init_enables_requested_features() {
+    let temp = tempdir().unwrap();
+    let config = temp.path().join("authkit.toml");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "init",
+            "--db",
+            "sqlite",
+            "--output",
+            config.to_str().unwrap(),
+            "--features",
+            "email_verification",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("email_verification"));
+
+    let content = std::fs::read_to_string(&config).unwrap();
+    assert!(content.contains("email_verification = true"));
+    assert!(content.contains("oauth = false"));
+
+    // Unknown names are rejected without writing a config
+    let other = temp.path().join("other.toml");
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "init",
+            "--output",
+            other.to_str().unwrap(),
+            "--features",
+            "email_verification,magic_links",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown feature 'magic_links'"));
+    assert!(!other.exists());
+}
+
+#[test]
+fn test_init_merge_keeps_existing_settings() {
+    let temp = tempdir().unwrap();
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        "# Team config\n[database]\ntype = \"sqlite\"\n\n[features]\nemail_password = true\nemail_verification = true\n",
+    )
+    .unwrap();
+    let path = config.to_str().unwrap();
+
+    // Without --merge an existing config is refused
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["init", "--output", path, "--features", "oauth"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("FileExists"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["init", "--merge", "--output", path, "--features", "oauth"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Updated"));
+
+    let content = std::fs::read_to_string(&config).unwrap();
+    assert!(content.starts_with("# Team config\n"));
+    assert!(content.contains("type = \"sqlite\""));
+    assert!(content.contains("email_verification = true"));
+    assert!(content.contains("oauth = true"));
+
+    // Running it again changes nothing
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["init", "--merge", "--output", path, "--features", "oauth"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already up to date"));
+    assert_eq!(std::fs::read_to_string(&config).unwrap(), content);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["feature-set-hash", "--config", path])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_list_features() {
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .arg("list-features")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("email_password"))
+        .stdout(predicate::str::contains("email_verification"));
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["list-features", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let features: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let features = features.as_array().unwrap();
+    assert_eq!(features[0]["key"], "email_password");
+    assert_eq!(features[0]["base"], true);
+    let verification = features
+        .iter()
+        .find(|f| f["key"] == "email_verification")
+        .unwrap();
+    assert_eq!(verification["version"], 2);
+    assert_eq!(verification["requires"][0], "email_password");
+}
+
+#[test]
+fn test_schema_from_database_single_table() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "schema", "--db-url", &db_url, "--format", "json", "--table", "users",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let tables = schema["tables"].as_array().unwrap();
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0]["name"], "users");
+    assert!(tables[0]["create_sql"]
+        .as_str()
+        .unwrap()
+        .contains("CREATE TABLE"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["schema", "--db-url", &db_url, "--table", "widgets"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("table 'widgets' not found"));
+}
+
+#[test]
+fn test_repair_marks_versions_applied_and_unapplied() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "baseline",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--version",
+            "1",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 pending migration(s)"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "repair",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--mark-applied",
+            "2",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("002_email_verification"));
+
+    // No SQL ran: the column email_verification would add is still missing
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pragma_table_info('users') WHERE name = 'email_verified'"
+        ),
+        0
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Database is up to date"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "repair",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--mark-unapplied",
+            "2",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        1
+    );
+
+    // Versions outside the config's feature set are rejected
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "repair",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--mark-applied",
+            "3",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("version 3 is not an enabled"));
+}
+
+#[test]
+fn test_env_profile_selects_features() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        "[database]\ntype = \"sqlite\"\n\n[features]\nemail_password = true\n\n[env.staging.features]\nemail_verification = true\n",
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    // Without --env only the base features are planned
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Email Verification").not());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "--env", "staging", "migrate", "--db-url", &db_url, "--config", config,
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        2
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "status", "--db-url", &db_url, "--config", config, "--env", "prod",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "environment 'prod' is not defined",
+        ));
+}
+
+#[test]
+fn test_schema_against_shows_added_feature_sql() {
+    let old_dir = tempdir().unwrap();
+    let new_dir = tempdir().unwrap();
+    let old_config = write_config(old_dir.path(), &[]);
+    let new_config = write_config(new_dir.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["schema", "--config", &new_config, "--against", &old_config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "@@ 002_email_verification (added) @@",
+        ))
+        .stdout(predicate::str::contains(
+            "+ALTER TABLE users ADD COLUMN email_verified",
+        ))
+        .stdout(predicate::str::contains("001_base").not());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["schema", "--config", &new_config, "--against", &new_config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No differences"));
+}
+
+#[test]
+fn test_migrate_baseline_existing_records_untracked_tables() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    // Simulate a run that created the base tables but crashed before recording
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(&db_url, "DELETE FROM _authkit_migrations");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--dry-run",
+            "--allow-dirty",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "001_base is not tracked, but its tables and columns already exist",
+        ));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--baseline-existing",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("001_base (already exists)"))
+        .stdout(predicate::str::contains(
+            "002_email_verification (already exists)",
+        ));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already up to date"));
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM _authkit_migrations WHERE version = 1"
+        ),
+        1
+    );
+}
+
+#[test]
+fn test_sync_rolls_back_disabled_feature() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let verification_column =
+        "SELECT COUNT(*) FROM pragma_table_info('users') WHERE name = 'email_verified'";
+
+    let config = write_config(temp.path(), &["email_verification"]);
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["sync", "--db-url", &db_url, "--config", &config, "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 002_email_verification"));
+    assert_eq!(query_scalar(&db_url, verification_column), 1);
+
+    // Disable the feature again
+    let config = write_config(temp.path(), &[]);
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "sync",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- 002_email_verification"));
+    assert_eq!(query_scalar(&db_url, verification_column), 1);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["sync", "--db-url", &db_url, "--config", &config, "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Rolled back 002_email_verification",
+        ));
+    assert_eq!(query_scalar(&db_url, verification_column), 0);
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        1
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Database is up to date"));
+}
+
+#[test]
+fn test_init_print_example_writes_nothing() {
+    let temp = tempdir().unwrap();
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["init", "--db", "sqlite", "--print-example"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!temp.path().join("authkit.toml").exists());
+
+    // The printed example is a working config
+    let example = String::from_utf8(output.stdout).unwrap();
+    assert!(example.contains("type = \"sqlite\""));
+    assert!(example.contains("# Requires: email_password, email_verification\ntwo_factor = false"));
+    std::fs::write(temp.path().join("authkit.toml"), &example).unwrap();
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["generate", "--stdout"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-- file: 001_base.up.sql"));
+}
+
+#[test]
+fn test_migrate_print_plan_shows_statements() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--print-plan",
+            "--dry-run",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("    -- statement 0\n    CREATE TABLE IF NOT EXISTS users ("));
+
+    // Every statement starts with SQL, never with a comment
+    let lines: Vec<&str> = stdout.lines().collect();
+    let statements: Vec<&str> = lines
+        .windows(2)
+        .filter(|pair| pair[0].starts_with("    -- statement "))
+        .map(|pair| pair[1].trim())
+        .collect();
+    assert!(!statements.is_empty());
+    assert!(statements
+        .iter()
+        .all(|s| !s.is_empty() && !s.starts_with("--")));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        0
+    );
+}
+
+#[test]
+fn test_phone_migrates_and_rolls_back() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["phone"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pragma_table_info('users') WHERE name IN ('phone_number', 'phone_verified')"
+        ),
+        2
+    );
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'phone_verification'"
+        ),
+        1
+    );
+
+    // The down migration drops the indexed columns and the codes table again
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["redo", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_doctor_reports_version_and_applied_migrations() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["doctor", "--db-url", &db_url])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Server version: 3."))
+        .stdout(predicate::str::contains("_authkit_migrations exists"))
+        .stdout(predicate::str::contains("1 migration(s) applied"));
+
+    // A missing file is not created by the read-only check
+    let missing = temp.path().join("missing.db");
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "doctor",
+            "--db-url",
+            &format!("sqlite:{}", missing.display()),
+            "--connect-timeout",
+            "1",
+            "--connect-retries",
+            "0",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Connection failed"));
+    assert!(!missing.exists());
+}
+
+#[test]
+fn test_generate_single_feature() {
+    let temp = tempdir().unwrap();
+    let config = write_config(temp.path(), &["email_verification"]);
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+    std::fs::create_dir_all(&sqlite_dir).unwrap();
+    std::fs::write(sqlite_dir.join("001_base.up.sql"), "-- edited by hand\n").unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--feature",
+            "email_verification",
+        ])
+        .assert()
+        .success();
+
+    assert!(sqlite_dir.join("002_email_verification.up.sql").exists());
+    assert!(sqlite_dir.join("002_email_verification.down.sql").exists());
+    assert!(!sqlite_dir.join("001_base.down.sql").exists());
+    assert_eq!(
+        std::fs::read_to_string(sqlite_dir.join("001_base.up.sql")).unwrap(),
+        "-- edited by hand\n"
+    );
+
+    // Disabled and unknown features are rejected
+    for name in ["oauth", "magic_links"] {
+        Command::cargo_bin("authkit")
+            .unwrap()
+            .args([
+                "generate",
+                "--config",
+                &config,
+                "--output",
+                output_dir.to_str().unwrap(),
+                "--feature",
+                name,
+            ])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("FeatureNotEnabled"));
+    }
+}
+
+#[test]
+fn test_migrate_refuses_untracked_base_tables() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    // A users table from some other tool
+    execute_sql(
+        &db_url,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, login TEXT NOT NULL)",
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Refusing to migrate: users already exist(s)",
+        ))
+        .stderr(predicate::str::contains("UntrackedTables"));
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'accounts'"
+        ),
+        0
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--allow-dirty",
+        ])
+        .assert()
+        .stderr(predicate::str::contains("Refusing to migrate").not());
+}
+
+#[test]
+fn test_migrate_progress_json_events() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--progress-json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let events: Vec<serde_json::Value> = String::from_utf8(output.stderr)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let done: Vec<&serde_json::Value> = events.iter().filter(|e| e["event"] == "done").collect();
+    assert_eq!(done.len(), 2);
+    assert_eq!(done[0]["version"], 1);
+    assert_eq!(done[1]["version"], 2);
+    assert!(done[1]["elapsed_ms"].is_u64());
+    assert_eq!(events[0]["event"], "start");
+    assert_eq!(events[0]["name"], "base");
+}
+
+#[test]
+fn test_rollback_print_plan_shows_down_statements() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "rollback",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--dry-run",
+            "--print-plan",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("002_email_verification"))
+        .stdout(predicate::str::contains(
+            "ALTER TABLE users DROP COLUMN email_verified_at",
+        ))
+        .stdout(predicate::str::contains(
+            "ALTER TABLE users DROP COLUMN email_verified\n",
+        ))
+        .stdout(predicate::str::contains("-- Note").not());
+
+    // Nothing was rolled back
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        2
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--print-plan"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "DROP TABLE IF EXISTS `verification`;",
+        ))
+        .stdout(predicate::str::contains(
+            "DROP TABLE IF EXISTS `_authkit_migrations`;",
+        ));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        2
+    );
+}
+
+#[test]
+fn test_rollback_to_version() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+    let verification_column =
+        "SELECT COUNT(*) FROM pragma_table_info('users') WHERE name = 'email_verified'";
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "rollback",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--to",
+            "1",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  - 002_email_verification"))
+        .stdout(predicate::str::contains("001_base").not());
+    assert_eq!(query_scalar(&db_url, verification_column), 1);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "down", "--db-url", &db_url, "--config", &config, "--to", "1", "--force",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Rolled back 002_email_verification",
+        ));
+
+    assert_eq!(query_scalar(&db_url, verification_column), 0);
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        1
+    );
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'"
+        ),
+        1
+    );
+}
+
+#[test]
+fn test_destroy_reports_total_rows() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(
+        &db_url,
+        "INSERT INTO users (id, email, created_at, updated_at) VALUES ('u1', 'a@example.com', 0, 0), ('u2', 'b@example.com', 0, 0)",
+    );
+
+    // Two users plus the base migration's tracking row; the lock table is empty
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["destroy", "--db-url", &db_url, "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total rows to be deleted: 3"))
+        .stdout(predicate::str::contains(
+            "Destroyed 6 tables containing 3 rows",
+        ));
+}
+
+#[test]
+fn test_migrate_repair_recreates_dropped_table() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["soft_delete"]);
+    let sessions = "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'sessions'";
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(&db_url, "DROP TABLE sessions");
+
+    // Without --repair the tracking rows make the database look up to date
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already up to date"));
+    assert_eq!(query_scalar(&db_url, sessions), 0);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate", "--db-url", &db_url, "--config", &config, "--repair",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Repaired 001_base (recreated table sessions)",
+        ))
+        .stdout(predicate::str::contains(
+            "Repaired 014_soft_delete (recreated column sessions.deleted_at)",
+        ));
+
+    assert_eq!(query_scalar(&db_url, sessions), 1);
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pragma_table_info('sessions') WHERE name = 'deleted_at'"
+        ),
+        1
+    );
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        2
+    );
+}
+
+#[test]
+fn test_refresh_tokens_migrates_and_rolls_back() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["refresh_tokens"]);
+    let table =
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'refresh_tokens'";
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Refresh Token Rotation"));
+    assert_eq!(query_scalar(&db_url, table), 1);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "rollback", "--db-url", &db_url, "--config", &config, "--force",
+        ])
+        .assert()
+        .success();
+    assert_eq!(query_scalar(&db_url, table), 0);
+}
+
+#[test]
+fn test_schema_single_feature_json() {
+    let temp = tempdir().unwrap();
+    let missing = temp.path().join("missing.toml");
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "schema",
+            "--db",
+            "sqlite",
+            "--config",
+            missing.to_str().unwrap(),
+            "--feature",
+            "email_verification",
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let migrations = schema["migrations"].as_array().unwrap();
+    assert_eq!(migrations.len(), 1);
+    assert_eq!(migrations[0]["version"], 2);
+    assert_eq!(migrations[0]["name"], "email_verification");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["schema", "--db", "sqlite", "--feature", "sms"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown feature 'sms'"));
+}
+
+#[test]
+fn test_migrate_strict_fails_on_checksum_drift() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    execute_sql(
+        &db_url,
+        "UPDATE _authkit_migrations SET checksum = 'tampered' WHERE version = 1",
+    );
+
+    // Enable a feature so there is something pending that --strict must hold back
+    let config = write_config(temp.path(), &["email_verification"]);
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate", "--db-url", &db_url, "--config", &config, "--strict",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ChecksumMismatch"));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        1
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM _authkit_migrations"),
+        2
+    );
+}
+
+#[test]
+fn test_migrate_fake_records_without_running_sql() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            &config,
+            "--fake",
+            "--dry-run",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate", "--db-url", &db_url, "--config", &config, "--fake",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Faked 001_base"))
+        .stdout(predicate::str::contains("Faked 002_email_verification"))
+        .stdout(predicate::str::contains("Recorded 2 migration(s)"));
+
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'"
+        ),
+        0
+    );
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date"))
+        .stdout(predicate::str::contains("Applied"));
+}
+
+#[test]
+fn test_history_lists_applied_migrations_oldest_first() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["history", "--db-url", &db_url])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let base = stdout.find("base").unwrap();
+    let verification = stdout.find("email_verification").unwrap();
+    assert!(base < verification);
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "history", "--db-url", &db_url, "--format", "json", "--limit", "1",
+        ])
+        .output()
+        .unwrap();
+    let history: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let history = history.as_array().unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["name"], "email_verification");
+}
+
+#[test]
+fn test_migrate_refuses_database_type_mismatch() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        "[database]\ntype = \"postgres\"\n\n[features]\nemail_password = true\n",
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", config])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("DatabaseTypeMismatch"));
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM sqlite_master"),
+        0
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "migrate",
+            "--db-url",
+            &db_url,
+            "--config",
+            config,
+            "--allow-type-mismatch",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Database URL is sqlite but config specifies postgres",
+        ));
+}
+
+#[test]
+fn test_generate_squash_writes_single_migration() {
+    let temp = tempdir().unwrap();
+    let config = write_config(
+        temp.path(),
+        &["email_verification", "two_factor", "api_keys"],
+    );
+    let output_dir = temp.path().join("migrations");
+    let sqlite_dir = output_dir.join("sqlite");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "generate",
+            "--config",
+            &config,
+            "--output",
+            output_dir.to_str().unwrap(),
+            "--squash",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Squashed into 001_authkit (checksum: ",
+        ));
+
+    let mut files: Vec<String> = std::fs::read_dir(&sqlite_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["001_authkit.down.sql", "001_authkit.up.sql"]);
+
+    let up = std::fs::read_to_string(sqlite_dir.join("001_authkit.up.sql")).unwrap();
+    for table in [
+        "users",
+        "sessions",
+        "two_factor",
+        "backup_codes",
+        "api_keys",
+    ] {
+        assert!(up.contains(&format!("CREATE TABLE IF NOT EXISTS {}", table)));
+    }
+    assert!(up.contains("ADD COLUMN email_verified"));
+}
+
+#[test]
+fn test_config_path_from_env() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["username"]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .env("AUTHKIT_CONFIG", &config)
+        .args(["status", "--db-url", &db_url])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Configuration: {}",
+            config
+        )))
+        .stdout(predicate::str::contains("Username Sign-In"));
+
+    // An explicit --config still wins
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .env("AUTHKIT_CONFIG", &config)
+        .args(["status", "--db-url", &db_url, "--config", "missing.toml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing.toml"));
+}
+
+#[test]
+fn test_table_prefix_namespaces_tables() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = temp.path().join("authkit.toml");
+    std::fs::write(
+        &config,
+        "[database]\ntype = \"sqlite\"\ntable_prefix = \"auth_\"\n\n[features]\nemail_password = true\nemail_verification = true\n",
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    // An app table named like an AuthKit one is left alone
+    execute_sql(&db_url, "CREATE TABLE users (id INTEGER PRIMARY KEY)");
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", config])
+        .assert()
+        .success();
+
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM pragma_table_info('auth_users') WHERE name = 'email_verified'"
+        ),
+        1
+    );
+    assert_eq!(
+        query_scalar(
+            &db_url,
+            "SELECT COUNT(*) FROM sqlite_master WHERE name = 'idx_users_email'"
+        ),
+        0
+    );
+    assert_eq!(
+        query_scalar(&db_url, "SELECT COUNT(*) FROM pragma_table_info('users')"),
+        1
+    );
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["status", "--db-url", &db_url, "--config", config, "--check"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "destroy",
+            "--db-url",
+            &db_url,
+            "--table-prefix",
+            "auth_",
+            "--print-plan",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "DROP TABLE IF EXISTS `auth_users`;",
+        ))
+        .stdout(predicate::str::contains("DROP TABLE IF EXISTS `users`;").not());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "destroy",
+            "--db-url",
+            &db_url,
+            "--table-prefix",
+            "Auth-",
+            "--force",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid table prefix 'Auth-'"));
+}
+
+#[test]
+fn test_migrate_quiet_prints_only_result_line() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &["email_verification"]);
+
+    let output = Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "-q", "--db-url", &db_url, "--config", &config])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().collect::<Vec<_>>().len(), 1, "{}", stdout);
+    assert!(stdout.contains("Applied 2 migration(s)"));
+    assert!(output.stderr.is_empty());
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args([
+            "--quiet", "migrate", "--db-url", &db_url, "--config", &config,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Database is already up to date"))
+        .stdout(predicate::str::contains("Enabled features").not());
+}
+
+#[test]
+fn test_status_and_history_csv() {
+    let temp = tempdir().unwrap();
+    let db_path = temp.path().join("test.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let config = write_config(temp.path(), &[]);
+
+    Command::cargo_bin("authkit")
+        .unwrap()
+        .args(["migrate", "--db-url", &db_url, "--config", &config])
+        .assert()
+        .success();
+
+    // Enable a feature afterwards so status has a pending row too
+    let config = write_config(temp.path(), &["oauth"]);
+
+    let parse = |args: &[&str]| -> Vec<Vec<String>> {
+        let output = Command::cargo_bin("authkit")
+            .unwrap()
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| line.split(',').map(str::to_string).collect())
+            .collect()
+    };
+
+    let status = parse(&[
+        "status", "--db-url", &db_url, "--config", &config, "--format", "csv",
+    ]);
+    assert_eq!(status[0], ["version", "name", "status", "applied_at"]);
+    assert_eq!(status.len(), 3);
+    assert!(status.iter().all(|row| row.len() == 4));
+    assert_eq!(status[1][..3], ["1", "base", "applied"]);
+    assert!(status[1][3].ends_with('Z'));
+    assert_eq!(status[2], ["3", "oauth", "pending", ""]);
+
+    let history = parse(&["history", "--db-url", &db_url, "--format", "csv"]);
+    assert_eq!(history[0], ["version", "name", "status", "applied_at"]);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1][..3], ["1", "base", "applied"]);
+    assert_eq!(history[1][3], status[1][3]);
+}
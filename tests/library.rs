@@ -0,0 +1,149 @@
+//! Uses the library API directly, without the CLI
+
+use authkit_cli::{migrate, AuthKitConfig, DatabaseType, Feature, MigrationRunner};
+use sqlx::any::AnyPoolOptions;
+
+#[tokio::test]
+async fn test_migrate_in_memory_sqlite() {
+    sqlx::any::install_default_drivers();
+    // Every connection to :memory: is a new database, so keep exactly one open
+    let pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+
+    let mut config = AuthKitConfig::default_config(DatabaseType::Sqlite);
+    config.features.set(Feature::EmailVerification, true);
+
+    let applied = migrate(&pool, &config).await.unwrap();
+    assert_eq!(applied, ["001_base", "002_email_verification"]);
+
+    let users: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'users'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(users, 1);
+
+    // A second run finds nothing pending
+    assert!(migrate(&pool, &config).await.unwrap().is_empty());
+    let tracked: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM _authkit_migrations")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(tracked, 2);
+}
+
+#[tokio::test]
+async fn test_runner_run_pending_in_memory_sqlite() {
+    sqlx::any::install_default_drivers();
+    let pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+
+    let mut config = AuthKitConfig::default_config(DatabaseType::Sqlite);
+    config.features.set(Feature::EmailVerification, true);
+
+    let runner = MigrationRunner::new(&pool, DatabaseType::Sqlite);
+    let applied = runner.run_pending(&config).await.unwrap();
+    assert_eq!(applied, ["base", "email_verification"]);
+    assert!(runner.run_pending(&config).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+#[ignore] // Run with: TEST_POSTGRES_URL=... cargo test --test library -- --ignored
+async fn test_runner_run_pending_waits_for_lock_postgres() {
+    let Ok(db_url) = std::env::var("TEST_POSTGRES_URL") else {
+        eprintln!("Skipping: TEST_POSTGRES_URL not set");
+        return;
+    };
+    sqlx::any::install_default_drivers();
+    let pool = sqlx::AnyPool::connect(&db_url).await.unwrap();
+    for table in [
+        "verification",
+        "sessions",
+        "accounts",
+        "users",
+        "_authkit_migrations",
+    ] {
+        sqlx::query(&format!("DROP TABLE IF EXISTS {} CASCADE", table))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    // Hold the advisory lock `authkit migrate` uses ("authkit" in ASCII)
+    let holder = sqlx::AnyPool::connect(&db_url).await.unwrap();
+    let mut holder = holder.acquire().await.unwrap();
+    sqlx::query("SELECT pg_advisory_lock(27432215569459572)")
+        .execute(&mut *holder)
+        .await
+        .unwrap();
+
+    let config = AuthKitConfig::default_config(DatabaseType::Postgres);
+    let run = {
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            MigrationRunner::new(&pool, DatabaseType::Postgres)
+                .run_pending(&config)
+                .await
+        })
+    };
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert!(!run.is_finished());
+
+    sqlx::query("SELECT pg_advisory_unlock(27432215569459572)")
+        .execute(&mut *holder)
+        .await
+        .unwrap();
+    assert_eq!(run.await.unwrap().unwrap(), ["base"]);
+}
+
+#[tokio::test]
+#[ignore] // Run with: TEST_POSTGRES_URL=... cargo test --test library -- --ignored
+async fn test_migrate_into_config_schema_postgres() {
+    let Ok(db_url) = std::env::var("TEST_POSTGRES_URL") else {
+        eprintln!("Skipping: TEST_POSTGRES_URL not set");
+        return;
+    };
+    sqlx::any::install_default_drivers();
+    let pool = sqlx::AnyPool::connect(&db_url).await.unwrap();
+    sqlx::query("DROP SCHEMA IF EXISTS authkit_embedded CASCADE")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut config = AuthKitConfig::default_config(DatabaseType::Postgres);
+    config.database.schema = Some("authkit_embedded".to_string());
+
+    let applied = migrate(&pool, &config).await.unwrap();
+    assert_eq!(applied, ["001_base"]);
+
+    let tables: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pg_tables WHERE schemaname = 'authkit_embedded' AND tablename IN ('users', '_authkit_migrations')",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(tables, 2);
+
+    // The embedder's own connections keep their search_path
+    let search_path: String = sqlx::query_scalar("SELECT current_setting('search_path')")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(!search_path.contains("authkit_embedded"));
+
+    sqlx::query("DROP SCHEMA authkit_embedded CASCADE")
+        .execute(&pool)
+        .await
+        .unwrap();
+}